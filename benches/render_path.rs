@@ -0,0 +1,71 @@
+// Copyright 2023 Simon Varey - github.com/simonvarey
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Benchmarks of drawing bitmaps to a 320x240 screen with each render path
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use simulate_lcd::{Bitmap, LcdScreen, RenderPath};
+
+const ROWS: usize = 240;
+const COLS: usize = 320;
+
+// Creates a bitmap with every dot set at random.
+fn random_bitmap(rng: &mut StdRng) -> Box<Bitmap<COLS, ROWS>> {
+    let rows: Vec<[bool; COLS]> = (0..ROWS).map(|_| rng.gen()).collect();
+    rows.into_boxed_slice().try_into().unwrap()
+}
+
+fn render_paths(c: &mut Criterion) {
+    let sdl_context = sdl2::init().unwrap();
+    let mut rng = StdRng::seed_from_u64(240);
+    let frames = [random_bitmap(&mut rng), random_bitmap(&mut rng)];
+    let blank: Box<Bitmap<COLS, ROWS>> = vec![[false; COLS]; ROWS]
+        .into_boxed_slice()
+        .try_into()
+        .unwrap();
+
+    let mut group = c.benchmark_group("draw_bitmap");
+    for path in [RenderPath::Rects, RenderPath::Texture] {
+        let mut screen = LcdScreen::<ROWS, COLS>::builder()
+            .title("LCD Benchmark")
+            .dot_size(2, 2)
+            .render_path(path)
+            .build(&sdl_context)
+            .unwrap();
+
+        // Every frame changes about half of the dots
+        let mut frame = 0;
+        group.bench_function(BenchmarkId::new("full_frame", format!("{path:?}")), |b| {
+            b.iter(|| {
+                frame ^= 1;
+                screen.draw_bitmap(frames[frame].as_ref()).unwrap();
+            })
+        });
+
+        // Every frame changes a single dot
+        screen.draw_bitmap(blank.as_ref()).unwrap();
+        let mut sparse = blank.clone();
+        group.bench_function(BenchmarkId::new("one_dot", format!("{path:?}")), |b| {
+            b.iter(|| {
+                sparse[ROWS / 2][COLS / 2] ^= true;
+                screen.draw_bitmap(sparse.as_ref()).unwrap();
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, render_paths);
+criterion_main!(benches);
@@ -0,0 +1,71 @@
+// * Simulate LCD: A Simple LCD Screen Simulator *
+
+// Copyright 2023 Simon Varey - github.com/simonvarey
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! An example of the async API, which needs the `async` feature: a task sends frames of a growing bar
+//! through a tokio channel, while the main task plays them and watches for the window being closed.
+
+use std::time::Duration;
+
+use sdl2::{event::Event, keyboard::Keycode};
+use simulate_lcd::{Bitmap, EventStream, LcdScreenBuilder};
+use tokio::{sync::mpsc, time::interval};
+use tokio_stream::{wrappers::ReceiverStream, StreamExt};
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() {
+    let sdl_context = sdl2::init().unwrap();
+    let mut screen = LcdScreenBuilder::<16, 64>::new()
+        .title("LCD Example: Async Frames")
+        .dot_size(10, 10)
+        .build(&sdl_context)
+        .unwrap();
+    let mut events = EventStream::new(&sdl_context, Duration::from_millis(5)).unwrap();
+
+    let (frames, receiver) = mpsc::channel::<Bitmap<64, 16>>(2);
+    tokio::spawn(async move {
+        let mut ticks = interval(Duration::from_millis(50));
+        for length in (0..=64).cycle() {
+            ticks.tick().await;
+            let bm = [[false; 64]; 16].map(|mut row| {
+                row[..length].fill(true);
+                row
+            });
+            if frames.send(bm).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    let quit = async {
+        while let Some(event) = events.next().await {
+            match event {
+                Event::Quit { .. }
+                | Event::KeyDown {
+                    keycode: Some(Keycode::Escape),
+                    ..
+                } => break,
+                _ => {}
+            }
+        }
+    };
+
+    tokio::select! {
+        result = screen.play(ReceiverStream::new(receiver)) => {
+            result.unwrap();
+        }
+        _ = quit => {}
+    }
+}
@@ -0,0 +1,73 @@
+// Copyright 2023 Simon Varey - github.com/simonvarey
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! An example of a screen which dims its backlight after a period without key presses, and switches
+//! it off after a longer period. Press P to toggle the polarity of the screen.
+use std::time::{Duration, Instant};
+
+use sdl2::{event::Event, keyboard::Keycode};
+use simulate_lcd::{Bitmap, Control, LcdScreen, Polarity, Runner, LCD_DARK_GREEN, LCD_LIGHT_GREEN};
+
+const DIM_AFTER: Duration = Duration::from_secs(3);
+const OFF_AFTER: Duration = Duration::from_secs(8);
+
+fn main() {
+    let sdl_context = sdl2::init().unwrap();
+    let mut screen = LcdScreen::<32, 32>::new(
+        &sdl_context,
+        "LCD Example: Backlight",
+        LCD_DARK_GREEN,
+        LCD_LIGHT_GREEN,
+        10,
+        10,
+    )
+    .unwrap();
+
+    let mut bm: Bitmap<32, 32> = [[false; 32]; 32];
+    for (nrow, row) in bm.iter_mut().enumerate() {
+        for (ncol, cell) in row.iter_mut().enumerate() {
+            *cell = (8..24).contains(&nrow) && (8..24).contains(&ncol);
+        }
+    }
+    screen.draw_bitmap(&bm).unwrap();
+
+    let mut last_input = Instant::now();
+    let mut runner = Runner::new(&sdl_context, 60).unwrap();
+    runner
+        .run(|tick| {
+            for event in tick.events {
+                if let Event::KeyDown { keycode, .. } = event {
+                    if *keycode == Some(Keycode::P) {
+                        let polarity = match screen.polarity() {
+                            Polarity::Positive => Polarity::Negative,
+                            Polarity::Negative => Polarity::Positive,
+                        };
+                        screen.set_polarity(polarity)?;
+                    }
+                    last_input = Instant::now();
+                    screen.set_backlight(true)?;
+                    screen.set_brightness(1.0)?;
+                }
+            }
+
+            let idle = last_input.elapsed();
+            if idle > OFF_AFTER && screen.backlight() {
+                screen.set_backlight(false)?;
+            } else if idle > DIM_AFTER && idle <= OFF_AFTER && screen.brightness() == 1.0 {
+                screen.set_brightness(0.3)?;
+            }
+            Ok(Control::Continue)
+        })
+        .unwrap();
+}
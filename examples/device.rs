@@ -0,0 +1,94 @@
+// * Simulate LCD: A Simple LCD Screen Simulator *
+
+// Copyright 2023 Simon Varey - github.com/simonvarey
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! An example of a device mockup: a screen showing a level bar, with a knob which sets the level, a
+//! button which clears it, and an LED which lights while the level is at its maximum
+use sdl2::{
+    event::{Event, WindowEvent},
+    keyboard::Keycode,
+    pixels::Color,
+};
+use simulate_lcd::{
+    Bitmap, Button, Control, DevicePanel, Encoder, LcdScreenBuilder, Led, PanelEvent, Runner,
+};
+
+const COLS: usize = 64;
+
+fn main() {
+    let sdl_context = sdl2::init().unwrap();
+    let mut panel = DevicePanel::new(&sdl_context, "LCD Example: Device", 420, 260).unwrap();
+    panel.set_face_color(Color::RGB(40, 44, 52));
+
+    let mut screen = LcdScreenBuilder::<16, COLS>::new()
+        .dot_size(5, 5)
+        .gap(1)
+        .build_in(panel.window(), 18, 30)
+        .unwrap();
+    let clear = panel.add_button(
+        Button::new(30, 170, 80, 40)
+            .rounded(10)
+            .key(Keycode::Backspace),
+    );
+    let knob = panel.add_encoder(
+        Encoder::new(300, 160, 60)
+            .detents(COLS as u32)
+            .keys(Keycode::Left, Keycode::Right),
+    );
+    let full =
+        panel.add_led(Led::new(380, 8, 14).colors(Color::RGB(40, 230, 60), Color::RGB(20, 60, 25)));
+
+    let mut level: i32 = 0;
+    panel.clear().unwrap();
+    panel.present().unwrap();
+
+    let mut runner = Runner::new(&sdl_context, 60).unwrap();
+    runner
+        .run(|tick| {
+            for event in tick.events {
+                if panel.handle_event(event) {
+                    continue;
+                }
+                if let Event::Window {
+                    win_event: WindowEvent::Exposed,
+                    ..
+                } = event
+                {
+                    panel.clear()?;
+                    screen.repaint()?;
+                }
+            }
+
+            while let Some(event) = panel.poll_event() {
+                match event {
+                    PanelEvent::EncoderTurned { encoder, steps } if encoder == knob => {
+                        level = (level + steps).clamp(0, COLS as i32);
+                    }
+                    PanelEvent::ButtonPressed(button) if button == clear => level = 0,
+                    _ => {}
+                }
+            }
+            panel.set_led(full, level == COLS as i32);
+
+            let bm: Bitmap<COLS, 16> = [[false; COLS]; 16].map(|mut row| {
+                row[..level as usize].fill(true);
+                row
+            });
+            screen.draw_bitmap(&bm)?;
+            panel.present()?;
+            Ok(Control::Continue)
+        })
+        .unwrap();
+}
@@ -13,11 +13,8 @@
 // limitations under the License.
 
 //! An example of generating a screen which plays Conway's Game of Life
-use std::{thread::sleep, time::Duration};
-
 use rand::{thread_rng, Rng};
-use sdl2::{event::Event, keyboard::Keycode};
-use simulate_lcd::{Bitmap, LcdScreen, LCD_DARK_GREEN, LCD_LIGHT_GREEN};
+use simulate_lcd::{Bitmap, Control, LcdScreen, Runner, LCD_DARK_GREEN, LCD_LIGHT_GREEN};
 
 fn random_bitmap<const C: usize, const R: usize>() -> Bitmap<C, R> {
     let mut rng = thread_rng();
@@ -89,24 +86,12 @@ fn main() {
 
     let mut bm = random_bitmap();
 
-    let mut event_pump = sdl_context.event_pump().unwrap();
-    'running: loop {
-        for event in event_pump.poll_iter() {
-            match event {
-                // Quit
-                Event::Quit { .. }
-                | Event::KeyDown {
-                    keycode: Some(Keycode::Escape),
-                    ..
-                } => break 'running,
-                _ => {}
-            }
-        }
-
-        screen.draw_bitmap(&bm).unwrap();
-
-        bm = next_state(bm);
-
-        sleep(Duration::new(0, 1_000_000_000u32 / 60));
-    }
+    let mut runner = Runner::new(&sdl_context, 60).unwrap();
+    runner
+        .run(|_| {
+            screen.draw_bitmap(&bm)?;
+            bm = next_state(bm);
+            Ok(Control::Continue)
+        })
+        .unwrap();
 }
@@ -0,0 +1,59 @@
+// * Simulate LCD: A Simple LCD Screen Simulator *
+
+// Copyright 2023 Simon Varey - github.com/simonvarey
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! An example of firmware-style keypad scanning: each key of a 4x4 keypad lights a block of the screen
+//! while it is held. Use the number keys, A to D, and the keypad * and / keys.
+use simulate_lcd::{Bitmap, Control, Keypad, LcdScreen, Runner, LCD_DARK_GREEN, LCD_LIGHT_GREEN};
+
+// Scans the keypad the way firmware would, one row at a time.
+fn scan(keypad: &mut Keypad<4, 4>) -> [[bool; 4]; 4] {
+    let mut keys = [[false; 4]; 4];
+    for (row, row_keys) in keys.iter_mut().enumerate() {
+        keypad.drive_row(row);
+        *row_keys = keypad.read_columns();
+    }
+    keypad.release_rows();
+    keys
+}
+
+fn main() {
+    let sdl_context = sdl2::init().unwrap();
+    let mut screen = LcdScreen::<16, 16>::new(
+        &sdl_context,
+        "LCD Example: Keypad",
+        LCD_DARK_GREEN,
+        LCD_LIGHT_GREEN,
+        20,
+        20,
+    )
+    .unwrap();
+    let mut keypad = Keypad::standard();
+
+    let mut runner = Runner::new(&sdl_context, 60).unwrap();
+    runner
+        .run(|tick| {
+            for event in tick.events {
+                keypad.handle_event(event);
+            }
+
+            let keys = scan(&mut keypad);
+            let bm: Bitmap<16, 16> =
+                std::array::from_fn(|row| std::array::from_fn(|col| keys[row / 4][col / 4]));
+            screen.draw_bitmap(&bm)?;
+            Ok(Control::Continue)
+        })
+        .unwrap();
+}
@@ -13,12 +13,10 @@
 // limitations under the License.
 
 //! An example of generating a screen with random patterns
-use std::{thread::sleep, time::Duration};
-
 use rand::{thread_rng, Rng};
-use sdl2::{event::Event, keyboard::Keycode, pixels::Color};
+use sdl2::pixels::Color;
 
-use simulate_lcd::LcdScreen;
+use simulate_lcd::{Control, LcdScreen, Runner};
 
 fn main() {
     let sdl_context = sdl2::init().unwrap();
@@ -32,25 +30,13 @@ fn main() {
     )
     .unwrap();
 
-    let mut event_pump = sdl_context.event_pump().unwrap();
-    'running: loop {
-        for event in event_pump.poll_iter() {
-            match event {
-                Event::Quit { .. }
-                | Event::KeyDown {
-                    keycode: Some(Keycode::Escape),
-                    ..
-                } => break 'running,
-                _ => {}
-            }
-        }
-
-        let mut rng = thread_rng();
-        let random_bits: Vec<[bool; 50]> = (0..15).map(|_| rng.gen()).collect();
-        screen
-            .draw_bitmap(&random_bits.try_into().unwrap())
-            .unwrap();
-
-        sleep(Duration::new(0, 1_000_000_000u32 / 60));
-    }
+    let mut rng = thread_rng();
+    let mut runner = Runner::new(&sdl_context, 60).unwrap();
+    runner
+        .run(|_| {
+            let random_bits: Vec<[bool; 50]> = (0..15).map(|_| rng.gen()).collect();
+            screen.draw_bitmap(&random_bits.try_into().unwrap())?;
+            Ok(Control::Continue)
+        })
+        .unwrap();
 }
@@ -0,0 +1,39 @@
+// * Simulate LCD: A Simple LCD Screen Simulator *
+
+// Copyright 2023 Simon Varey - github.com/simonvarey
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! An example of drawing to a screen from another process, which needs the `server` feature. Clients
+//! connect to port 7878 and draw with the protocol described in the documentation of `FrameServer`.
+use simulate_lcd::{Control, Delivery, FrameServer, LcdScreenBuilder, Runner};
+
+fn main() {
+    let sdl_context = sdl2::init().unwrap();
+    let mut screen = LcdScreenBuilder::<64, 128>::new()
+        .title("LCD Example: Server")
+        .dot_size(5, 5)
+        .build(&sdl_context)
+        .unwrap();
+    let sender = screen.frame_channel(8, Delivery::Queued);
+    let server = FrameServer::tcp("127.0.0.1:7878", sender).unwrap();
+    println!("Listening on {}", server.local_addr().unwrap());
+
+    let mut runner = Runner::new(&sdl_context, 60).unwrap();
+    runner
+        .run(|_| {
+            screen.drain()?;
+            Ok(Control::Continue)
+        })
+        .unwrap();
+}
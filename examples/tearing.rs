@@ -0,0 +1,72 @@
+// * Simulate LCD: A Simple LCD Screen Simulator *
+
+// Copyright 2023 Simon Varey - github.com/simonvarey
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! An example of display tearing: a bar moves across a slowly refreshing screen. Frames are drawn
+//! whenever they are ready, which tears the bar, until T is pressed to wait for the TE signal instead.
+use std::{cell::Cell, rc::Rc};
+
+use sdl2::{event::Event, keyboard::Keycode};
+use simulate_lcd::{Bitmap, Control, LcdScreenBuilder, Refresh, Runner};
+
+fn main() {
+    let sdl_context = sdl2::init().unwrap();
+    let mut screen = LcdScreenBuilder::<32, 64>::new()
+        .title("LCD Example: Tearing")
+        .dot_size(8, 8)
+        .refresh(Refresh {
+            rate: 5,
+            ..Default::default()
+        })
+        .build(&sdl_context)
+        .unwrap();
+
+    let te = Rc::new(Cell::new(false));
+    let te_flag = te.clone();
+    screen.on_tearing_effect(move || te_flag.set(true));
+
+    let mut wait_for_te = false;
+    let mut position = 0;
+    let mut runner = Runner::new(&sdl_context, 120).unwrap();
+    runner
+        .run(|tick| {
+            for event in tick.events {
+                if let Event::KeyDown {
+                    keycode: Some(Keycode::T),
+                    ..
+                } = event
+                {
+                    wait_for_te = !wait_for_te;
+                }
+            }
+
+            screen.update()?;
+            let frame_ready = if wait_for_te {
+                te.replace(false)
+            } else {
+                tick.number % 7 == 0
+            };
+            if frame_ready {
+                position = (position + 4) % 64;
+                let bm: Bitmap<64, 32> = [[false; 64]; 32].map(|mut row| {
+                    row[position..(position + 4).min(64)].fill(true);
+                    row
+                });
+                screen.draw_bitmap(&bm)?;
+            }
+            Ok(Control::Continue)
+        })
+        .unwrap();
+}
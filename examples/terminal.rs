@@ -0,0 +1,59 @@
+// * Simulate LCD: A Simple LCD Screen Simulator *
+
+// Copyright 2023 Simon Varey - github.com/simonvarey
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! An example of drawing to a terminal instead of a window: a scrolling sine wave, drawn with
+//! half-blocks, or with Braille patterns when run with the argument `braille`.
+use std::{env, f32::consts::TAU, thread, time::Duration};
+
+use simulate_lcd::{Bitmap, DotMatrix, Glyphs, LcdError, LcdTheme, TerminalColors, TerminalScreen};
+
+const ROWS: usize = 32;
+const COLS: usize = 64;
+
+// Draws one frame of the wave, to any kind of screen
+fn draw_wave<const R: usize, const C: usize>(
+    screen: &mut impl DotMatrix<R, C>,
+    phase: f32,
+) -> Result<(), LcdError> {
+    let wave: Vec<usize> = (0..C)
+        .map(|col| {
+            let height = ((col as f32 / C as f32 * TAU + phase).sin() + 1.0) / 2.0;
+            (height * (R - 1) as f32).round() as usize
+        })
+        .collect();
+    let mut bm: Bitmap<C, R> = [[false; C]; R];
+    for (row, row_bm) in bm.iter_mut().enumerate() {
+        for (col, dot) in row_bm.iter_mut().enumerate() {
+            // Mark the axis every 4 dots, under the wave
+            *dot = wave[col] == row || (row == R / 2 && col % 4 == 0);
+        }
+    }
+    screen.draw_bitmap(&bm)
+}
+
+fn main() {
+    let glyphs = match env::args().nth(1).as_deref() {
+        Some("braille") => Glyphs::Braille,
+        _ => Glyphs::HalfBlocks,
+    };
+    let mut screen =
+        TerminalScreen::<ROWS, COLS>::new(&LcdTheme::GREEN, glyphs, TerminalColors::TrueColor);
+
+    for frame in 0..300 {
+        draw_wave(&mut screen, frame as f32 / 10.0).unwrap();
+        thread::sleep(Duration::from_millis(33));
+    }
+}
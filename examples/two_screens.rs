@@ -0,0 +1,68 @@
+// Copyright 2023 Simon Varey - github.com/simonvarey
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! An example of a device with two displays, a main screen and a status screen, in a single window
+use rand::{thread_rng, Rng};
+use sdl2::event::{Event, WindowEvent};
+use simulate_lcd::{Bitmap, Control, LcdScreenBuilder, LcdTheme, LcdWindow, Runner};
+
+fn main() {
+    let sdl_context = sdl2::init().unwrap();
+    let window = LcdWindow::new(&sdl_context, "LCD Example: Two Screens", 660, 420).unwrap();
+
+    let mut main_screen = LcdScreenBuilder::<64, 128>::new()
+        .dot_size(5, 5)
+        .build_in(&window, 10, 10)
+        .unwrap();
+    let mut status_screen = LcdScreenBuilder::<8, 80>::new()
+        .theme(&LcdTheme::BLUE_WHITE_STN)
+        .dot_size(4, 4)
+        .gap(1)
+        .build_in(&window, 130, 350)
+        .unwrap();
+
+    let mut rng = thread_rng();
+    let mut level = 0;
+
+    let mut runner = Runner::new(&sdl_context, 30).unwrap();
+    runner
+        .run(|tick| {
+            for event in tick.events {
+                if let Event::Window {
+                    win_event: WindowEvent::Exposed,
+                    ..
+                } = event
+                {
+                    window.clear();
+                    main_screen.repaint()?;
+                    status_screen.repaint()?;
+                }
+            }
+
+            let random_bits: Vec<[bool; 128]> = (0..64).map(|_| rng.gen()).collect();
+            let random_bm: Box<Bitmap<128, 64>> = random_bits.try_into().unwrap();
+            main_screen.draw_bitmap(random_bm.as_ref())?;
+
+            level = (level + 1) % 80;
+            let status_bm: Bitmap<80, 8> = [[false; 80]; 8].map(|mut row| {
+                row[..level].fill(true);
+                row
+            });
+            status_screen.draw_bitmap(&status_bm)?;
+
+            window.present();
+            Ok(Control::Continue)
+        })
+        .unwrap();
+}
@@ -0,0 +1,68 @@
+// * Simulate LCD: A Simple LCD Screen Simulator *
+
+// Copyright 2023 Simon Varey - github.com/simonvarey
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! An example of drawing from a worker thread: the worker moves a ball as fast as it can and sends
+//! every frame, and the main thread shows only the latest one at 30 frames per second.
+use std::{thread, time::Duration};
+
+use simulate_lcd::{Bitmap, Control, Delivery, DrawCommand, LcdScreenBuilder, Runner};
+
+const ROWS: usize = 32;
+const COLS: usize = 64;
+
+fn main() {
+    let sdl_context = sdl2::init().unwrap();
+    let mut screen = LcdScreenBuilder::<ROWS, COLS>::new()
+        .title("LCD Example: Worker Thread")
+        .dot_size(8, 8)
+        .build(&sdl_context)
+        .unwrap();
+    let sender = screen.frame_channel(4, Delivery::LatestFrame);
+
+    thread::spawn(move || {
+        let (mut row, mut col) = (0, 0);
+        let (mut down, mut right) = (true, true);
+        loop {
+            let mut bm: Bitmap<COLS, ROWS> = [[false; COLS]; ROWS];
+            bm[row][col] = true;
+            if sender.send_frame(&bm).is_err() {
+                break;
+            }
+            if (down && row == ROWS - 1) || (!down && row == 0) {
+                down = !down;
+            }
+            if (right && col == COLS - 1) || (!right && col == 0) {
+                right = !right;
+            }
+            row = if down { row + 1 } else { row - 1 };
+            col = if right { col + 1 } else { col - 1 };
+
+            // Mark the corners, which are drawn on top of the frame before it
+            for (row, col) in [(0, 0), (0, COLS - 1), (ROWS - 1, 0), (ROWS - 1, COLS - 1)] {
+                let _ = sender.try_send(DrawCommand::Dot { row, col, on: true });
+            }
+            thread::sleep(Duration::from_millis(5));
+        }
+    });
+
+    let mut runner = Runner::new(&sdl_context, 30).unwrap();
+    runner
+        .run(|_| {
+            screen.drain()?;
+            Ok(Control::Continue)
+        })
+        .unwrap();
+}
@@ -0,0 +1,122 @@
+// * Simulate LCD: A Simple LCD Screen Simulator *
+
+// Copyright 2023 Simon Varey - github.com/simonvarey
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// Imports
+
+use std::path::PathBuf;
+
+use sdl2::pixels::Color;
+
+// Border
+
+/// The widths, in pixels, of the four sides of a border around the dots of an [`LcdScreen`].
+///
+/// [`LcdScreen`]: crate::LcdScreen
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Border {
+    /// The width of the top side of the border
+    pub top: u32,
+    /// The width of the right side of the border
+    pub right: u32,
+    /// The width of the bottom side of the border
+    pub bottom: u32,
+    /// The width of the left side of the border
+    pub left: u32,
+}
+
+impl Border {
+    /// Creates a border whose four sides all have the same width.
+    pub const fn uniform(width: u32) -> Self {
+        Self {
+            top: width,
+            right: width,
+            bottom: width,
+            left: width,
+        }
+    }
+
+    /// Returns whether every side of the border has a width of zero.
+    pub const fn is_empty(&self) -> bool {
+        self.top == 0 && self.right == 0 && self.bottom == 0 && self.left == 0
+    }
+
+    pub(crate) fn horizontal(&self) -> u32 {
+        self.left.saturating_add(self.right)
+    }
+
+    pub(crate) fn vertical(&self) -> u32 {
+        self.top.saturating_add(self.bottom)
+    }
+}
+
+// * Bezel *
+
+/// The glass and frame surrounding the dots of an [`LcdScreen`].
+///
+/// Real display modules have a border of unused glass around their dots, which is itself surrounded by
+/// a frame. The glass is drawn in the gap color of the screen (see [`DotStyle::gap_color`]) and the
+/// frame is drawn in the bezel color. Both are added to the size of the window containing the screen.
+///
+/// The default bezel has no glass margin and no frame, so that the dots fill the whole window.
+///
+/// # Examples
+///
+/// ```
+/// # use simulate_lcd::{Bezel, Border};
+/// let bezel = Bezel {
+///     glass: Border::uniform(12),
+///     frame: Border {
+///         top: 30,
+///         right: 30,
+///         bottom: 60,
+///         left: 30,
+///     },
+///     ..Default::default()
+/// };
+/// ```
+///
+/// [`LcdScreen`]: crate::LcdScreen
+/// [`DotStyle::gap_color`]: crate::DotStyle::gap_color
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Bezel {
+    /// The margin of unused glass between the dots and the frame
+    pub glass: Border,
+    /// The thickness of the frame around the glass
+    pub frame: Border,
+    /// The color of the frame. If `None`, the [`LcdTheme::bezel_color`] of the theme the screen was
+    /// created with is used, or that of the default theme if it was not created with a theme.
+    ///
+    /// [`LcdTheme::bezel_color`]: crate::LcdTheme::bezel_color
+    pub color: Option<Color>,
+    /// The path to a BMP image which is stretched over the whole window and drawn on top of the frame
+    /// color. Only the part of the image covering the frame is visible.
+    pub overlay: Option<PathBuf>,
+}
+
+impl Bezel {
+    pub(crate) fn outer(&self) -> Border {
+        Border {
+            top: self.glass.top.saturating_add(self.frame.top),
+            right: self.glass.right.saturating_add(self.frame.right),
+            bottom: self.glass.bottom.saturating_add(self.frame.bottom),
+            left: self.glass.left.saturating_add(self.frame.left),
+        }
+    }
+
+    pub(crate) fn is_framed(&self) -> bool {
+        !self.frame.is_empty() || self.overlay.is_some()
+    }
+}
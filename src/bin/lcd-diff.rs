@@ -0,0 +1,203 @@
+// * Simulate LCD: A Simple LCD Screen Simulator *
+
+// Copyright 2023 Simon Varey - github.com/simonvarey
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Compares two captures of a screen, such as snapshots saved from `LcdScreen` sessions, reporting the
+//! dots which differ and optionally writing a diff image.
+
+// Imports
+
+use std::{
+    env,
+    error::Error,
+    fs::{self, File},
+    io::BufWriter,
+    path::{Path, PathBuf},
+    process,
+};
+
+use simulate_lcd::{BitmapDiff, DiffColors, DynSnapshot, LcdError};
+
+// Captures
+
+// Parses a capture, which is either a binary (P4) or plain (P1) PBM image, or the text of a `Snapshot`.
+fn parse_capture(mut bytes: &[u8]) -> Result<DynSnapshot, String> {
+    if bytes.starts_with(b"P4") || bytes.starts_with(b"P1") {
+        // Note: the bytes start with a magic number, so there is an image to read
+        return DynSnapshot::read_pbm(&mut bytes)
+            .map(Option::unwrap)
+            .map_err(|err| err.to_string());
+    }
+    let text = std::str::from_utf8(bytes).map_err(|_| "the capture is not text or PBM")?;
+    text.parse().map_err(|err: LcdError| err.to_string())
+}
+
+fn read_capture(path: &Path) -> Result<DynSnapshot, String> {
+    let bytes =
+        fs::read(path).map_err(|err| format!("could not read {}: {err}", path.display()))?;
+    parse_capture(&bytes).map_err(|err| format!("could not parse {}: {err}", path.display()))
+}
+
+// Options
+
+const USAGE: &str = "\
+Usage: lcd-diff [OPTIONS] <A> <B>
+
+Compares two captures of a screen, and reports the dots added in B, the dots removed from A, and the
+rectangle containing the changes. Each capture is a PBM image or the text of a snapshot, with rows of
+'#' for 'on' and '.' for 'off'. Exits with 0 if the captures are the same, 1 if they differ, and 2 on
+an error.
+
+Options:
+  --image <PATH>  Write a PPM diff image, with unchanged 'on' dots in grey, unchanged 'off' dots in
+                  white, dots only in A in red, and dots only in B in green
+  --scale <N>     The size in pixels of each dot of the diff image [default: 8]
+  --dots          List the row and column of every added and removed dot
+  -h, --help      Print this message
+";
+
+// The options given on the command line.
+#[derive(Debug, Default, PartialEq)]
+struct Options {
+    image: Option<PathBuf>,
+    scale: usize,
+    list_dots: bool,
+    captures: Vec<PathBuf>,
+}
+
+// Parses the command line arguments, returning `None` if help was asked for.
+fn parse_args(args: impl IntoIterator<Item = String>) -> Result<Option<Options>, String> {
+    let mut options = Options {
+        scale: 8,
+        ..Default::default()
+    };
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| format!("{arg} needs a value"));
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "--image" => options.image = Some(PathBuf::from(value()?)),
+            "--scale" => {
+                let scale = value()?;
+                options.scale = scale
+                    .parse()
+                    .ok()
+                    .filter(|&scale| scale > 0)
+                    .ok_or_else(|| format!("{scale} is not a dot size"))?;
+            }
+            "--dots" => options.list_dots = true,
+            _ if arg.starts_with('-') => Err(format!("{arg} is not an option"))?,
+            _ => options.captures.push(PathBuf::from(arg)),
+        }
+    }
+    if options.captures.len() != 2 {
+        Err("two captures must be given")?
+    }
+    Ok(Some(options))
+}
+
+// Main
+
+// Compares the captures, returning whether they differ.
+fn run(options: &Options) -> Result<bool, Box<dyn Error>> {
+    let a = read_capture(&options.captures[0])?;
+    let b = read_capture(&options.captures[1])?;
+    if (a.rows(), a.cols()) != (b.rows(), b.cols()) {
+        Err(format!(
+            "the captures have different sizes: {} rows by {} columns and {} rows by {} columns",
+            a.rows(),
+            a.cols(),
+            b.rows(),
+            b.cols()
+        ))?
+    }
+    let diff = BitmapDiff::from_dots(a.rows(), a.cols(), a.dots(), b.dots())?;
+
+    println!("{diff}");
+    if options.list_dots {
+        for (label, dots) in [("removed", diff.removed()), ("added", diff.added())] {
+            for (row, col) in dots {
+                println!("{label} row {row} column {col}");
+            }
+        }
+    }
+    if let Some(path) = &options.image {
+        let file = File::create(path)
+            .map_err(|err| format!("could not create {}: {err}", path.display()))?;
+        diff.write_ppm(BufWriter::new(file), options.scale, &DiffColors::default())
+            .map_err(|err| format!("could not write {}: {err}", path.display()))?;
+    }
+    Ok(!diff.is_empty())
+}
+
+fn main() {
+    let options = match parse_args(env::args().skip(1)) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            print!("{USAGE}");
+            return;
+        }
+        Err(err) => {
+            eprint!("lcd-diff: {err}\n\n{USAGE}");
+            process::exit(2);
+        }
+    };
+    match run(&options) {
+        Ok(differ) => process::exit(i32::from(differ)),
+        Err(err) => {
+            eprintln!("lcd-diff: {err}");
+            process::exit(2);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_captures() {
+        let expected = DynSnapshot::new(2, 3, vec![true, false, false, false, false, true]);
+        assert_eq!(parse_capture(b"#..\n..#\n").ok(), expected.ok());
+        let parse = |bytes: &[u8]| parse_capture(bytes).map(DynSnapshot::into_dots);
+        assert_eq!(parse(b"\nlcd 2x3\n#..\n..#\n"), parse(b"#..\n..#\n"));
+        assert_eq!(parse(b"P4\n# comment\n3 2\n\x80\x20"), parse(b"#..\n..#\n"));
+        assert_eq!(parse(b"P1 3 2\n1 0 0\n001\n"), parse(b"#..\n..#\n"));
+
+        assert!(parse_capture(b"lcd 3x3\n#..\n..#\n").is_err());
+        assert!(parse_capture(b"#..\n..\n").is_err());
+        assert!(parse_capture(b"#.x\n").is_err());
+        assert!(parse_capture(b"P4 3 2\n\x80").is_err());
+        assert!(parse_capture(b"#..\n..#\n\n...\n").is_err());
+        assert!(parse_capture(format!("P4 {} 2\n", usize::MAX).as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let parse = |args: &[&str]| parse_args(args.iter().map(|arg| arg.to_string()));
+        assert_eq!(
+            parse(&["a.txt", "--image", "diff.ppm", "b.txt"]),
+            Ok(Some(Options {
+                image: Some(PathBuf::from("diff.ppm")),
+                scale: 8,
+                list_dots: false,
+                captures: vec![PathBuf::from("a.txt"), PathBuf::from("b.txt")],
+            }))
+        );
+        assert_eq!(parse(&["--help"]), Ok(None));
+        assert!(parse(&["a.txt"]).is_err());
+        assert!(parse(&["a.txt", "b.txt", "--scale", "0"]).is_err());
+    }
+}
@@ -0,0 +1,197 @@
+// * Simulate LCD: A Simple LCD Screen Simulator *
+
+// Copyright 2023 Simon Varey - github.com/simonvarey
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// Imports
+
+use std::{
+    io::{self, BufRead, ErrorKind},
+    str::FromStr,
+};
+
+use simulate_lcd::DynSnapshot;
+
+// Frame Formats
+
+// The formats frames can be read in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    // Binary (P4) or plain (P1) PBM images, one after another
+    Pbm,
+    // Rows of dots packed 8 to a byte, first dot in the most significant bit, each row starting on a new
+    // byte
+    Raw,
+    // Rows of '#' for 'on' and '.' for 'off', one row per line, with frames separated by blank lines
+    Ascii,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "pbm" => Ok(Format::Pbm),
+            "raw" => Ok(Format::Raw),
+            "ascii" => Ok(Format::Ascii),
+            _ => Err(format!(
+                "{name} is not a frame format. Use pbm, raw or ascii."
+            )),
+        }
+    }
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(ErrorKind::InvalidData, message)
+}
+
+// Returns whether the reader has no more bytes.
+fn at_end(reader: &mut impl BufRead) -> io::Result<bool> {
+    Ok(reader.fill_buf()?.is_empty())
+}
+
+// Reads the next frame of a screen with `rows` rows and `cols` columns of dots, returning its dots in
+// row-major order, or `None` if the input ended before the frame started.
+pub fn read_frame(
+    reader: &mut impl BufRead,
+    format: Format,
+    rows: usize,
+    cols: usize,
+) -> io::Result<Option<Vec<bool>>> {
+    match format {
+        Format::Pbm => read_pbm(reader, rows, cols),
+        Format::Raw => {
+            if at_end(reader)? {
+                return Ok(None);
+            }
+            read_packed(reader, rows, cols).map(Some)
+        }
+        Format::Ascii => read_ascii(reader, rows, cols),
+    }
+}
+
+// Reads rows of packed dots.
+fn read_packed(reader: &mut impl BufRead, rows: usize, cols: usize) -> io::Result<Vec<bool>> {
+    let mut packed = vec![0; cols.div_ceil(8)];
+    let mut dots = Vec::with_capacity(rows * cols);
+    for _ in 0..rows {
+        reader.read_exact(&mut packed)?;
+        dots.extend((0..cols).map(|col| packed[col / 8] & (0x80 >> (col % 8)) != 0));
+    }
+    Ok(dots)
+}
+
+// * PBM *
+
+fn read_pbm(reader: &mut impl BufRead, rows: usize, cols: usize) -> io::Result<Option<Vec<bool>>> {
+    let Some(image) = DynSnapshot::read_pbm(reader)? else {
+        return Ok(None);
+    };
+    if (image.rows(), image.cols()) != (rows, cols) {
+        Err(invalid_data(format!(
+            "a {}x{} PBM image does not fit a {cols}x{rows} screen",
+            image.cols(),
+            image.rows()
+        )))?
+    }
+    Ok(Some(image.into_dots()))
+}
+
+// * ASCII *
+
+fn read_ascii(
+    reader: &mut impl BufRead,
+    rows: usize,
+    cols: usize,
+) -> io::Result<Option<Vec<bool>>> {
+    let mut dots = Vec::with_capacity(rows * cols);
+    let mut line = String::new();
+    while dots.len() < rows * cols {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            if dots.is_empty() {
+                return Ok(None);
+            }
+            Err(io::Error::from(ErrorKind::UnexpectedEof))?
+        }
+        let row = line.trim_end();
+        // Note: blank lines separate frames, so they are skipped before a frame starts
+        if row.is_empty() && dots.is_empty() {
+            continue;
+        }
+        if row.chars().count() > cols {
+            Err(invalid_data(format!(
+                "the row {row:?} is longer than {cols} dots"
+            )))?
+        }
+        let start = dots.len();
+        for dot in row.chars() {
+            match dot {
+                '#' => dots.push(true),
+                '.' => dots.push(false),
+                _ => Err(invalid_data(format!(
+                    "{dot:?} is not a dot. Use '#' or '.'"
+                )))?,
+            }
+        }
+        // Note: a short row is padded with 'off' dots
+        dots.resize(start + cols, false);
+    }
+    Ok(Some(dots))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    #[test]
+    fn test_formats() {
+        let expected = vec![
+            true, false, false, false, false, false, false, false, false, true,
+        ];
+        let next = |input: &[u8], format| {
+            let mut reader = Cursor::new(input.to_vec());
+            let frames = [
+                read_frame(&mut reader, format, 2, 5).unwrap(),
+                read_frame(&mut reader, format, 2, 5).unwrap(),
+            ];
+            assert_eq!(read_frame(&mut reader, format, 2, 5).unwrap(), None);
+            frames
+        };
+
+        let raw = next(&[0x80, 0x08, 0x00, 0x00], Format::Raw);
+        assert_eq!(raw[0].as_ref(), Some(&expected));
+
+        let pbm = next(
+            b"P4\n# a comment\n5 2\n\x80\x08P1 5 2\n1000 0\n00001\n",
+            Format::Pbm,
+        );
+        assert_eq!(pbm[0].as_ref(), Some(&expected));
+        assert_eq!(pbm[1].as_ref(), Some(&expected));
+
+        let ascii = next(b"#\n....#\n\n#....\n....#\n", Format::Ascii);
+        assert_eq!(ascii[0].as_ref(), Some(&expected));
+        assert_eq!(ascii[1].as_ref(), Some(&expected));
+
+        // Frames of the wrong size or with unknown dots are rejected
+        let mut wrong_size = Cursor::new(b"P4 4 2\n\x80\x08".to_vec());
+        assert!(read_frame(&mut wrong_size, Format::Pbm, 2, 5).is_err());
+        let mut unknown = Cursor::new(b"#..x.\n".to_vec());
+        assert!(read_frame(&mut unknown, Format::Ascii, 2, 5).is_err());
+        let mut truncated = Cursor::new(vec![0x80]);
+        assert!(read_frame(&mut truncated, Format::Raw, 2, 5).is_err());
+    }
+}
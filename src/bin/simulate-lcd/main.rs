@@ -0,0 +1,379 @@
+// * Simulate LCD: A Simple LCD Screen Simulator *
+
+// Copyright 2023 Simon Varey - github.com/simonvarey
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A simulated LCD screen showing frames read from standard input or a FIFO, so that programs in any
+//! language can preview their monochrome output.
+
+// Imports
+
+use std::{
+    env,
+    error::Error,
+    fs::File,
+    io::{self, BufRead, BufReader},
+    path::{Path, PathBuf},
+    process,
+    sync::{
+        mpsc::{self, SyncSender},
+        Arc, Mutex, Weak,
+    },
+    thread,
+};
+
+use sdl2::event::{Event, WindowEvent};
+use simulate_lcd::{Control, DynLcdScreen, LcdTheme, Runner};
+
+use frames::{read_frame, Format};
+
+// Modules
+
+mod frames;
+
+// Options
+
+// The options given on the command line.
+#[derive(Debug, Clone, PartialEq)]
+struct Options {
+    rows: usize,
+    cols: usize,
+    dot_width: u32,
+    dot_height: u32,
+    theme: LcdTheme,
+    format: Format,
+    title: String,
+    latest: bool,
+    path: Option<PathBuf>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            rows: 64,
+            cols: 128,
+            dot_width: 4,
+            dot_height: 4,
+            theme: LcdTheme::default(),
+            format: Format::Pbm,
+            title: String::from("simulate-lcd"),
+            latest: false,
+            path: None,
+        }
+    }
+}
+
+fn usage() -> String {
+    let themes: Vec<&str> = LcdTheme::PRESETS.iter().map(|(name, _)| *name).collect();
+    format!(
+        "\
+Usage: simulate-lcd [OPTIONS] [PATH]
+
+Shows frames read from PATH, or from standard input if no path is given, on a simulated LCD screen.
+If PATH is a FIFO, it is opened again whenever the program writing to it closes it.
+
+Every frame is shown, at up to 60 frames per second. A program writing frames faster than that is
+made to wait, unless --latest is given, in which case frames not yet shown are dropped instead.
+
+Options:
+  --rows <ROWS>      The number of rows of dots [default: 64]
+  --cols <COLS>      The number of columns of dots [default: 128]
+  --dot-size <SIZE>  The size of each dot in pixels, as N or WxH [default: 4]
+  --theme <THEME>    The colors of the screen, one of: {} [default: green]
+  --format <FORMAT>  The format of the frames [default: pbm]
+  --title <TITLE>    The title of the window [default: simulate-lcd]
+  --latest           Show only the newest frame at each refresh, dropping older frames, so that
+                     the writer is never made to wait
+  -h, --help         Print this message
+
+Formats:
+  pbm    Binary (P4) or plain (P1) PBM images of the size of the screen, one after another
+  raw    Rows of dots packed 8 to a byte, first dot in the most significant bit, each row starting
+         on a new byte
+  ascii  Rows of '#' for 'on' and '.' for 'off', one row per line, with frames separated by blank
+         lines
+",
+        themes.join(", ")
+    )
+}
+
+// Parses the command line arguments, returning `None` if help was asked for.
+fn parse_args(args: impl IntoIterator<Item = String>) -> Result<Option<Options>, String> {
+    let mut options = Options::default();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| format!("{arg} needs a value"));
+        let number = |value: String| -> Result<usize, String> {
+            value
+                .parse()
+                .map_err(|_| format!("{value} is not a number"))
+        };
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "--rows" => options.rows = number(value()?)?,
+            "--cols" => options.cols = number(value()?)?,
+            "--dot-size" => {
+                let size = value()?;
+                let (width, height) = size.split_once('x').unwrap_or((&size, &size));
+                options.dot_width = width
+                    .parse()
+                    .map_err(|_| format!("{size} is not a dot size"))?;
+                options.dot_height = height
+                    .parse()
+                    .map_err(|_| format!("{size} is not a dot size"))?;
+            }
+            "--theme" => {
+                let name = value()?;
+                options.theme =
+                    LcdTheme::preset(&name).ok_or_else(|| format!("{name} is not a theme"))?;
+            }
+            "--format" => options.format = value()?.parse()?,
+            "--title" => options.title = value()?,
+            "--latest" => options.latest = true,
+            _ if arg.starts_with('-') && arg != "-" => Err(format!("{arg} is not an option"))?,
+            _ if options.path.is_some() => Err(format!("{arg} is a second input path"))?,
+            // Note: "-" is the usual name for standard input
+            _ if arg == "-" => {}
+            _ => options.path = Some(PathBuf::from(arg)),
+        }
+    }
+    Ok(Some(options))
+}
+
+// Input
+
+// Returns whether the file at `path` is a FIFO, which is opened again when its writer closes it.
+#[cfg(unix)]
+fn is_fifo(path: &Path) -> bool {
+    use std::os::unix::fs::FileTypeExt;
+
+    path.metadata()
+        .is_ok_and(|metadata| metadata.file_type().is_fifo())
+}
+
+#[cfg(not(unix))]
+fn is_fifo(_path: &Path) -> bool {
+    false
+}
+
+// Where the reader thread puts the frames it reads.
+enum FrameSink {
+    // Every frame, in order. The reader waits while the queue is full.
+    Queue(SyncSender<Vec<bool>>),
+    // Only the newest frame, which replaces any frame not yet shown, so the reader never waits. The
+    // slot is dropped when the window is closed.
+    Latest(Weak<Mutex<Option<Vec<bool>>>>),
+}
+
+impl FrameSink {
+    // Puts a frame in the sink, returning false once the window is closed.
+    fn put(&self, frame: Vec<bool>) -> bool {
+        match self {
+            Self::Queue(sender) => sender.send(frame).is_ok(),
+            Self::Latest(slot) => match slot.upgrade() {
+                Some(slot) => {
+                    // Note: neither thread can panic while holding the lock
+                    *slot.lock().unwrap() = Some(frame);
+                    true
+                }
+                None => false,
+            },
+        }
+    }
+}
+
+// Reads frames from `reader` into `frames` until the input ends, returning false if the window is
+// closed first.
+fn send_frames(
+    reader: &mut impl BufRead,
+    format: Format,
+    rows: usize,
+    cols: usize,
+    frames: &FrameSink,
+) -> io::Result<bool> {
+    while let Some(frame) = read_frame(reader, format, rows, cols)? {
+        if !frames.put(frame) {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+// Reads frames from `path`, or from standard input, and sends them to the main thread until the input
+// ends or the window is closed.
+fn read_frames(
+    path: Option<&Path>,
+    format: Format,
+    rows: usize,
+    cols: usize,
+    frames: &FrameSink,
+) -> io::Result<()> {
+    loop {
+        let mut reader: Box<dyn BufRead> = match path {
+            Some(path) => Box::new(BufReader::new(File::open(path)?)),
+            None => Box::new(io::stdin().lock()),
+        };
+        if !send_frames(&mut reader, format, rows, cols, frames)? || !path.is_some_and(is_fifo) {
+            return Ok(());
+        }
+    }
+}
+
+// Main
+
+fn run(options: Options) -> Result<(), Box<dyn Error>> {
+    let sdl_context = sdl2::init()?;
+    let mut screen = DynLcdScreen::new(
+        &sdl_context,
+        &options.title,
+        options.rows,
+        options.cols,
+        &options.theme,
+        options.dot_width,
+        options.dot_height,
+    )?;
+
+    // Note: the channel is short, so unless only the newest frame is kept, a fast writer is slowed down
+    //   to the rate frames are shown at
+    let (sender, receiver) = mpsc::sync_channel(2);
+    let newest = Arc::new(Mutex::new(None));
+    let Options {
+        rows,
+        cols,
+        format,
+        latest,
+        path,
+        ..
+    } = options;
+    let frames = if latest {
+        FrameSink::Latest(Arc::downgrade(&newest))
+    } else {
+        FrameSink::Queue(sender)
+    };
+    thread::spawn(move || {
+        if let Err(err) = read_frames(path.as_deref(), format, rows, cols, &frames) {
+            eprintln!("simulate-lcd: error reading frames: {err}");
+        }
+    });
+
+    let mut runner = Runner::new(&sdl_context, 60)?;
+    runner.run(|tick| {
+        for event in tick.events {
+            if let Event::Window {
+                win_event: WindowEvent::Exposed,
+                ..
+            } = event
+            {
+                screen.repaint()?;
+            }
+        }
+        // Note: one frame is shown per tick, so that every frame is seen, unless only the newest frame
+        //   is wanted
+        let frame = if latest {
+            newest.lock().unwrap().take()
+        } else {
+            receiver.try_recv().ok()
+        };
+        if let Some(frame) = frame {
+            screen.draw_dots(&frame)?;
+        }
+        Ok(Control::Continue)
+    })?;
+    Ok(())
+}
+
+fn main() {
+    let options = match parse_args(env::args().skip(1)) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            print!("{}", usage());
+            return;
+        }
+        Err(err) => {
+            eprint!("simulate-lcd: {err}\n\n{}", usage());
+            process::exit(2);
+        }
+    };
+    if let Err(err) = run(options) {
+        eprintln!("simulate-lcd: {err}");
+        process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Option<Options>, String> {
+        parse_args(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn test_parse_args() {
+        assert_eq!(parse(&[]), Ok(Some(Options::default())));
+        assert_eq!(parse(&["--rows", "8", "-h"]), Ok(None));
+        assert_eq!(
+            parse(&[
+                "--rows",
+                "32",
+                "--dot-size",
+                "3x5",
+                "--theme",
+                "white-oled",
+                "--format",
+                "ascii",
+                "--latest",
+                "frames.fifo"
+            ]),
+            Ok(Some(Options {
+                rows: 32,
+                dot_width: 3,
+                dot_height: 5,
+                theme: LcdTheme::WHITE_OLED,
+                format: Format::Ascii,
+                latest: true,
+                path: Some(PathBuf::from("frames.fifo")),
+                ..Default::default()
+            }))
+        );
+
+        assert!(parse(&["--cols"]).is_err());
+        assert!(parse(&["--cols", "many"]).is_err());
+        assert!(parse(&["--theme", "vfd"]).is_err());
+        assert!(parse(&["--format", "png"]).is_err());
+        assert!(parse(&["--verbose"]).is_err());
+        assert!(parse(&["a.pbm", "b.pbm"]).is_err());
+    }
+
+    #[test]
+    fn test_latest_frame() {
+        // Many more frames than the queue holds, with nothing taking them
+        let mut input: Vec<u8> = (0..100).collect();
+        input.push(0x81);
+        let newest = Arc::new(Mutex::new(None));
+        let frames = FrameSink::Latest(Arc::downgrade(&newest));
+
+        let mut reader = Cursor::new(input);
+        assert!(send_frames(&mut reader, Format::Raw, 1, 8, &frames).unwrap());
+        let expected = vec![true, false, false, false, false, false, false, true];
+        assert_eq!(newest.lock().unwrap().take(), Some(expected));
+
+        // The reader stops once the window is closed
+        drop(newest);
+        reader.set_position(0);
+        assert!(!send_frames(&mut reader, Format::Raw, 1, 8, &frames).unwrap());
+    }
+}
@@ -0,0 +1,513 @@
+// * Simulate LCD: A Simple LCD Screen Simulator *
+
+// Copyright 2023 Simon Varey - github.com/simonvarey
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// Imports
+
+use std::time::{Duration, Instant};
+
+use crate::{Bitmap, LcdError, LcdScreen};
+
+// Blink Timing
+
+/// The timing of a blinking attribute: it repeats every `period`, and is applied for the first `duty`
+/// fraction of each period.
+///
+/// The default blink has a period of one second and a duty of one half.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Blink {
+    period: Duration,
+    duty: f32,
+}
+
+impl Default for Blink {
+    fn default() -> Self {
+        Self {
+            period: Duration::from_secs(1),
+            duty: 0.5,
+        }
+    }
+}
+
+impl Blink {
+    /// Creates a blink timing which repeats every `period`, and is applied for the first `duty` fraction
+    /// of each period. A period of zero applies the attribute all the time.
+    ///
+    /// # Errors
+    ///
+    /// - [`LcdError::OutOfRange`] when `duty` is outside of the range `0.0` to `1.0` inclusive
+    ///
+    pub fn new(period: Duration, duty: f32) -> Result<Self, LcdError> {
+        if !(0.0..=1.0).contains(&duty) {
+            Err(LcdError::OutOfRange {
+                setting: "duty",
+                value: duty,
+            })?
+        }
+        Ok(Self { period, duty })
+    }
+
+    /// Returns the time after which the blink repeats.
+    pub fn period(&self) -> Duration {
+        self.period
+    }
+
+    /// Returns the fraction of each period for which the attribute is applied.
+    pub fn duty(&self) -> f32 {
+        self.duty
+    }
+
+    // Returns whether the attribute is applied at `clock` since the blinking started.
+    fn active(&self, clock: Duration) -> bool {
+        let period = self.period.as_nanos();
+        if period == 0 {
+            return true;
+        }
+        // Note: the phase is less than the period, so it is exact enough in an f64
+        (clock.as_nanos() % period) as f64 / (period as f64) < f64::from(self.duty)
+    }
+}
+
+// Blink Masks
+
+/// What happens to the dots of a [`BlinkMask`] while its blink is applied.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BlinkStyle {
+    /// The dots are turned 'off', so what is drawn there flashes. This is the default style.
+    #[default]
+    Off,
+    /// The dots are turned 'on'.
+    On,
+    /// The dots are inverted, so what is drawn there alternates with its negative.
+    Invert,
+}
+
+/// A set of dots of a screen of `R` rows and `C` columns which blink, such as a flashing warning or a
+/// selected menu item.
+///
+/// # Examples
+///
+/// ```
+/// # use std::time::Duration;
+/// # use simulate_lcd::{Blink, BlinkMask, BlinkStyle};
+/// let mut mask = [[false; 128]; 64];
+/// for row in &mut mask[8..16] {
+///     row[..64].fill(true);
+/// }
+/// let warning = BlinkMask {
+///     mask: Box::new(mask),
+///     blink: Blink::new(Duration::from_millis(500), 0.5).unwrap(),
+///     style: BlinkStyle::Invert,
+/// };
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlinkMask<const R: usize, const C: usize> {
+    /// The dots which blink, marked `true`
+    pub mask: Box<Bitmap<C, R>>,
+    /// The timing of the blink
+    pub blink: Blink,
+    /// What happens to the dots while the blink is applied
+    pub style: BlinkStyle,
+}
+
+// Cursor
+
+/// The shape of a [`Cursor`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CursorShape {
+    /// A line along the bottom row of dots of the cell, as on HD44780 character LCDs. This is the default
+    /// shape.
+    #[default]
+    Underline,
+    /// Every dot of the cell, covering what is drawn there.
+    Block,
+}
+
+/// A hardware cursor over a cell of dots, such as a character cell of a character LCD.
+///
+/// The cell's top left dot is at `row` and `col`, so the cursor at a character position is found by
+/// multiplying it by the pitch of the characters. The default cursor is a steady underline of a 5 by 8
+/// dot cell at the top left of the screen, the size of a character of an HD44780.
+///
+/// # Examples
+///
+/// ```
+/// # use simulate_lcd::{Blink, Cursor, CursorShape};
+/// // A blinking block over the third character of the second line, with characters 6 dots apart
+/// // horizontally and 9 dots apart vertically
+/// let cursor = Cursor {
+///     row: 9,
+///     col: 2 * 6,
+///     shape: CursorShape::Block,
+///     blink: Some(Blink::default()),
+///     ..Default::default()
+/// };
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Cursor {
+    /// The row of the top of the cell
+    pub row: usize,
+    /// The column of the left of the cell
+    pub col: usize,
+    /// The width of the cell in dots
+    pub width: usize,
+    /// The height of the cell in dots
+    pub height: usize,
+    /// The shape of the cursor
+    pub shape: CursorShape,
+    /// The timing of the cursor's blink, during which it is shown, or `None` for a steady cursor
+    pub blink: Option<Blink>,
+}
+
+impl Default for Cursor {
+    fn default() -> Self {
+        Self {
+            row: 0,
+            col: 0,
+            width: 5,
+            height: 8,
+            shape: CursorShape::default(),
+            blink: None,
+        }
+    }
+}
+
+impl Cursor {
+    fn active(&self, clock: Duration) -> bool {
+        self.blink.is_none_or(|blink| blink.active(clock))
+    }
+}
+
+// Attributes
+
+// The blinking attributes of a screen, and the bitmap drawn under them.
+pub(crate) struct Attributes<const R: usize, const C: usize> {
+    pub(crate) base: Box<Bitmap<C, R>>,
+    masks: Vec<BlinkMask<R, C>>,
+    cursor: Option<Cursor>,
+    // The time since the attributes were set
+    clock: Duration,
+    pub(crate) last_update: Instant,
+}
+
+impl<const R: usize, const C: usize> Attributes<R, C> {
+    fn new(base: Box<Bitmap<C, R>>) -> Self {
+        Self {
+            base,
+            masks: Vec::new(),
+            cursor: None,
+            clock: Duration::ZERO,
+            last_update: Instant::now(),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.masks.is_empty() && self.cursor.is_none()
+    }
+
+    // Returns whether each attribute is applied at the current time.
+    fn phases(&self) -> Vec<bool> {
+        let masks = self.masks.iter().map(|mask| mask.blink.active(self.clock));
+        masks
+            .chain(self.cursor.map(|cursor| cursor.active(self.clock)))
+            .collect()
+    }
+
+    // Returns the base bitmap with the attributes applied at the current time.
+    pub(crate) fn compose(&self) -> Box<Bitmap<C, R>> {
+        let mut bm = self.base.clone();
+        for mask in self
+            .masks
+            .iter()
+            .filter(|mask| mask.blink.active(self.clock))
+        {
+            for (row_bm, row_mask) in bm.iter_mut().zip(mask.mask.iter()) {
+                for (dot, _) in row_bm
+                    .iter_mut()
+                    .zip(row_mask)
+                    .filter(|(_, masked)| **masked)
+                {
+                    *dot = match mask.style {
+                        BlinkStyle::Off => false,
+                        BlinkStyle::On => true,
+                        BlinkStyle::Invert => !*dot,
+                    };
+                }
+            }
+        }
+        if let Some(cursor) = self.cursor.filter(|cursor| cursor.active(self.clock)) {
+            let bottom = cursor.row.saturating_add(cursor.height);
+            let rows = match cursor.shape {
+                CursorShape::Underline => bottom.saturating_sub(1).max(cursor.row),
+                CursorShape::Block => cursor.row,
+            }..bottom;
+            for row_bm in bm.iter_mut().skip(rows.start).take(rows.len()) {
+                for dot in row_bm.iter_mut().skip(cursor.col).take(cursor.width) {
+                    *dot = true;
+                }
+            }
+        }
+        bm
+    }
+}
+
+// * Screen Attributes *
+
+impl<const R: usize, const C: usize> LcdScreen<R, C> {
+    /// Adds a [`BlinkMask`] to the screen, making its dots blink, and returns its index in
+    /// [`blink_masks`]. The blink is animated by [`update`] or [`advance`], without the bitmap being drawn
+    /// again.
+    ///
+    /// While the screen has blinking attributes, [`bitmap`] returns the bitmap last drawn, without the
+    /// attributes applied.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use simulate_lcd::{Blink, BlinkMask, BlinkStyle, Control, LcdScreen, Runner};
+    /// # let sdl_context = sdl2::init().unwrap();
+    /// let mut screen = LcdScreen::<8, 8>::builder().build(&sdl_context).unwrap();
+    /// screen.draw_bitmap(&[[true; 8]; 8]).unwrap();
+    /// let warning = screen
+    ///     .add_blink_mask(BlinkMask {
+    ///         mask: Box::new([[true; 8]; 8]),
+    ///         blink: Blink::default(),
+    ///         style: BlinkStyle::Off,
+    ///     })
+    ///     .unwrap();
+    ///
+    /// let mut runner = Runner::new(&sdl_context, 30).unwrap();
+    /// runner
+    ///     .run(|tick| {
+    ///         // Stop blinking after five seconds
+    ///         if tick.number == 150 {
+    ///             screen.remove_blink_mask(warning)?;
+    ///         }
+    ///         screen.update()?;
+    ///         Ok(Control::Continue)
+    ///     })
+    ///     .unwrap();
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// - [`LcdError::Fill`] when there is an error filling one of the dots with the relevant color
+    /// - [`LcdError::Texture`] when there is an error drawing the dots through a texture
+    ///
+    /// [`blink_masks`]: crate::LcdScreen::blink_masks
+    /// [`update`]: crate::LcdScreen::update
+    /// [`advance`]: crate::LcdScreen::advance
+    /// [`bitmap`]: crate::LcdScreen::bitmap
+    ///
+    pub fn add_blink_mask(&mut self, mask: BlinkMask<R, C>) -> Result<usize, LcdError> {
+        let masks = &mut self.attributes_mut().masks;
+        masks.push(mask);
+        let index = masks.len() - 1;
+        self.show_attributes()?;
+        Ok(index)
+    }
+
+    /// Returns the [`BlinkMask`]s of the screen.
+    pub fn blink_masks(&self) -> &[BlinkMask<R, C>] {
+        self.attributes
+            .as_ref()
+            .map_or(&[], |attributes| &attributes.masks)
+    }
+
+    /// Removes the [`BlinkMask`] at `index` in [`blink_masks`] from the screen and returns it, or `None`
+    /// if there is no such mask. The dots of the mask are shown as drawn, unless other masks make them
+    /// blink. Like removing an element of a `Vec`, this shifts the index of every later mask down by one.
+    ///
+    /// # Errors
+    ///
+    /// - [`LcdError::Fill`] when there is an error filling one of the dots with the relevant color
+    /// - [`LcdError::Texture`] when there is an error drawing the dots through a texture
+    ///
+    /// [`blink_masks`]: crate::LcdScreen::blink_masks
+    ///
+    pub fn remove_blink_mask(&mut self, index: usize) -> Result<Option<BlinkMask<R, C>>, LcdError> {
+        if index >= self.blink_masks().len() {
+            return Ok(None);
+        }
+        let mask = self.attributes_mut().masks.remove(index);
+        self.show_attributes()?;
+        Ok(Some(mask))
+    }
+
+    /// Removes every [`BlinkMask`] from the screen, showing its dots as drawn.
+    ///
+    /// # Errors
+    ///
+    /// - [`LcdError::Fill`] when there is an error filling one of the dots with the relevant color
+    /// - [`LcdError::Texture`] when there is an error drawing the dots through a texture
+    ///
+    pub fn clear_blink_masks(&mut self) -> Result<(), LcdError> {
+        if self.attributes.is_none() {
+            return Ok(());
+        }
+        self.attributes_mut().masks.clear();
+        self.show_attributes()
+    }
+
+    /// Returns the [`Cursor`] of the screen, if it has one.
+    pub fn cursor(&self) -> Option<Cursor> {
+        self.attributes
+            .as_ref()
+            .and_then(|attributes| attributes.cursor)
+    }
+
+    /// Shows a [`Cursor`] on the screen, or removes it if `cursor` is `None`. A blinking cursor is
+    /// animated by [`update`] or [`advance`], without the bitmap being drawn again.
+    ///
+    /// # Errors
+    ///
+    /// - [`LcdError::Fill`] when there is an error filling one of the dots with the relevant color
+    /// - [`LcdError::Texture`] when there is an error drawing the dots through a texture
+    ///
+    /// [`update`]: crate::LcdScreen::update
+    /// [`advance`]: crate::LcdScreen::advance
+    ///
+    pub fn set_cursor(&mut self, cursor: Option<Cursor>) -> Result<(), LcdError> {
+        if cursor.is_none() && self.attributes.is_none() {
+            return Ok(());
+        }
+        self.attributes_mut().cursor = cursor;
+        self.show_attributes()
+    }
+
+    // Returns the attributes of the screen, starting them from the dots shown if it has none.
+    fn attributes_mut(&mut self) -> &mut Attributes<R, C> {
+        if self.attributes.is_none() {
+            self.attributes = Some(Attributes::new(self.shown_bitmap()));
+        }
+        // Note: the attributes were set above if there were none, so this cannot fail
+        self.attributes.as_mut().unwrap()
+    }
+
+    // Draws the bitmap under the attributes with them applied, dropping the attributes if there are none
+    // left.
+    fn show_attributes(&mut self) -> Result<(), LcdError> {
+        let Some(attributes) = &self.attributes else {
+            return Ok(());
+        };
+        if attributes.is_empty() {
+            // Note: the attributes were checked above, so this cannot fail
+            let base = self.attributes.take().unwrap().base;
+            return self.show_bitmap(&base);
+        }
+        let shown = attributes.compose();
+        self.show_bitmap(&shown)
+    }
+
+    // Moves the blinking attributes on by `elapsed`, redrawing the screen if any of them has changed
+    // phase.
+    pub(crate) fn advance_attributes(&mut self, elapsed: Duration) -> Result<(), LcdError> {
+        let Some(attributes) = &mut self.attributes else {
+            return Ok(());
+        };
+        let before = attributes.phases();
+        attributes.clock += elapsed;
+        if attributes.phases() == before {
+            return Ok(());
+        }
+        let shown = attributes.compose();
+        self.show_bitmap(&shown)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_blink() {
+        assert!(Blink::new(Duration::from_secs(1), 1.5).is_err());
+        let blink = Blink::new(Duration::from_millis(100), 0.25).unwrap();
+        assert!(blink.active(Duration::ZERO));
+        assert!(blink.active(Duration::from_millis(24)));
+        assert!(!blink.active(Duration::from_millis(25)));
+        assert!(!blink.active(Duration::from_millis(99)));
+        assert!(blink.active(Duration::from_millis(110)));
+        assert!(Blink::new(Duration::ZERO, 0.0)
+            .unwrap()
+            .active(Duration::from_secs(3)));
+    }
+
+    #[test]
+    fn test_attributes() {
+        let mut attributes = Attributes::<2, 3>::new(Box::new([[true, false, true], [false; 3]]));
+        let mask = |style| BlinkMask {
+            mask: Box::new([[true, true, false], [false; 3]]),
+            blink: Blink::default(),
+            style,
+        };
+        attributes.masks.push(mask(BlinkStyle::Invert));
+        assert_eq!(
+            *attributes.compose(),
+            [[false, true, true], [false, false, false]]
+        );
+        attributes.masks[0] = mask(BlinkStyle::Off);
+        assert_eq!(*attributes.compose(), [[false, false, true], [false; 3]]);
+
+        // Half way through the period, the blink is not applied
+        attributes.clock = Duration::from_millis(500);
+        assert_eq!(attributes.phases(), vec![false]);
+        assert_eq!(*attributes.compose(), *attributes.base);
+
+        attributes.masks.clear();
+        attributes.cursor = Some(Cursor {
+            col: 1,
+            width: 2,
+            height: 2,
+            ..Default::default()
+        });
+        assert_eq!(
+            *attributes.compose(),
+            [[true, false, true], [false, true, true]]
+        );
+        attributes.cursor = Some(Cursor {
+            shape: CursorShape::Block,
+            blink: Some(Blink::default()),
+            ..attributes.cursor.unwrap()
+        });
+        assert_eq!(*attributes.compose(), *attributes.base);
+        attributes.clock = Duration::ZERO;
+        assert_eq!(
+            *attributes.compose(),
+            [[true, true, true], [false, true, true]]
+        );
+
+        // A cursor reaching past the end of the screen is clipped to it
+        attributes.cursor = Some(Cursor {
+            row: usize::MAX,
+            height: usize::MAX,
+            ..Default::default()
+        });
+        assert_eq!(*attributes.compose(), *attributes.base);
+        attributes.cursor = Some(Cursor {
+            row: 1,
+            height: usize::MAX,
+            ..Default::default()
+        });
+        assert_eq!(*attributes.compose(), *attributes.base);
+        attributes.cursor = Some(Cursor {
+            shape: CursorShape::Block,
+            row: 1,
+            height: usize::MAX,
+            ..Default::default()
+        });
+        assert_eq!(*attributes.compose(), [[true, false, true], [true; 3]]);
+    }
+}
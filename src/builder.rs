@@ -0,0 +1,333 @@
+// * Simulate LCD: A Simple LCD Screen Simulator *
+
+// Copyright 2023 Simon Varey - github.com/simonvarey
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// Imports
+
+use sdl2::{pixels::Color, Sdl};
+
+use crate::{
+    Backend, Bezel, Bitmap, DotShape, DotStyle, LcdError, LcdScreen, LcdTheme, LcdWindow,
+    Orientation, Refresh, RenderPath, ScreenConfig, WindowOptions,
+};
+
+// * LCD Screen Builder *
+
+/// A builder for [`LcdScreens`], covering every option a screen can be created with.
+///
+/// Options which are not set take the same values as [`LcdScreen::new`] would give them, with a window
+/// title of `"LCD Screen"` and dots 10 pixels wide and high. The options are checked by [`validate`],
+/// which [`build`] calls before making any SDL calls.
+///
+/// # Parameters
+///
+/// * `R` - The number of rows of dots of the screen
+/// * `C` - The number of columns of dots of the screen
+///
+/// # Examples
+///
+/// ```
+/// # use simulate_lcd::{Backend, DotShape, LcdScreenBuilder, LcdTheme, Scaling, WindowOptions};
+/// # let sdl_context = sdl2::init().unwrap();
+/// let mut screen = LcdScreenBuilder::<16, 32>::new()
+///     .title("LCD Example: Builder")
+///     .theme(&LcdTheme::RED_LED_MATRIX)
+///     .dot_size(16, 16)
+///     .gap(4)
+///     .dot_shape(DotShape::Circle)
+///     .window_options(WindowOptions::new().resizable(Scaling::Aspect))
+///     .backend(Backend::Software)
+///     .build(&sdl_context)
+///     .unwrap();
+/// # std::thread::sleep(std::time::Duration::from_secs(1));
+/// ```
+///
+/// [`LcdScreens`]: crate::LcdScreen
+/// [`LcdScreen::new`]: crate::LcdScreen::new
+/// [`validate`]: crate::LcdScreenBuilder::validate
+/// [`build`]: crate::LcdScreenBuilder::build
+#[derive(Debug, Clone)]
+pub struct LcdScreenBuilder<const R: usize, const C: usize> {
+    title: String,
+    theme: LcdTheme,
+    dot_width: u32,
+    dot_height: u32,
+    style: DotStyle,
+    bezel: Bezel,
+    orientation: Orientation,
+    window: WindowOptions,
+    backend: Backend,
+    render_path: RenderPath,
+    contents: Option<Box<Bitmap<C, R>>>,
+    refresh: Option<Refresh>,
+}
+
+impl<const R: usize, const C: usize> Default for LcdScreenBuilder<R, C> {
+    fn default() -> Self {
+        Self {
+            title: String::from("LCD Screen"),
+            theme: LcdTheme::default(),
+            dot_width: 10,
+            dot_height: 10,
+            style: DotStyle::default(),
+            bezel: Bezel::default(),
+            orientation: Orientation::default(),
+            window: WindowOptions::default(),
+            backend: Backend::default(),
+            render_path: RenderPath::default(),
+            contents: None,
+            refresh: None,
+        }
+    }
+}
+
+impl<const R: usize, const C: usize> LcdScreenBuilder<R, C> {
+    /// Creates a builder with the default options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the title of the window containing the screen.
+    pub fn title(mut self, title: &str) -> Self {
+        self.title = String::from(title);
+        self
+    }
+
+    /// Sets the color of a dot when it is 'on' and the color of a dot when it is 'off'.
+    pub fn colors(mut self, on_color: Color, off_color: Color) -> Self {
+        self.theme.on_color = on_color;
+        self.theme.off_color = off_color;
+        self
+    }
+
+    /// Sets the colors of the screen, including its backlight and bezel colors, from an [`LcdTheme`].
+    pub fn theme(mut self, theme: &LcdTheme) -> Self {
+        self.theme = *theme;
+        self
+    }
+
+    /// Sets the width and height of a dot on the screen in pixels.
+    pub fn dot_size(mut self, dot_width: u32, dot_height: u32) -> Self {
+        self.dot_width = dot_width;
+        self.dot_height = dot_height;
+        self
+    }
+
+    /// Sets every option of the appearance of the dots from a [`DotStyle`].
+    pub fn style(mut self, style: DotStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Sets the size in pixels of the gap between adjacent dots. See [`DotStyle::gap`].
+    pub fn gap(mut self, gap: u32) -> Self {
+        self.style.gap = gap;
+        self
+    }
+
+    /// Sets the color of the gaps between dots. See [`DotStyle::gap_color`].
+    pub fn gap_color(mut self, color: Color) -> Self {
+        self.style.gap_color = Some(color);
+        self
+    }
+
+    /// Sets the shape of each dot. See [`DotStyle::shape`].
+    pub fn dot_shape(mut self, shape: DotShape) -> Self {
+        self.style.shape = shape;
+        self
+    }
+
+    /// Sets the color of the outline of 'off' dots. See [`DotStyle::grid_color`].
+    pub fn grid_color(mut self, color: Color) -> Self {
+        self.style.grid_color = Some(color);
+        self
+    }
+
+    /// Sets the [`Bezel`] surrounding the dots.
+    pub fn bezel(mut self, bezel: Bezel) -> Self {
+        self.bezel = bezel;
+        self
+    }
+
+    /// Sets the [`Orientation`] of the screen within its window.
+    pub fn orientation(mut self, orientation: Orientation) -> Self {
+        self.orientation = orientation;
+        self
+    }
+
+    /// Sets the [`WindowOptions`] of the window containing the screen.
+    pub fn window_options(mut self, options: WindowOptions) -> Self {
+        self.window = options;
+        self
+    }
+
+    /// Sets the SDL renderer [`Backend`] used to draw the screen.
+    pub fn backend(mut self, backend: Backend) -> Self {
+        self.backend = backend;
+        self
+    }
+
+    /// Sets the [`RenderPath`] used to draw the dots of the screen. See [`LcdScreen::set_render_path`].
+    ///
+    /// [`LcdScreen::set_render_path`]: crate::LcdScreen::set_render_path
+    pub fn render_path(mut self, path: RenderPath) -> Self {
+        self.render_path = path;
+        self
+    }
+
+    /// Sets the bitmap drawn on the screen when it is created. By default every dot is 'off'.
+    pub fn contents(mut self, bm: &Bitmap<C, R>) -> Self {
+        self.contents = Some(Box::new(*bm));
+        self
+    }
+
+    /// Sets the [`Refresh`] timing of the screen, so that drawn bitmaps are scanned out line by line
+    /// instead of being shown at once. See [`LcdScreen::set_refresh`]. The initial contents of the screen
+    /// are shown at once.
+    ///
+    /// [`LcdScreen::set_refresh`]: crate::LcdScreen::set_refresh
+    pub fn refresh(mut self, refresh: Refresh) -> Self {
+        self.refresh = Some(refresh);
+        self
+    }
+
+    fn config(&self) -> ScreenConfig<'_> {
+        ScreenConfig {
+            title: &self.title,
+            theme: self.theme,
+            dot_width: self.dot_width,
+            dot_height: self.dot_height,
+            style: self.style,
+            bezel: self.bezel.clone(),
+            orientation: self.orientation,
+            window: self.window.clone(),
+            backend: self.backend,
+            render_path: self.render_path,
+        }
+    }
+
+    /// Checks that a screen can be created with the options of this builder, without making any SDL
+    /// calls.
+    ///
+    /// # Errors
+    ///
+    /// - [`LcdError::WindowBuild`] when the title contains a nul byte
+    /// - [`LcdError::DotSize`] when the dot width or height is zero
+    /// - [`LcdError::Overlay`] when the overlay image of the bezel is not a file
+    /// - [`LcdError::WindowWidth`] when the total window width, in pixels, would exceed [`i32::MAX`]
+    /// - [`LcdError::WindowHeight`] when the total window width, in pixels, would exceed [`i32::MAX`]
+    ///
+    /// [`i32::MAX`]: std::i32::MAX
+    ///
+    pub fn validate(&self) -> Result<(), LcdError> {
+        self.config().validate(R, C).map(|_| ())
+    }
+
+    /// Creates the screen.
+    ///
+    /// # Arguments
+    ///
+    /// * `sdl_context` - An [`Sdl`] context object
+    ///
+    /// # Errors
+    ///
+    /// Any of the errors of [`validate`], as well as:
+    ///
+    /// - [`LcdError::Video`] when there is an error initializing the SDL video subsystem
+    /// - [`LcdError::WindowBuild`] when there is an error building the window
+    /// - [`LcdError::CanvasBuild`] when there is an error building the window canvas
+    /// - [`LcdError::Overlay`] when the overlay image of the bezel cannot be loaded
+    /// - [`LcdError::Fill`] when there is an error drawing the initial contents of the screen
+    /// - [`LcdError::Texture`] when there is an error drawing the screen through a texture
+    ///
+    /// [`Sdl`]: sdl2::Sdl
+    /// [`validate`]: crate::LcdScreenBuilder::validate
+    ///
+    pub fn build(&self, sdl_context: &Sdl) -> Result<LcdScreen<R, C>, LcdError> {
+        let screen = LcdScreen::create(sdl_context, self.config())?;
+        self.finish(screen)
+    }
+
+    /// Creates the screen inside an [`LcdWindow`] shared with other screens, with the top-left corner of
+    /// the screen, including its bezel, at `(x, y)` pixels from the top-left corner of the window.
+    ///
+    /// The title, window options and backend of the builder are not used, as the screen does not have
+    /// its own window. The initial contents of the screen are not shown until the window is presented
+    /// with [`LcdWindow::present`].
+    ///
+    /// # Arguments
+    ///
+    /// * `window` - The [`LcdWindow`] to create the screen in
+    /// * `x` - The x coordinate of the screen in the window in pixels
+    /// * `y` - The y coordinate of the screen in the window in pixels
+    ///
+    /// # Errors
+    ///
+    /// Any of the errors of [`validate`], as well as:
+    ///
+    /// - [`LcdError::Placement`] when the screen does not fit inside the window
+    /// - [`LcdError::Overlay`] when the overlay image of the bezel cannot be loaded
+    /// - [`LcdError::Fill`] when there is an error drawing the screen
+    /// - [`LcdError::Texture`] when there is an error drawing the screen through a texture
+    ///
+    /// [`validate`]: crate::LcdScreenBuilder::validate
+    ///
+    pub fn build_in(
+        &self,
+        window: &LcdWindow,
+        x: i32,
+        y: i32,
+    ) -> Result<LcdScreen<R, C>, LcdError> {
+        let screen = LcdScreen::create_in(window, x, y, self.config())?;
+        self.finish(screen)
+    }
+
+    // Draws the initial contents of a newly created screen, then starts its refresh timing.
+    fn finish(&self, mut screen: LcdScreen<R, C>) -> Result<LcdScreen<R, C>, LcdError> {
+        if let Some(contents) = &self.contents {
+            screen.draw_bitmap(contents.as_ref())?;
+        }
+        screen.set_refresh(self.refresh)?;
+        Ok(screen)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate() {
+        assert!(LcdScreenBuilder::<64, 128>::new().validate().is_ok());
+        assert!(matches!(
+            LcdScreenBuilder::<64, 128>::new().dot_size(0, 4).validate(),
+            Err(LcdError::DotSize { .. })
+        ));
+        assert!(matches!(
+            LcdScreenBuilder::<64, 128>::new()
+                .dot_size(i32::MAX as u32, 1)
+                .validate(),
+            Err(LcdError::WindowWidth { .. })
+        ));
+        assert!(matches!(
+            LcdScreenBuilder::<0, 128>::new().validate(),
+            Err(LcdError::WindowHeight { .. })
+        ));
+        assert!(matches!(
+            LcdScreenBuilder::<64, 128>::new().title("a\0b").validate(),
+            Err(LcdError::WindowBuild(_))
+        ));
+    }
+}
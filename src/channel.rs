@@ -0,0 +1,404 @@
+// * Simulate LCD: A Simple LCD Screen Simulator *
+
+// Copyright 2023 Simon Varey - github.com/simonvarey
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// Imports
+
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError},
+};
+
+use crate::{Bitmap, LcdError};
+
+// Draw Commands
+
+/// A change to the dots of an [`LcdScreen`], sent from another thread through a [`FrameSender`].
+///
+/// [`LcdScreen`]: crate::LcdScreen
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DrawCommand<const R: usize, const C: usize> {
+    /// Draws a whole bitmap, replacing every dot.
+    Frame(Box<Bitmap<C, R>>),
+    /// Switches a single dot on or off. Dots outside the screen are ignored.
+    Dot {
+        /// the row of the dot
+        row: usize,
+        /// the column of the dot
+        col: usize,
+        /// whether the dot is switched on
+        on: bool,
+    },
+    /// Writes a rectangle of dots with its top-left corner at `(row, col)`. Any part of the rectangle
+    /// outside the screen is ignored.
+    Patch {
+        /// the row of the top-left corner of the rectangle
+        row: usize,
+        /// the column of the top-left corner of the rectangle
+        col: usize,
+        /// the width of the rectangle in dots
+        width: usize,
+        /// the dots of the rectangle, in rows of `width` dots from top to bottom
+        dots: Vec<bool>,
+    },
+    /// Switches every dot off.
+    Clear,
+    /// Switches every dot which is on off, and every dot which is off on.
+    Invert,
+}
+
+impl<const R: usize, const C: usize> DrawCommand<R, C> {
+    // Whether the command replaces every dot, so that the commands sent before it have no effect.
+    fn supersedes(&self) -> bool {
+        matches!(self, DrawCommand::Frame(_) | DrawCommand::Clear)
+    }
+
+    // Applies the command to a bitmap.
+    pub(crate) fn apply(&self, bm: &mut Bitmap<C, R>) {
+        match self {
+            DrawCommand::Frame(frame) => *bm = **frame,
+            DrawCommand::Dot { row, col, on } => {
+                if let Some(bit) = bm.get_mut(*row).and_then(|bm_row| bm_row.get_mut(*col)) {
+                    *bit = *on;
+                }
+            }
+            DrawCommand::Patch {
+                row,
+                col,
+                width,
+                dots,
+            } => {
+                if *width == 0 {
+                    return;
+                }
+                for (bm_row, patch_row) in bm.iter_mut().skip(*row).zip(dots.chunks(*width)) {
+                    for (bit, dot) in bm_row.iter_mut().skip(*col).zip(patch_row) {
+                        *bit = *dot;
+                    }
+                }
+            }
+            DrawCommand::Clear => *bm = [[false; C]; R],
+            DrawCommand::Invert => bm.iter_mut().flatten().for_each(|bit| *bit = !*bit),
+        }
+    }
+}
+
+// Delivery
+
+/// How the channel of an [`LcdScreen`] treats commands sent faster than the screen draws them.
+///
+/// [`LcdScreen`]: crate::LcdScreen
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Delivery {
+    /// Every command is kept until it is drawn. Senders block while the channel is full. This is the
+    /// default.
+    #[default]
+    Queued,
+    /// A [`DrawCommand::Frame`] or [`DrawCommand::Clear`] discards every command waiting before it, as
+    /// they would be overwritten anyway, so a slow screen skips to the latest frame instead of falling
+    /// behind. Other commands are queued, and senders block while the channel is full of them.
+    LatestFrame,
+}
+
+// Inbox
+
+// The commands waiting to be drawn, shared between the screen and its senders.
+#[derive(Debug)]
+struct Pending<const R: usize, const C: usize> {
+    commands: VecDeque<DrawCommand<R, C>>,
+    // Whether the screen has stopped receiving, so senders should give up
+    closed: bool,
+}
+
+#[derive(Debug)]
+struct Shared<const R: usize, const C: usize> {
+    pending: Mutex<Pending<R, C>>,
+    // Signalled whenever commands are taken from a full channel, or the channel is closed
+    space: Condvar,
+    capacity: usize,
+    delivery: Delivery,
+}
+
+impl<const R: usize, const C: usize> Shared<R, C> {
+    // Locks the pending commands. A sender which panicked cannot leave them in an inconsistent state, so
+    // a poisoned lock is still used.
+    fn lock(&self) -> MutexGuard<'_, Pending<R, C>> {
+        self.pending.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+// The receiving end of the channel of a screen, which closes the channel when dropped.
+#[derive(Debug)]
+pub(crate) struct Inbox<const R: usize, const C: usize> {
+    shared: Arc<Shared<R, C>>,
+}
+
+impl<const R: usize, const C: usize> Inbox<R, C> {
+    // Creates a channel holding up to `capacity` commands, returning its receiving and sending ends. A
+    // capacity of zero is treated as one.
+    pub(crate) fn new(capacity: usize, delivery: Delivery) -> (Self, FrameSender<R, C>) {
+        let shared = Arc::new(Shared {
+            pending: Mutex::new(Pending {
+                commands: VecDeque::new(),
+                closed: false,
+            }),
+            space: Condvar::new(),
+            capacity: capacity.max(1),
+            delivery,
+        });
+        (
+            Self {
+                shared: shared.clone(),
+            },
+            FrameSender { shared },
+        )
+    }
+
+    // Takes every waiting command, in the order they were sent.
+    pub(crate) fn take(&self) -> VecDeque<DrawCommand<R, C>> {
+        let commands = std::mem::take(&mut self.shared.lock().commands);
+        if !commands.is_empty() {
+            self.shared.space.notify_all();
+        }
+        commands
+    }
+}
+
+impl<const R: usize, const C: usize> Drop for Inbox<R, C> {
+    fn drop(&mut self) {
+        self.shared.lock().closed = true;
+        self.shared.space.notify_all();
+    }
+}
+
+// * Frame Sender *
+
+/// A handle for drawing to an [`LcdScreen`] from other threads, as returned by
+/// [`LcdScreen::frame_channel`].
+///
+/// SDL windows can only be drawn from the main thread, so an [`LcdScreen`] cannot be sent to other
+/// threads. A frame sender can, and can be cloned so that several threads draw to the same screen. The
+/// commands it sends wait in a bounded channel until the main thread calls [`LcdScreen::drain`], which
+/// draws them all as a single frame.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use std::thread;
+/// # use simulate_lcd::{Control, Delivery, LcdScreen, Runner};
+/// # let sdl_context = sdl2::init().unwrap();
+/// let mut screen = LcdScreen::<32, 64>::builder().build(&sdl_context).unwrap();
+/// let sender = screen.frame_channel(2, Delivery::LatestFrame);
+///
+/// thread::spawn(move || {
+///     let mut on = false;
+///     loop {
+///         on = !on;
+///         if sender.send_frame(&[[on; 64]; 32]).is_err() {
+///             break;
+///         }
+///     }
+/// });
+///
+/// let mut runner = Runner::new(&sdl_context, 30).unwrap();
+/// runner
+///     .run(|_| {
+///         screen.drain()?;
+///         Ok(Control::Continue)
+///     })
+///     .unwrap();
+/// ```
+///
+/// [`LcdScreen`]: crate::LcdScreen
+/// [`LcdScreen::frame_channel`]: crate::LcdScreen::frame_channel
+/// [`LcdScreen::drain`]: crate::LcdScreen::drain
+#[derive(Debug)]
+pub struct FrameSender<const R: usize, const C: usize> {
+    shared: Arc<Shared<R, C>>,
+}
+
+impl<const R: usize, const C: usize> Clone for FrameSender<R, C> {
+    fn clone(&self) -> Self {
+        Self {
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<const R: usize, const C: usize> FrameSender<R, C> {
+    /// Sends a command to the screen, waiting for space in the channel if it is full.
+    ///
+    /// # Errors
+    ///
+    /// - [`LcdError::Disconnected`] when the screen has been dropped, or has opened a new channel
+    ///
+    pub fn send(&self, command: DrawCommand<R, C>) -> Result<(), LcdError> {
+        let mut pending = self.shared.lock();
+        let mut command = command;
+        loop {
+            if pending.closed {
+                Err(LcdError::Disconnected)?
+            }
+            match self.push(&mut pending, command) {
+                Ok(()) => return Ok(()),
+                Err(full) => {
+                    command = full;
+                    pending = self
+                        .shared
+                        .space
+                        .wait(pending)
+                        .unwrap_or_else(PoisonError::into_inner);
+                }
+            }
+        }
+    }
+
+    /// Sends a command to the screen if there is space for it in the channel, without waiting.
+    ///
+    /// # Errors
+    ///
+    /// - [`LcdError::Disconnected`] when the screen has been dropped, or has opened a new channel
+    /// - [`LcdError::ChannelFull`] when the channel is full
+    ///
+    pub fn try_send(&self, command: DrawCommand<R, C>) -> Result<(), LcdError> {
+        let mut pending = self.shared.lock();
+        if pending.closed {
+            Err(LcdError::Disconnected)?
+        }
+        self.push(&mut pending, command)
+            .map_err(|_| LcdError::ChannelFull {
+                capacity: self.shared.capacity,
+            })
+    }
+
+    /// Sends a whole bitmap to the screen, waiting for space in the channel if it is full. This is a
+    /// shorthand for sending a [`DrawCommand::Frame`].
+    ///
+    /// # Errors
+    ///
+    /// - [`LcdError::Disconnected`] when the screen has been dropped, or has opened a new channel
+    ///
+    pub fn send_frame(&self, bm: &Bitmap<C, R>) -> Result<(), LcdError> {
+        self.send(DrawCommand::Frame(Box::new(*bm)))
+    }
+
+    /// Returns whether the screen is still receiving commands from this sender.
+    pub fn is_connected(&self) -> bool {
+        !self.shared.lock().closed
+    }
+
+    // Adds a command to the pending commands, or hands it back if the channel is full.
+    fn push(
+        &self,
+        pending: &mut Pending<R, C>,
+        command: DrawCommand<R, C>,
+    ) -> Result<(), DrawCommand<R, C>> {
+        if self.shared.delivery == Delivery::LatestFrame && command.supersedes() {
+            pending.commands.clear();
+        }
+        if pending.commands.len() < self.shared.capacity {
+            pending.commands.push_back(command);
+            Ok(())
+        } else {
+            Err(command)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::*;
+
+    #[test]
+    fn test_commands() {
+        let mut bm = [[false; 4]; 3];
+        DrawCommand::<3, 4>::Dot {
+            row: 1,
+            col: 2,
+            on: true,
+        }
+        .apply(&mut bm);
+        DrawCommand::<3, 4>::Dot {
+            row: 3,
+            col: 0,
+            on: true,
+        }
+        .apply(&mut bm);
+        assert_eq!(bm, [[false; 4], [false, false, true, false], [false; 4]]);
+
+        // The part of a patch beyond the screen is dropped
+        DrawCommand::<3, 4>::Patch {
+            row: 2,
+            col: 2,
+            width: 3,
+            dots: vec![true, false, true, true, true, true],
+        }
+        .apply(&mut bm);
+        assert_eq!(bm[2], [false, false, true, false]);
+
+        DrawCommand::<3, 4>::Invert.apply(&mut bm);
+        assert_eq!(bm[1], [true, true, false, true]);
+        DrawCommand::<3, 4>::Clear.apply(&mut bm);
+        assert_eq!(bm, [[false; 4]; 3]);
+    }
+
+    #[test]
+    fn test_channel() {
+        // Queued commands are kept until the channel is full
+        let (inbox, sender) = Inbox::<2, 2>::new(2, Delivery::Queued);
+        sender.send_frame(&[[true; 2]; 2]).unwrap();
+        sender.try_send(DrawCommand::Invert).unwrap();
+        assert!(matches!(
+            sender.try_send(DrawCommand::Clear),
+            Err(LcdError::ChannelFull { capacity: 2 })
+        ));
+        assert_eq!(inbox.take().len(), 2);
+        assert!(inbox.take().is_empty());
+
+        // A blocked sender continues once the commands are taken
+        sender.try_send(DrawCommand::Invert).unwrap();
+        sender.try_send(DrawCommand::Invert).unwrap();
+        let blocked = sender.clone();
+        let handle = thread::spawn(move || blocked.send(DrawCommand::Clear));
+        while inbox.take().back() != Some(&DrawCommand::Clear) {
+            thread::yield_now();
+        }
+        handle.join().unwrap().unwrap();
+
+        // The latest frame discards the commands before it
+        let (inbox, sender) = Inbox::<2, 2>::new(2, Delivery::LatestFrame);
+        sender.try_send(DrawCommand::Invert).unwrap();
+        sender.send_frame(&[[true; 2]; 2]).unwrap();
+        sender.send_frame(&[[false; 2]; 2]).unwrap();
+        sender.try_send(DrawCommand::Invert).unwrap();
+        assert_eq!(
+            inbox.take(),
+            [
+                DrawCommand::Frame(Box::new([[false; 2]; 2])),
+                DrawCommand::Invert
+            ]
+        );
+
+        // Dropping the screen disconnects its senders
+        drop(inbox);
+        assert!(!sender.is_connected());
+        assert!(matches!(
+            sender.send(DrawCommand::Clear),
+            Err(LcdError::Disconnected)
+        ));
+    }
+}
@@ -0,0 +1,375 @@
+// * Simulate LCD: A Simple LCD Screen Simulator *
+
+// Copyright 2023 Simon Varey - github.com/simonvarey
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// Imports
+
+use std::{
+    fmt::{Display, Formatter, Result as FmtResult},
+    io::{self, ErrorKind, Write},
+};
+
+use sdl2::pixels::Color;
+
+use crate::{Bitmap, LcdError, Snapshot};
+
+// Dot Differences
+
+/// How a dot differs between two bitmaps, A and B.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DotDiff {
+    /// The dot is the same in both bitmaps, and is 'on' if `true`
+    Same(bool),
+    /// The dot is 'on' only in bitmap A, so it was removed in B
+    OnlyA,
+    /// The dot is 'on' only in bitmap B, so it was added in B
+    OnlyB,
+}
+
+/// The smallest rectangle of dots containing every dot which differs between two bitmaps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DotBounds {
+    /// The row of the top of the rectangle
+    pub row: usize,
+    /// The column of the left of the rectangle
+    pub col: usize,
+    /// The number of rows of the rectangle
+    pub height: usize,
+    /// The number of columns of the rectangle
+    pub width: usize,
+}
+
+/// The colors of a diff image written by [`BitmapDiff::write_ppm`].
+///
+/// Unchanged dots keep a color for 'on' and a color for 'off', so that the image still shows what the
+/// bitmaps look like around the changes. By default these are grey and white, while dots only in
+/// bitmap A are red and dots only in bitmap B are green.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DiffColors {
+    /// The color of a dot which is 'on' in both bitmaps
+    pub same_on: Color,
+    /// The color of a dot which is 'off' in both bitmaps
+    pub same_off: Color,
+    /// The color of a dot which is 'on' only in bitmap A
+    pub only_a: Color,
+    /// The color of a dot which is 'on' only in bitmap B
+    pub only_b: Color,
+}
+
+impl Default for DiffColors {
+    fn default() -> Self {
+        Self {
+            same_on: Color::RGB(96, 96, 96),
+            same_off: Color::WHITE,
+            only_a: Color::RGB(220, 40, 40),
+            only_b: Color::RGB(40, 170, 60),
+        }
+    }
+}
+
+// * Bitmap Diff *
+
+/// The differences between two bitmaps of the same size, A and B, such as two [`Snapshot`]s of a screen
+/// taken before and after a change.
+///
+/// A diff reports the dots added in B, the dots removed from A, and the [`DotBounds`] of the changes,
+/// and can be written as an image with the unchanged 'on' dots in grey, the unchanged 'off' dots in
+/// white, and the changed dots in a color for each bitmap. Its [`Display`] form is a short summary of
+/// the changes. The `lcd-diff` binary which comes with the crate compares two snapshot files in this
+/// way.
+///
+/// # Examples
+///
+/// ```
+/// # use simulate_lcd::{BitmapDiff, DotBounds};
+/// let before = [[true, false, false], [false, false, false]];
+/// let after = [[false, false, false], [false, true, true]];
+/// let diff = BitmapDiff::new(&before, &after);
+///
+/// assert_eq!(diff.added(), vec![(1, 1), (1, 2)]);
+/// assert_eq!(diff.removed(), vec![(0, 0)]);
+/// assert_eq!(
+///     diff.bounds(),
+///     Some(DotBounds { row: 0, col: 0, height: 2, width: 3 })
+/// );
+/// assert_eq!(
+///     diff.to_string(),
+///     "2 dots added and 1 dot removed, within 3 columns by 2 rows at column 0, row 0"
+/// );
+/// ```
+///
+/// [`Snapshot`]: crate::Snapshot
+/// [`Display`]: std::fmt::Display
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BitmapDiff {
+    rows: usize,
+    cols: usize,
+    dots: Vec<DotDiff>,
+}
+
+impl BitmapDiff {
+    /// Compares bitmap `a` with bitmap `b`.
+    pub fn new<const R: usize, const C: usize>(a: &Bitmap<C, R>, b: &Bitmap<C, R>) -> Self {
+        let dots = a.iter().flatten().zip(b.iter().flatten());
+        Self::from_pairs(R, C, dots)
+    }
+
+    /// Compares two bitmaps whose size is only known at run time, given as `rows` rows of `cols` dots
+    /// from top to bottom, with `true` for a dot which is 'on'.
+    ///
+    /// # Errors
+    ///
+    /// - [`LcdError::BitmapSize`] when the number of dots of `a` or `b` is not `rows` times `cols`
+    ///
+    pub fn from_dots(rows: usize, cols: usize, a: &[bool], b: &[bool]) -> Result<Self, LcdError> {
+        for dots in [a, b] {
+            if rows.checked_mul(cols) != Some(dots.len()) {
+                Err(LcdError::BitmapSize {
+                    expected: rows.saturating_mul(cols),
+                    actual: dots.len(),
+                })?
+            }
+        }
+        Ok(Self::from_pairs(rows, cols, a.iter().zip(b)))
+    }
+
+    fn from_pairs<'a>(
+        rows: usize,
+        cols: usize,
+        pairs: impl Iterator<Item = (&'a bool, &'a bool)>,
+    ) -> Self {
+        let dots = pairs
+            .map(|(&a, &b)| match (a, b) {
+                (true, false) => DotDiff::OnlyA,
+                (false, true) => DotDiff::OnlyB,
+                (on, _) => DotDiff::Same(on),
+            })
+            .collect();
+        Self { rows, cols, dots }
+    }
+
+    /// Returns the number of rows of dots of the bitmaps.
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Returns the number of columns of dots of the bitmaps.
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// Returns how the dot at `row` and `col` differs between the bitmaps, or `None` if there is no such
+    /// dot.
+    pub fn get(&self, row: usize, col: usize) -> Option<DotDiff> {
+        if row >= self.rows || col >= self.cols {
+            return None;
+        }
+        Some(self.dots[row * self.cols + col])
+    }
+
+    /// Returns whether the bitmaps are the same.
+    pub fn is_empty(&self) -> bool {
+        self.dots.iter().all(|dot| matches!(dot, DotDiff::Same(_)))
+    }
+
+    // Returns the row and column of each dot which differs in the given way.
+    fn positions(&self, diff: DotDiff) -> Vec<(usize, usize)> {
+        self.dots
+            .iter()
+            .enumerate()
+            .filter(|(_, dot)| **dot == diff)
+            .map(|(index, _)| (index / self.cols, index % self.cols))
+            .collect()
+    }
+
+    /// Returns the row and column of each dot which is 'on' in bitmap B but not in bitmap A, in row-major
+    /// order.
+    pub fn added(&self) -> Vec<(usize, usize)> {
+        self.positions(DotDiff::OnlyB)
+    }
+
+    /// Returns the row and column of each dot which is 'on' in bitmap A but not in bitmap B, in row-major
+    /// order.
+    pub fn removed(&self) -> Vec<(usize, usize)> {
+        self.positions(DotDiff::OnlyA)
+    }
+
+    /// Returns the smallest rectangle of dots containing every dot which differs, or `None` if the
+    /// bitmaps are the same.
+    pub fn bounds(&self) -> Option<DotBounds> {
+        let mut changed = self
+            .dots
+            .iter()
+            .enumerate()
+            .filter(|(_, dot)| !matches!(dot, DotDiff::Same(_)))
+            .map(|(index, _)| (index / self.cols, index % self.cols));
+        let (first_row, first_col) = changed.next()?;
+        let (mut top, mut left, mut bottom, mut right) =
+            (first_row, first_col, first_row, first_col);
+        for (row, col) in changed {
+            top = top.min(row);
+            left = left.min(col);
+            bottom = bottom.max(row);
+            right = right.max(col);
+        }
+        Some(DotBounds {
+            row: top,
+            col: left,
+            height: bottom - top + 1,
+            width: right - left + 1,
+        })
+    }
+
+    /// Writes the diff as a binary PPM image, with each dot drawn as a `scale` by `scale` pixel square in
+    /// the relevant [`DiffColors`]. PPM images can be opened by most image viewers, or converted to PNG
+    /// by tools such as ImageMagick.
+    ///
+    /// # Errors
+    ///
+    /// Returns any error from writing to `writer`, and an [`ErrorKind::InvalidInput`] error when the
+    /// image would be too large for its width or height to be held in a `usize`.
+    ///
+    /// [`ErrorKind::InvalidInput`]: std::io::ErrorKind::InvalidInput
+    ///
+    pub fn write_ppm<W: Write>(
+        &self,
+        mut writer: W,
+        scale: usize,
+        colors: &DiffColors,
+    ) -> io::Result<()> {
+        let scale = scale.max(1);
+        let (Some(width), Some(height)) =
+            (self.cols.checked_mul(scale), self.rows.checked_mul(scale))
+        else {
+            Err(io::Error::new(
+                ErrorKind::InvalidInput,
+                format!("a diff image of {scale} pixels per dot is too large"),
+            ))?
+        };
+        write!(writer, "P6\n{width} {height}\n255\n")?;
+        let mut line = Vec::new();
+        for row in self.dots.chunks(self.cols.max(1)).take(self.rows) {
+            line.clear();
+            for dot in row {
+                let color = match dot {
+                    DotDiff::Same(true) => colors.same_on,
+                    DotDiff::Same(false) => colors.same_off,
+                    DotDiff::OnlyA => colors.only_a,
+                    DotDiff::OnlyB => colors.only_b,
+                };
+                for _ in 0..scale {
+                    line.extend_from_slice(&[color.r, color.g, color.b]);
+                }
+            }
+            for _ in 0..scale {
+                writer.write_all(&line)?;
+            }
+        }
+        writer.flush()
+    }
+}
+
+impl Display for BitmapDiff {
+    fn fmt(&self, fmtr: &mut Formatter<'_>) -> FmtResult {
+        let Some(bounds) = self.bounds() else {
+            return write!(fmtr, "no dots differ");
+        };
+        let plural = |count: usize| if count == 1 { "" } else { "s" };
+        let (added, removed) = (self.added().len(), self.removed().len());
+        write!(
+            fmtr,
+            "{added} dot{} added and {removed} dot{} removed, within {} column{} by {} row{} at column \
+             {}, row {}",
+            plural(added),
+            plural(removed),
+            bounds.width,
+            plural(bounds.width),
+            bounds.height,
+            plural(bounds.height),
+            bounds.col,
+            bounds.row
+        )
+    }
+}
+
+impl<const R: usize, const C: usize> Snapshot<R, C> {
+    /// Compares this snapshot, as bitmap A, with `other`, as bitmap B. See [`BitmapDiff`].
+    pub fn diff(&self, other: &Snapshot<R, C>) -> BitmapDiff {
+        BitmapDiff::new(self.bitmap(), other.bitmap())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff() {
+        let a = [[true, true, false, false], [false, false, false, false]];
+        let b = [[true, false, false, false], [false, false, false, true]];
+        let diff = BitmapDiff::new(&a, &b);
+        assert_eq!(diff.get(0, 0), Some(DotDiff::Same(true)));
+        assert_eq!(diff.get(0, 1), Some(DotDiff::OnlyA));
+        assert_eq!(diff.get(1, 3), Some(DotDiff::OnlyB));
+        assert_eq!(diff.get(2, 0), None);
+        assert_eq!(diff.added(), vec![(1, 3)]);
+        assert_eq!(diff.removed(), vec![(0, 1)]);
+        assert_eq!(
+            diff.bounds(),
+            Some(DotBounds {
+                row: 0,
+                col: 1,
+                height: 2,
+                width: 3
+            })
+        );
+        assert_eq!(
+            diff.to_string(),
+            "1 dot added and 1 dot removed, within 3 columns by 2 rows at column 1, row 0"
+        );
+
+        let same = BitmapDiff::new(&a, &a);
+        assert!(same.is_empty());
+        assert_eq!(same.bounds(), None);
+        assert_eq!(same.to_string(), "no dots differ");
+
+        let flat: Vec<bool> = a.iter().flatten().copied().collect();
+        assert!(BitmapDiff::from_dots(2, 4, &flat, &flat)
+            .unwrap()
+            .is_empty());
+        assert!(BitmapDiff::from_dots(2, 3, &flat, &flat).is_err());
+    }
+
+    #[test]
+    fn test_ppm() {
+        let diff = BitmapDiff::new(&[[true, true, false]], &[[true, false, true]]);
+        let colors = DiffColors {
+            same_on: Color::RGB(1, 1, 1),
+            same_off: Color::RGB(0, 0, 0),
+            only_a: Color::RGB(2, 2, 2),
+            only_b: Color::RGB(3, 3, 3),
+        };
+        let mut image = Vec::new();
+        diff.write_ppm(&mut image, 2, &colors).unwrap();
+        assert!(diff.write_ppm(Vec::new(), usize::MAX, &colors).is_err());
+        let header = b"P6\n6 2\n255\n";
+        assert_eq!(&image[..header.len()], header);
+        let line: Vec<u8> = [1, 1, 2, 2, 3, 3]
+            .iter()
+            .flat_map(|&value| [value; 3])
+            .collect();
+        assert_eq!(image[header.len()..], [line.clone(), line].concat());
+    }
+}
@@ -0,0 +1,240 @@
+// * Simulate LCD: A Simple LCD Screen Simulator *
+
+// Copyright 2023 Simon Varey - github.com/simonvarey
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// Imports
+
+use std::collections::BTreeMap;
+
+// Bus Model
+
+/// How the display RAM of a panel controller is laid out, which determines the bytes that must be sent
+/// over the bus to update a region of dots.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BusLayout {
+    /// One bit per dot, with each byte holding 8 vertically adjacent dots in a column of a page of 8 rows,
+    /// as on the SSD1306, SH1106 and ST7565. This is the default layout.
+    #[default]
+    VerticalPages,
+    /// One bit per dot, with each byte holding 8 horizontally adjacent dots of a row, as on the ST7920
+    /// and RA8835.
+    Horizontal,
+    /// The given number of bits per dot, packed along each row, as on color TFT controllers such as the
+    /// ST7735 and ILI9341 (16 or 18 bits).
+    Packed {
+        /// the number of bits sent for each dot
+        bits_per_dot: u32,
+    },
+}
+
+/// How the changed dots of a frame are grouped into regions, each of which is sent to the panel as a
+/// separate write with its own addressing overhead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Coalescing {
+    /// Each run of changed bytes in a row (or page) of display RAM is a region. This is the default.
+    #[default]
+    RowSpans,
+    /// Runs covering the same bytes in consecutive rows (or pages) are merged into rectangles.
+    Rects,
+    /// A single rectangle covers every changed byte of the frame.
+    BoundingBox,
+}
+
+/// A model of the bus between a microcontroller and a panel, used to estimate the number of bytes each
+/// frame drawn to an [`LcdScreen`] would send, and so the bandwidth an update strategy needs.
+///
+/// # Examples
+///
+/// ```
+/// # use simulate_lcd::{BusLayout, BusModel, Coalescing};
+/// // An SSD1306 updated with one write per changed rectangle, each needing 6 command bytes to set the
+/// // column and page address
+/// let bus = BusModel {
+///     layout: BusLayout::VerticalPages,
+///     coalescing: Coalescing::Rects,
+///     overhead_bytes: 6,
+/// };
+/// ```
+///
+/// [`LcdScreen`]: crate::LcdScreen
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct BusModel {
+    /// The layout of the display RAM
+    pub layout: BusLayout,
+    /// How changed bytes are grouped into writes
+    pub coalescing: Coalescing,
+    /// The number of bytes, such as address commands, sent before each write
+    pub overhead_bytes: usize,
+}
+
+// A rectangle of display RAM, in lines (rows or pages) and units (bytes or dots) along each line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Region {
+    pub(crate) line: usize,
+    pub(crate) start: usize,
+    pub(crate) lines: usize,
+    pub(crate) units: usize,
+}
+
+impl BusModel {
+    // Groups runs of changed dots, given as `(row, first col, last col)`, into the regions of display RAM
+    // which would be written, returning the regions and the total bytes sent.
+    pub(crate) fn regions(&self, runs: &[(usize, usize, usize)]) -> (Vec<Region>, usize) {
+        // Convert each run into the units of RAM it touches, and merge overlapping or adjacent units
+        let mut lines: BTreeMap<usize, Vec<(usize, usize)>> = BTreeMap::new();
+        for &(row, first, last) in runs {
+            let (line, start, end) = match self.layout {
+                BusLayout::VerticalPages => (row / 8, first, last),
+                BusLayout::Horizontal => (row, first / 8, last / 8),
+                BusLayout::Packed { .. } => (row, first, last),
+            };
+            lines.entry(line).or_default().push((start, end));
+        }
+        let mut spans: Vec<Region> = Vec::new();
+        for (line, mut ranges) in lines {
+            ranges.sort_unstable();
+            let mut merged: Vec<(usize, usize)> = Vec::new();
+            for (start, end) in ranges {
+                match merged.last_mut() {
+                    Some(last) if start <= last.1 + 1 => last.1 = last.1.max(end),
+                    _ => merged.push((start, end)),
+                }
+            }
+            spans.extend(merged.into_iter().map(|(start, end)| Region {
+                line,
+                start,
+                lines: 1,
+                units: end - start + 1,
+            }));
+        }
+
+        let regions = match self.coalescing {
+            Coalescing::RowSpans => spans,
+            Coalescing::Rects => {
+                let mut rects: Vec<Region> = Vec::new();
+                for span in spans {
+                    match rects.iter_mut().find(|rect| {
+                        rect.start == span.start
+                            && rect.units == span.units
+                            && rect.line + rect.lines == span.line
+                    }) {
+                        Some(rect) => rect.lines += 1,
+                        None => rects.push(span),
+                    }
+                }
+                rects
+            }
+            Coalescing::BoundingBox => match (spans.first(), spans.last()) {
+                (Some(first), Some(last)) => {
+                    let start = spans.iter().map(|span| span.start).min().unwrap_or(0);
+                    let end = spans
+                        .iter()
+                        .map(|span| span.start + span.units)
+                        .max()
+                        .unwrap_or(0);
+                    vec![Region {
+                        line: first.line,
+                        start,
+                        lines: last.line - first.line + 1,
+                        units: end - start,
+                    }]
+                }
+                _ => Vec::new(),
+            },
+        };
+
+        let bytes = regions
+            .iter()
+            .map(|region| {
+                let line_bytes = match self.layout {
+                    BusLayout::Packed { bits_per_dot } => {
+                        (region.units * bits_per_dot as usize).div_ceil(8)
+                    }
+                    _ => region.units,
+                };
+                region.lines * line_bytes + self.overhead_bytes
+            })
+            .sum();
+        (regions, bytes)
+    }
+}
+
+// Frame Statistics
+
+/// Statistics about the last frame drawn to an [`LcdScreen`], as returned by
+/// [`LcdScreen::frame_stats`].
+///
+/// [`LcdScreen`]: crate::LcdScreen
+/// [`LcdScreen::frame_stats`]: crate::LcdScreen::frame_stats
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FrameStats {
+    /// The number of dots which changed
+    pub dots_changed: usize,
+    /// The number of rects filled to draw the changed dots, after adjacent rects of the same color were
+    /// merged. This is zero for screens drawn with [`RenderPath::Texture`].
+    ///
+    /// [`RenderPath::Texture`]: crate::RenderPath::Texture
+    pub rects_filled: usize,
+    /// The number of separate writes of display RAM the frame would need, according to the
+    /// [`BusModel`] of the screen
+    pub regions: usize,
+    /// The number of bytes the frame would send over the bus, according to the [`BusModel`] of the
+    /// screen
+    pub bus_bytes: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bus_model() {
+        // Two runs in rows 1 and 2 over columns 3 to 9, and one in row 9 over columns 20 to 21
+        let runs = [(1, 3, 9), (2, 3, 9), (9, 20, 21)];
+
+        let pages = BusModel::default();
+        let (regions, bytes) = pages.regions(&runs);
+        assert_eq!(regions.len(), 2);
+        assert_eq!(bytes, 7 + 2);
+
+        let horizontal = BusModel {
+            layout: BusLayout::Horizontal,
+            coalescing: Coalescing::Rects,
+            overhead_bytes: 3,
+        };
+        let (regions, bytes) = horizontal.regions(&runs);
+        assert_eq!(
+            regions[0],
+            Region {
+                line: 1,
+                start: 0,
+                lines: 2,
+                units: 2
+            }
+        );
+        assert_eq!(bytes, (2 * 2 + 3) + (1 + 3));
+
+        let packed = BusModel {
+            layout: BusLayout::Packed { bits_per_dot: 16 },
+            coalescing: Coalescing::BoundingBox,
+            overhead_bytes: 0,
+        };
+        let (regions, bytes) = packed.regions(&runs);
+        assert_eq!(regions.len(), 1);
+        assert_eq!(bytes, 9 * 19 * 2);
+
+        assert_eq!(packed.regions(&[]), (Vec::new(), 0));
+    }
+}
@@ -0,0 +1,191 @@
+// * Simulate LCD: A Simple LCD Screen Simulator *
+
+// Copyright 2023 Simon Varey - github.com/simonvarey
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// Imports
+
+use sdl2::{render::Canvas, video::Window, Sdl};
+
+use crate::{
+    Backend, Bezel, DotPainter, DotStyle, LcdDot, LcdError, LcdTheme, Orientation, RenderPath,
+    ScreenConfig, WindowOptions,
+};
+
+// * Dynamic LCD Screen *
+
+/// A simulated LCD dot-matrix screen whose number of rows and columns of dots is chosen at run time.
+///
+/// An [`LcdScreen`] is sized by const parameters, which suits programs simulating a particular panel.
+/// Programs which only learn the size of the screen when they run, such as the `simulate-lcd` binary,
+/// can use this type instead. It draws dots in the same way, but has fewer options: the dots are square
+/// with no gaps, and there is no bezel.
+///
+/// # Examples
+///
+/// ```
+/// # use simulate_lcd::{DynLcdScreen, LcdTheme};
+/// # let sdl_context = sdl2::init().unwrap();
+/// let (rows, cols) = (2, 3);
+/// let mut screen =
+///     DynLcdScreen::new(&sdl_context, "LCD Example: Dynamic", rows, cols, &LcdTheme::GREEN, 20, 20)
+///         .unwrap();
+/// screen
+///     .draw_dots(&[true, false, true, false, true, false])
+///     .unwrap();
+/// # std::thread::sleep(std::time::Duration::from_secs(1));
+/// ```
+///
+/// [`LcdScreen`]: crate::LcdScreen
+pub struct DynLcdScreen {
+    rows: usize,
+    cols: usize,
+    dots: Vec<LcdDot>,
+    canvas: Canvas<Window>,
+    painter: DotPainter,
+}
+
+impl DynLcdScreen {
+    /// Creates a simulated LCD screen with `rows` rows and `cols` columns of dots, in the colors of
+    /// `theme`.
+    ///
+    /// # Arguments
+    ///
+    /// * `sdl_context` - An [`Sdl`] context object
+    /// * `title` - The title of the window containing the screen
+    /// * `rows` - The number of rows of dots of the screen
+    /// * `cols` - The number of columns of dots of the screen
+    /// * `theme` - The [`LcdTheme`] giving the colors of the screen
+    /// * `dot_width` - The width of a dot on the screen in pixels
+    /// * `dot_height` - The height of a dot on the screen in pixels
+    ///
+    /// # Errors
+    ///
+    /// - [`LcdError::Video`] when there is an error initializing the SDL video subsystem
+    /// - [`LcdError::WindowBuild`] when there is an error building the window
+    /// - [`LcdError::CanvasBuild`] when there is an error building the window canvas
+    /// - [`LcdError::DotSize`] when `dot_width` or `dot_height` is zero
+    /// - [`LcdError::WindowWidth`] when the total window width, in pixels, would exceed [`i32::MAX`],
+    ///   or `cols` is zero
+    /// - [`LcdError::WindowHeight`] when the total window width, in pixels, would exceed [`i32::MAX`],
+    ///   or `rows` is zero
+    /// - [`LcdError::Fill`] when there is an error filling the screen
+    ///
+    /// [`Sdl`]: sdl2::Sdl
+    /// [`i32::MAX`]: std::i32::MAX
+    ///
+    pub fn new(
+        sdl_context: &Sdl,
+        title: &str,
+        rows: usize,
+        cols: usize,
+        theme: &LcdTheme,
+        dot_width: u32,
+        dot_height: u32,
+    ) -> Result<Self, LcdError> {
+        let config = ScreenConfig {
+            title,
+            theme: *theme,
+            dot_width,
+            dot_height,
+            style: DotStyle::default(),
+            bezel: Bezel::default(),
+            orientation: Orientation::default(),
+            window: WindowOptions::default(),
+            backend: Backend::default(),
+            render_path: RenderPath::default(),
+        };
+        let (window_width, window_height) = config.validate(rows, cols)?;
+        let canvas = config.open_canvas(sdl_context, window_width, window_height)?;
+
+        let geometry = config.geometry();
+        let dots = (0..rows)
+            .flat_map(|row| {
+                (0..cols).map(move |col| LcdDot {
+                    rect: geometry.dot_rect(row, col, rows, cols),
+                    on: false,
+                })
+            })
+            .collect();
+        let (_, _, painter) = config.painter();
+
+        let mut screen = Self {
+            rows,
+            cols,
+            dots,
+            canvas,
+            painter,
+        };
+        screen.repaint()?;
+        Ok(screen)
+    }
+
+    /// Returns the number of rows of dots of the screen.
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Returns the number of columns of dots of the screen.
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// Returns the window containing the screen.
+    pub fn window(&self) -> &Window {
+        self.canvas.window()
+    }
+
+    /// Clears the window and redraws every dot of the screen in its current state. See
+    /// [`LcdScreen::repaint`].
+    ///
+    /// # Errors
+    ///
+    /// - [`LcdError::Fill`] when there is an error filling one of the dots with the relevant color
+    ///
+    /// [`LcdScreen::repaint`]: crate::LcdScreen::repaint
+    ///
+    pub fn repaint(&mut self) -> Result<(), LcdError> {
+        self.canvas.set_draw_color(self.painter.palette.gap);
+        self.canvas.clear();
+        self.painter.paint_all(&mut self.canvas, &self.dots)?;
+        self.canvas.present();
+        Ok(())
+    }
+
+    /// Draws the dots of a bitmap to the screen, given in rows of [`cols`] dots from top to bottom,
+    /// with `true` for a dot which is 'on'. Only the dots which have changed are redrawn.
+    ///
+    /// # Errors
+    ///
+    /// - [`LcdError::BitmapSize`] when the number of dots is not [`rows`] times [`cols`]
+    /// - [`LcdError::Fill`] when there is an error filling one of the dots with the relevant color
+    ///
+    /// [`rows`]: crate::DynLcdScreen::rows
+    /// [`cols`]: crate::DynLcdScreen::cols
+    ///
+    pub fn draw_dots(&mut self, dots: &[bool]) -> Result<(), LcdError> {
+        if dots.len() != self.dots.len() {
+            Err(LcdError::BitmapSize {
+                expected: self.dots.len(),
+                actual: dots.len(),
+            })?
+        }
+        self.painter.paint_changes(
+            &mut self.canvas,
+            self.dots.iter_mut().zip(dots.iter().copied()),
+        )?;
+        self.canvas.present();
+        Ok(())
+    }
+}
@@ -0,0 +1,740 @@
+// * Simulate LCD: A Simple LCD Screen Simulator *
+
+// Copyright 2023 Simon Varey - github.com/simonvarey
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// Imports
+
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use sdl2::{
+    event::{Event, WindowEvent},
+    keyboard::Keycode,
+    mouse::MouseButton,
+    rect::Rect,
+};
+
+// * Keypad *
+
+/// A change in the state of a key of a [`Keypad`], as returned by [`Keypad::poll_event`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeypadEvent {
+    /// The key at the given row and column of the matrix was pressed.
+    Pressed {
+        /// the row of the key
+        row: usize,
+        /// the column of the key
+        col: usize,
+    },
+    /// The key at the given row and column of the matrix was released.
+    Released {
+        /// the row of the key
+        row: usize,
+        /// the column of the key
+        col: usize,
+    },
+}
+
+/// A simulated matrix keypad with `ROWS` rows and `COLS` columns of keys, driven by the host keyboard.
+///
+/// Each key of the matrix can be bound to one or more keys on the host keyboard. SDL events passed to
+/// [`handle_event`] press and release the keys of the matrix, and the state of the matrix can then be
+/// read in two ways:
+///
+/// - as [`KeypadEvents`], read with [`poll_event`], for applications which only care which key was
+///   pressed
+/// - as a simulated scan of the matrix, for running unmodified keypad scanning code. The code drives
+///   rows with [`drive_row`] or [`drive_rows`] and reads the columns with [`read_columns`], exactly as
+///   it would drive and read the GPIO pins of a real keypad.
+///
+/// By default each key of the matrix has a diode, so any number of keys can be pressed at once. Use
+/// [`diodes`] to simulate a matrix without diodes, in which pressing three keys at the corners of a
+/// rectangle makes the fourth key appear pressed too ('ghosting').
+///
+/// # Examples
+///
+/// ```
+/// # use simulate_lcd::Keypad;
+/// let mut keypad = Keypad::<4, 4>::standard();
+/// keypad.set_pressed(2, 1, true);
+///
+/// // A typical scan: drive each row in turn and read which columns are connected to it
+/// let mut pressed = None;
+/// for row in 0..4 {
+///     keypad.drive_row(row);
+///     if let Some(col) = keypad.read_columns().iter().position(|&active| active) {
+///         pressed = Some((row, col));
+///     }
+/// }
+/// keypad.release_rows();
+/// assert_eq!(pressed, Some((2, 1)));
+/// ```
+///
+/// [`handle_event`]: crate::Keypad::handle_event
+/// [`KeypadEvents`]: crate::KeypadEvent
+/// [`poll_event`]: crate::Keypad::poll_event
+/// [`drive_row`]: crate::Keypad::drive_row
+/// [`drive_rows`]: crate::Keypad::drive_rows
+/// [`read_columns`]: crate::Keypad::read_columns
+/// [`diodes`]: crate::Keypad::diodes
+#[derive(Debug, Clone)]
+pub struct Keypad<const ROWS: usize, const COLS: usize> {
+    bindings: HashMap<Keycode, (usize, usize)>,
+    held_keys: HashSet<Keycode>,
+    held_manually: [[bool; COLS]; ROWS],
+    driven: [bool; ROWS],
+    diodes: bool,
+    events: VecDeque<KeypadEvent>,
+}
+
+impl<const ROWS: usize, const COLS: usize> Default for Keypad<ROWS, COLS> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const ROWS: usize, const COLS: usize> Keypad<ROWS, COLS> {
+    /// Creates a keypad with no keys bound to the host keyboard.
+    pub fn new() -> Self {
+        Self {
+            bindings: HashMap::new(),
+            held_keys: HashSet::new(),
+            held_manually: [[false; COLS]; ROWS],
+            driven: [false; ROWS],
+            diodes: true,
+            events: VecDeque::new(),
+        }
+    }
+
+    /// Creates a keypad with each key of the matrix bound to the host key at the same position in
+    /// `layout`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simulate_lcd::Keypad;
+    /// # use sdl2::keyboard::Keycode;
+    /// let arrows = Keypad::<2, 2>::with_layout([
+    ///     [Keycode::Up, Keycode::Return],
+    ///     [Keycode::Down, Keycode::Escape],
+    /// ]);
+    /// ```
+    pub fn with_layout(layout: [[Keycode; COLS]; ROWS]) -> Self {
+        let mut keypad = Self::new();
+        for (row, keys) in layout.iter().enumerate() {
+            for (col, &key) in keys.iter().enumerate() {
+                keypad = keypad.bind(key, row, col);
+            }
+        }
+        keypad
+    }
+
+    /// Binds a host key to the key at `row` and `col` of the matrix, replacing any previous binding of
+    /// the host key. Several host keys may be bound to the same key of the matrix.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `row` or `col` is outside the matrix.
+    pub fn bind(mut self, key: Keycode, row: usize, col: usize) -> Self {
+        assert!(row < ROWS, "keypad row {row} is out of range");
+        assert!(col < COLS, "keypad column {col} is out of range");
+        self.bindings.insert(key, (row, col));
+        self
+    }
+
+    /// Sets whether each key of the matrix has a diode. Without diodes, a driven row also drives every
+    /// row connected to it through pressed keys, so some key combinations produce ghost key presses in
+    /// a scan. Key events are not affected.
+    pub fn diodes(mut self, diodes: bool) -> Self {
+        self.diodes = diodes;
+        self
+    }
+
+    /// Returns whether the key at `row` and `col` of the matrix is pressed, either by a host key or by
+    /// [`set_pressed`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `row` or `col` is outside the matrix.
+    ///
+    /// [`set_pressed`]: crate::Keypad::set_pressed
+    pub fn is_pressed(&self, row: usize, col: usize) -> bool {
+        self.held_manually[row][col]
+            || self
+                .held_keys
+                .iter()
+                .any(|key| self.bindings.get(key) == Some(&(row, col)))
+    }
+
+    /// Presses or releases the key at `row` and `col` of the matrix, independently of the host keyboard.
+    /// This is useful for scripted input, or for pressing keys from the buttons of a [`DevicePanel`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `row` or `col` is outside the matrix.
+    ///
+    /// [`DevicePanel`]: crate::DevicePanel
+    pub fn set_pressed(&mut self, row: usize, col: usize, pressed: bool) {
+        self.update(row, col, |keypad| keypad.held_manually[row][col] = pressed);
+    }
+
+    // Applies a change to the keypad, queueing an event if it changes whether a key is pressed.
+    fn update(&mut self, row: usize, col: usize, change: impl FnOnce(&mut Self)) {
+        let was_pressed = self.is_pressed(row, col);
+        change(self);
+        match (was_pressed, self.is_pressed(row, col)) {
+            (false, true) => self.events.push_back(KeypadEvent::Pressed { row, col }),
+            (true, false) => self.events.push_back(KeypadEvent::Released { row, col }),
+            _ => {}
+        }
+    }
+
+    /// Presses and releases keys of the matrix according to an SDL event, returning whether the event was
+    /// for a bound host key.
+    ///
+    /// Auto-repeated key presses are ignored. When the window loses focus, every host key is released,
+    /// as SDL does not report keys released while the window is unfocused.
+    pub fn handle_event(&mut self, event: &Event) -> bool {
+        match *event {
+            Event::KeyDown {
+                keycode: Some(key),
+                repeat,
+                ..
+            } => match self.bindings.get(&key) {
+                Some(&(row, col)) => {
+                    if !repeat {
+                        self.update(row, col, |keypad| {
+                            keypad.held_keys.insert(key);
+                        });
+                    }
+                    true
+                }
+                None => false,
+            },
+            Event::KeyUp {
+                keycode: Some(key), ..
+            } => match self.bindings.get(&key) {
+                Some(&(row, col)) => {
+                    self.update(row, col, |keypad| {
+                        keypad.held_keys.remove(&key);
+                    });
+                    true
+                }
+                None => false,
+            },
+            Event::Window {
+                win_event: WindowEvent::FocusLost,
+                ..
+            } => {
+                let held: Vec<Keycode> = self.held_keys.iter().copied().collect();
+                for key in held {
+                    let (row, col) = self.bindings[&key];
+                    self.update(row, col, |keypad| {
+                        keypad.held_keys.remove(&key);
+                    });
+                }
+                false
+            }
+            _ => false,
+        }
+    }
+
+    /// Removes and returns the oldest [`KeypadEvent`] which has not yet been read, if there is one.
+    pub fn poll_event(&mut self) -> Option<KeypadEvent> {
+        self.events.pop_front()
+    }
+
+    /// Drives a single row of the matrix, releasing every other row.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `row` is outside the matrix.
+    pub fn drive_row(&mut self, row: usize) {
+        self.driven = [false; ROWS];
+        self.driven[row] = true;
+    }
+
+    /// Drives every row of the matrix for which `rows` is `true`, and releases the others.
+    pub fn drive_rows(&mut self, rows: [bool; ROWS]) {
+        self.driven = rows;
+    }
+
+    /// Releases every row of the matrix.
+    pub fn release_rows(&mut self) {
+        self.driven = [false; ROWS];
+    }
+
+    /// Reads the columns of the matrix, returning `true` for each column connected to a driven row
+    /// through a pressed key.
+    ///
+    /// The columns are read as logical levels. On hardware where rows are driven low and columns are
+    /// pulled high, the pin levels are the inverse of these values.
+    pub fn read_columns(&self) -> [bool; COLS] {
+        let pressed: [[bool; COLS]; ROWS] =
+            std::array::from_fn(|row| std::array::from_fn(|col| self.is_pressed(row, col)));
+        let mut rows = self.driven;
+        let mut cols = [false; COLS];
+
+        // Without diodes, current also flows backwards from a column into every other row with a pressed
+        // key on that column, so keep spreading until no more rows or columns are reached.
+        loop {
+            let mut spread = false;
+            for (row, keys) in pressed.iter().enumerate() {
+                for (col, &key) in keys.iter().enumerate() {
+                    if key && rows[row] && !cols[col] {
+                        cols[col] = true;
+                        spread = true;
+                    }
+                    if key && cols[col] && !rows[row] && !self.diodes {
+                        rows[row] = true;
+                        spread = true;
+                    }
+                }
+            }
+            if !spread {
+                return cols;
+            }
+        }
+    }
+
+    /// Reads a single column of the matrix, as [`read_columns`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `col` is outside the matrix.
+    ///
+    /// [`read_columns`]: crate::Keypad::read_columns
+    pub fn read_column(&self, col: usize) -> bool {
+        self.read_columns()[col]
+    }
+}
+
+impl Keypad<4, 4> {
+    /// Creates a keypad laid out as a common 4x4 membrane keypad:
+    ///
+    /// ```text
+    /// 1 2 3 A
+    /// 4 5 6 B
+    /// 7 8 9 C
+    /// * 0 # D
+    /// ```
+    ///
+    /// The digits are bound to both the number row and the numeric keypad of the host keyboard, the
+    /// letters to the letter keys, `*` to the keypad multiply key and `#` to the keypad divide key.
+    pub fn standard() -> Self {
+        let layout = [
+            [Keycode::Num1, Keycode::Num2, Keycode::Num3, Keycode::A],
+            [Keycode::Num4, Keycode::Num5, Keycode::Num6, Keycode::B],
+            [Keycode::Num7, Keycode::Num8, Keycode::Num9, Keycode::C],
+            [
+                Keycode::KpMultiply,
+                Keycode::Num0,
+                Keycode::KpDivide,
+                Keycode::D,
+            ],
+        ];
+        let keypad_digits = [
+            [Keycode::Kp1, Keycode::Kp2, Keycode::Kp3],
+            [Keycode::Kp4, Keycode::Kp5, Keycode::Kp6],
+            [Keycode::Kp7, Keycode::Kp8, Keycode::Kp9],
+        ];
+        let mut keypad = Self::with_layout(layout).bind(Keycode::Kp0, 3, 1);
+        for (row, keys) in keypad_digits.iter().enumerate() {
+            for (col, &key) in keys.iter().enumerate() {
+                keypad = keypad.bind(key, row, col);
+            }
+        }
+        keypad
+    }
+}
+
+// * Touch Panel *
+
+/// A channel which a [`ResistiveTouch`] controller can measure.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TouchChannel {
+    /// The position of the touch along the X plate
+    X,
+    /// The position of the touch along the Y plate
+    Y,
+    /// The first pressure measurement, which rises as the panel is pressed harder
+    Z1,
+    /// The second pressure measurement, which falls as the panel is pressed harder
+    Z2,
+}
+
+// The resistances in ohms used to model pressure readings: the X plate of the panel, and the contact
+// between the plates of a typical touch.
+const X_PLATE_OHMS: f64 = 400.0;
+const TOUCH_OHMS: f64 = 350.0;
+
+/// A simulated 4-wire resistive touch panel over an area of the window, read through a 12-bit touch
+/// controller in the style of the XPT2046 or ADS7843.
+///
+/// Pressing the left mouse button inside the area touches the panel, dragging moves the touch and
+/// releasing the button (or dragging off the area) lifts it. Touch calibration code can then read the
+/// panel with [`read`], or send controller commands to it with [`transfer`], and receives raw ADC
+/// readings just like those of real hardware:
+///
+/// - the readings only span part of the ADC range, from a minimum to a maximum set with
+///   [`raw_range_x`] and [`raw_range_y`], which may be reversed for panels mounted upside down
+/// - the X and Y axes of the panel may be swapped relative to the screen with [`swap_axes`]
+/// - every reading has random noise added to it, of an amplitude set with [`noise`]
+///
+/// The panel is fixed to the glass of the screen, so its axes follow the physical dots of the screen
+/// whatever its [`Orientation`].
+///
+/// # Examples
+///
+/// ```
+/// # use simulate_lcd::{ResistiveTouch, TouchChannel};
+/// # use sdl2::rect::Rect;
+/// let mut touch = ResistiveTouch::new(Rect::new(0, 0, 320, 240))
+///     .raw_range_x(3900, 200)
+///     .noise(0);
+/// touch.touch(0, 120);
+/// assert_eq!(touch.read(TouchChannel::X), 3900);
+/// ```
+///
+/// [`read`]: crate::ResistiveTouch::read
+/// [`transfer`]: crate::ResistiveTouch::transfer
+/// [`raw_range_x`]: crate::ResistiveTouch::raw_range_x
+/// [`raw_range_y`]: crate::ResistiveTouch::raw_range_y
+/// [`swap_axes`]: crate::ResistiveTouch::swap_axes
+/// [`noise`]: crate::ResistiveTouch::noise
+/// [`Orientation`]: crate::Orientation
+#[derive(Debug, Clone)]
+pub struct ResistiveTouch {
+    area: Rect,
+    x_range: (u16, u16),
+    y_range: (u16, u16),
+    swap_axes: bool,
+    noise: u16,
+    rng: u64,
+    // The position of the touch as fractions of the width and height of the area
+    position: Option<(f64, f64)>,
+}
+
+impl ResistiveTouch {
+    /// The largest reading of the 12-bit ADC of the controller.
+    pub const ADC_MAX: u16 = 4095;
+
+    /// Creates an untouched panel covering `area` of the window, typically the [`dot_area`] of a screen.
+    ///
+    /// The panel initially reads from 200 to 3900 along X and from 240 to 3850 along Y, with noise of up
+    /// to 6 counts, which is typical of small panels.
+    ///
+    /// [`dot_area`]: crate::LcdScreen::dot_area
+    pub fn new(area: Rect) -> Self {
+        Self {
+            area,
+            x_range: (200, 3900),
+            y_range: (240, 3850),
+            swap_axes: false,
+            noise: 6,
+            rng: 0x2545_f491_4f6c_dd1d,
+            position: None,
+        }
+    }
+
+    /// Sets the X readings at the left and right edges of the area. Values above [`ADC_MAX`] are
+    /// clamped to it.
+    ///
+    /// [`ADC_MAX`]: crate::ResistiveTouch::ADC_MAX
+    pub fn raw_range_x(mut self, left: u16, right: u16) -> Self {
+        self.x_range = (left.min(Self::ADC_MAX), right.min(Self::ADC_MAX));
+        self
+    }
+
+    /// Sets the Y readings at the top and bottom edges of the area. Values above [`ADC_MAX`] are
+    /// clamped to it.
+    ///
+    /// [`ADC_MAX`]: crate::ResistiveTouch::ADC_MAX
+    pub fn raw_range_y(mut self, top: u16, bottom: u16) -> Self {
+        self.y_range = (top.min(Self::ADC_MAX), bottom.min(Self::ADC_MAX));
+        self
+    }
+
+    /// Sets whether the X plate of the panel runs vertically and the Y plate horizontally, so that the X
+    /// channel measures the vertical position of a touch. The ranges set by [`raw_range_x`] and
+    /// [`raw_range_y`] still apply to the X and Y channels.
+    ///
+    /// [`raw_range_x`]: crate::ResistiveTouch::raw_range_x
+    /// [`raw_range_y`]: crate::ResistiveTouch::raw_range_y
+    pub fn swap_axes(mut self, swap: bool) -> Self {
+        self.swap_axes = swap;
+        self
+    }
+
+    /// Sets the largest amount of noise in ADC counts added to, or subtracted from, each reading.
+    pub fn noise(mut self, amplitude: u16) -> Self {
+        self.noise = amplitude;
+        self
+    }
+
+    /// Seeds the generator of the noise, so that a sequence of readings can be reproduced.
+    pub fn seed(mut self, seed: u64) -> Self {
+        // Note: the xorshift generator never leaves zero, so zero is not a valid state
+        self.rng = seed.max(1);
+        self
+    }
+
+    /// Touches the panel at the point `(x, y)` of the window, or lifts the touch if the point is outside
+    /// the area of the panel.
+    pub fn touch(&mut self, x: i32, y: i32) {
+        self.position = self.area.contains_point((x, y)).then(|| {
+            // The first and last pixels of the area are at the edges of the panel
+            let fraction = |offset: i32, length: u32| -> f64 {
+                f64::from(offset) / f64::from(length.saturating_sub(1).max(1))
+            };
+            (
+                fraction(x - self.area.x(), self.area.width()),
+                fraction(y - self.area.y(), self.area.height()),
+            )
+        });
+    }
+
+    /// Lifts any touch from the panel.
+    pub fn release(&mut self) {
+        self.position = None;
+    }
+
+    /// Returns whether the panel is touched. This is the inverse of the level of the active-low PENIRQ
+    /// output of the controller.
+    pub fn is_touched(&self) -> bool {
+        self.position.is_some()
+    }
+
+    /// Touches, moves and lifts the touch on the panel according to an SDL mouse event, returning whether
+    /// the event was for the panel.
+    pub fn handle_event(&mut self, event: &Event) -> bool {
+        match *event {
+            Event::MouseButtonDown {
+                mouse_btn: MouseButton::Left,
+                x,
+                y,
+                ..
+            } => {
+                self.touch(x, y);
+                self.is_touched()
+            }
+            Event::MouseMotion {
+                mousestate, x, y, ..
+            } if mousestate.left() && self.is_touched() => {
+                self.touch(x, y);
+                true
+            }
+            Event::MouseButtonUp {
+                mouse_btn: MouseButton::Left,
+                ..
+            } if self.is_touched() => {
+                self.release();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    // Draws a random offset of at most the noise amplitude, using a xorshift generator.
+    fn next_noise(&mut self) -> i32 {
+        if self.noise == 0 {
+            return 0;
+        }
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 7;
+        self.rng ^= self.rng << 17;
+        let span = 2 * u64::from(self.noise) + 1;
+        // Note: the remainder is at most 2 * u16::MAX, so this is a true cast
+        (self.rng % span) as i32 - i32::from(self.noise)
+    }
+
+    /// Measures a channel of the panel, returning a 12-bit reading between 0 and [`ADC_MAX`].
+    ///
+    /// While the panel is not touched, the X, Y and Z1 channels read 0 and the Z2 channel reads
+    /// [`ADC_MAX`], without noise. While it is touched, the pressure readings satisfy the usual formula
+    /// for the touch resistance, `R_touch = R_x_plate * X / 4096 * (Z2 / Z1 - 1)`, for a touch of 350
+    /// ohms on an X plate of 400 ohms.
+    ///
+    /// [`ADC_MAX`]: crate::ResistiveTouch::ADC_MAX
+    pub fn read(&mut self, channel: TouchChannel) -> u16 {
+        let Some((across, down)) = self.position else {
+            return match channel {
+                TouchChannel::Z2 => Self::ADC_MAX,
+                _ => 0,
+            };
+        };
+        let (x_fraction, y_fraction) = if self.swap_axes {
+            (down, across)
+        } else {
+            (across, down)
+        };
+        let scale = |fraction: f64, (start, end): (u16, u16)| -> f64 {
+            f64::from(start) + fraction * (f64::from(end) - f64::from(start))
+        };
+        let x = scale(x_fraction, self.x_range);
+        let z1 = x * X_PLATE_OHMS / (X_PLATE_OHMS + TOUCH_OHMS);
+        let value = match channel {
+            TouchChannel::X => x,
+            TouchChannel::Y => scale(y_fraction, self.y_range),
+            TouchChannel::Z1 => z1,
+            TouchChannel::Z2 => z1 + 4096.0 * TOUCH_OHMS / (X_PLATE_OHMS + TOUCH_OHMS),
+        };
+        // Note: the reading is clamped to the range of a u16, so this is a true cast
+        (value.round() as i32 + self.next_noise()).clamp(0, i32::from(Self::ADC_MAX)) as u16
+    }
+
+    /// Executes a controller command byte, as sent over SPI to an XPT2046 or ADS7843, and returns the
+    /// conversion result right-aligned.
+    ///
+    /// The channel is selected by bits 6 to 4 of the command: `0b101` for X, `0b001` for Y, `0b011` for
+    /// Z1 and `0b100` for Z2, so the usual commands `0xD0`, `0x90`, `0xB0` and `0xC0` read those
+    /// channels. If bit 3 is set the result is reduced to 8 bits. Commands without the start bit 7, or
+    /// for channels other than the touch channels, return 0.
+    pub fn transfer(&mut self, command: u8) -> u16 {
+        if command & 0x80 == 0 {
+            return 0;
+        }
+        let channel = match (command >> 4) & 0b111 {
+            0b101 => TouchChannel::X,
+            0b001 => TouchChannel::Y,
+            0b011 => TouchChannel::Z1,
+            0b100 => TouchChannel::Z2,
+            _ => return 0,
+        };
+        let reading = self.read(channel);
+        if command & 0x08 != 0 {
+            reading >> 4
+        } else {
+            reading
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sdl2::keyboard::Mod;
+
+    fn key(key: Keycode, down: bool) -> Event {
+        if down {
+            Event::KeyDown {
+                timestamp: 0,
+                window_id: 0,
+                keycode: Some(key),
+                scancode: None,
+                keymod: Mod::NOMOD,
+                repeat: false,
+            }
+        } else {
+            Event::KeyUp {
+                timestamp: 0,
+                window_id: 0,
+                keycode: Some(key),
+                scancode: None,
+                keymod: Mod::NOMOD,
+                repeat: false,
+            }
+        }
+    }
+
+    #[test]
+    fn test_events() {
+        let mut keypad = Keypad::<4, 4>::standard();
+        assert!(!keypad.handle_event(&key(Keycode::E, true)));
+        assert!(keypad.handle_event(&key(Keycode::Num5, true)));
+        // A second host key bound to the same matrix key does not press it again
+        assert!(keypad.handle_event(&key(Keycode::Kp5, true)));
+        assert!(keypad.handle_event(&key(Keycode::Num5, false)));
+        assert!(keypad.is_pressed(1, 1));
+        assert!(keypad.handle_event(&key(Keycode::Kp5, false)));
+        assert!(keypad.handle_event(&key(Keycode::KpDivide, true)));
+
+        assert_eq!(
+            keypad.poll_event(),
+            Some(KeypadEvent::Pressed { row: 1, col: 1 })
+        );
+        assert_eq!(
+            keypad.poll_event(),
+            Some(KeypadEvent::Released { row: 1, col: 1 })
+        );
+        assert_eq!(
+            keypad.poll_event(),
+            Some(KeypadEvent::Pressed { row: 3, col: 2 })
+        );
+        assert_eq!(keypad.poll_event(), None);
+    }
+
+    #[test]
+    fn test_scan() {
+        let mut keypad = Keypad::<4, 4>::new();
+        keypad.set_pressed(0, 0, true);
+        keypad.set_pressed(0, 2, true);
+        keypad.set_pressed(1, 0, true);
+
+        assert_eq!(keypad.read_columns(), [false; 4]);
+        keypad.drive_row(0);
+        assert_eq!(keypad.read_columns(), [true, false, true, false]);
+        keypad.drive_row(1);
+        assert_eq!(keypad.read_columns(), [true, false, false, false]);
+        keypad.drive_rows([false, false, true, true]);
+        assert_eq!(keypad.read_columns(), [false; 4]);
+
+        // Without diodes, (1, 2) appears pressed through (1, 0), (0, 0) and (0, 2)
+        let mut keypad = keypad.diodes(false);
+        keypad.drive_row(1);
+        assert_eq!(keypad.read_columns(), [true, false, true, false]);
+        keypad.release_rows();
+        assert!(!keypad.read_column(0));
+    }
+
+    #[test]
+    fn test_touch() {
+        let mut touch = ResistiveTouch::new(Rect::new(10, 20, 101, 51))
+            .raw_range_x(1000, 3000)
+            .noise(0);
+        assert_eq!(touch.read(TouchChannel::X), 0);
+        assert_eq!(touch.transfer(0xC0), ResistiveTouch::ADC_MAX);
+
+        touch.touch(60, 45);
+        assert!(touch.is_touched());
+        assert_eq!(touch.read(TouchChannel::X), 2000);
+        assert_eq!(touch.transfer(0x90), 2045);
+        assert_eq!(touch.transfer(0xD8), 2000 >> 4);
+        assert_eq!(touch.transfer(0x50), 0);
+        let (z1, z2) = (touch.transfer(0xB0), touch.transfer(0xC0));
+        assert!(z1 > 0 && z2 > z1);
+
+        // Swapping the axes makes X measure the vertical position and Y the horizontal position
+        touch.touch(35, 60);
+        assert_eq!(touch.read(TouchChannel::X), 1500);
+        assert_eq!(touch.read(TouchChannel::Y), 3128);
+        let mut touch = touch.swap_axes(true);
+        assert_eq!(touch.read(TouchChannel::X), 2600);
+        assert_eq!(touch.read(TouchChannel::Y), 1143);
+
+        touch.touch(9, 45);
+        assert!(!touch.is_touched());
+
+        let mut noisy = ResistiveTouch::new(Rect::new(0, 0, 10, 10))
+            .raw_range_x(2000, 2000)
+            .noise(5)
+            .seed(7);
+        noisy.touch(5, 5);
+        let readings: Vec<u16> = (0..100).map(|_| noisy.read(TouchChannel::X)).collect();
+        assert!(readings
+            .iter()
+            .all(|reading| (1995..=2005).contains(reading)));
+        assert!(readings.iter().any(|&reading| reading != 2000));
+    }
+}
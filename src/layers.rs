@@ -0,0 +1,270 @@
+// * Simulate LCD: A Simple LCD Screen Simulator *
+
+// Copyright 2023 Simon Varey - github.com/simonvarey
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// Imports
+
+use crate::{Bitmap, LcdError, LcdScreen};
+
+// Blend Operations
+
+/// How the dots of a [`Layer`] are combined with the dots of the layers below it, as on controllers such
+/// as the RA8835 and SED1335.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Blend {
+    /// A dot is 'on' if it is 'on' in the layer or below it. This is the default operation.
+    #[default]
+    Or,
+    /// A dot is 'on' if it is 'on' in either the layer or below it, but not both, so the layer inverts
+    /// the dots below its 'on' dots, as for a text cursor or a highlighted menu item.
+    Xor,
+    /// A dot is 'on' only if it is 'on' in both the layer and below it, so the layer masks the dots
+    /// below it.
+    And,
+}
+
+impl Blend {
+    fn apply(self, below: bool, layer: bool) -> bool {
+        match self {
+            Blend::Or => below | layer,
+            Blend::Xor => below ^ layer,
+            Blend::And => below & layer,
+        }
+    }
+}
+
+// * Layer *
+
+/// A plane of dots of a [`LayerStack`], the size of a screen of `R` rows and `C` columns.
+///
+/// The bitmap of a layer can be shifted by an offset, in which case the dots shifted past the edges of
+/// the screen are not shown, and the dots uncovered at the other edges are 'off'. A layer which is not
+/// visible is left out of the stack.
+///
+/// # Examples
+///
+/// ```
+/// # use simulate_lcd::{Blend, Layer};
+/// let cursor = Layer {
+///     row_offset: 8,
+///     col_offset: 30,
+///     blend: Blend::Xor,
+///     ..Layer::<32, 128>::new(Box::new([[true; 128]; 32]))
+/// };
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Layer<const R: usize, const C: usize> {
+    /// The dots of the layer, with `true` for a dot which is 'on'
+    pub bitmap: Box<Bitmap<C, R>>,
+    /// The number of rows the bitmap is shifted down by, or up by if negative
+    pub row_offset: isize,
+    /// The number of columns the bitmap is shifted right by, or left by if negative
+    pub col_offset: isize,
+    /// Whether the layer is shown
+    pub visible: bool,
+    /// How the dots of the layer are combined with the dots below it
+    pub blend: Blend,
+}
+
+impl<const R: usize, const C: usize> Layer<R, C> {
+    /// Creates a visible layer of the dots of `bitmap`, with no offset, combined with the layers below
+    /// it by [`Blend::Or`].
+    pub fn new(bitmap: Box<Bitmap<C, R>>) -> Self {
+        Self {
+            bitmap,
+            row_offset: 0,
+            col_offset: 0,
+            visible: true,
+            blend: Blend::default(),
+        }
+    }
+
+    // Returns whether the dot of the layer shown at `row` and `col` of the screen is 'on', taking its
+    // offset into account.
+    fn dot(&self, row: usize, col: usize) -> bool {
+        // Note: screen sizes are the lengths of arrays, so they fit in an isize. A dot whose position
+        //   on the layer overflows an isize is far off the layer
+        let (Some(row), Some(col)) = (
+            (row as isize).checked_sub(self.row_offset),
+            (col as isize).checked_sub(self.col_offset),
+        ) else {
+            return false;
+        };
+        (0..R as isize).contains(&row)
+            && (0..C as isize).contains(&col)
+            && self.bitmap[row as usize][col as usize]
+    }
+}
+
+impl<const R: usize, const C: usize> Default for Layer<R, C> {
+    fn default() -> Self {
+        // Note: the vec must be R in length, so this cannot fail
+        Self::new(vec![[false; C]; R].into_boxed_slice().try_into().unwrap())
+    }
+}
+
+// * Layer Stack *
+
+/// A stack of [`Layer`]s composited into the dots of a screen of `R` rows and `C` columns, like the
+/// overlaid graphics and text planes of controllers such as the RA8835 and SED1335.
+///
+/// The layers are composited from the bottom of the stack, the first layer pushed, to the top. Starting
+/// from all dots 'off', each visible layer is combined with the dots below it by its [`Blend`]
+/// operation, so the bottom layer usually uses [`Blend::Or`]. The stack is drawn to a screen with
+/// [`LcdScreen::draw_layers`], which, like [`draw_bitmap`], only redraws the dots which have changed.
+///
+/// # Examples
+///
+/// ```
+/// # use simulate_lcd::{Blend, Layer, LayerStack};
+/// let mut stack = LayerStack::<1, 4>::new();
+/// stack.push(Layer::new(Box::new([[true, true, false, false]])));
+/// let cursor = stack.push(Layer {
+///     col_offset: 1,
+///     blend: Blend::Xor,
+///     ..Layer::new(Box::new([[true, true, false, false]]))
+/// });
+/// assert_eq!(*stack.composite(), [[true, false, true, false]]);
+///
+/// stack.get_mut(cursor).unwrap().visible = false;
+/// assert_eq!(*stack.composite(), [[true, true, false, false]]);
+/// ```
+///
+/// [`LcdScreen::draw_layers`]: crate::LcdScreen::draw_layers
+/// [`draw_bitmap`]: crate::LcdScreen::draw_bitmap
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct LayerStack<const R: usize, const C: usize> {
+    layers: Vec<Layer<R, C>>,
+}
+
+impl<const R: usize, const C: usize> LayerStack<R, C> {
+    /// Creates an empty stack, which composites to all dots 'off'.
+    pub fn new() -> Self {
+        Self { layers: Vec::new() }
+    }
+
+    /// Adds a layer to the top of the stack, and returns its index in the stack.
+    pub fn push(&mut self, layer: Layer<R, C>) -> usize {
+        self.layers.push(layer);
+        self.layers.len() - 1
+    }
+
+    /// Removes the top layer of the stack and returns it, or `None` if the stack is empty.
+    pub fn pop(&mut self) -> Option<Layer<R, C>> {
+        self.layers.pop()
+    }
+
+    /// Returns the layer at `index` in the stack, counting from the bottom, or `None` if there is no such
+    /// layer.
+    pub fn get(&self, index: usize) -> Option<&Layer<R, C>> {
+        self.layers.get(index)
+    }
+
+    /// Returns the layer at `index` in the stack, counting from the bottom, for changing its dots,
+    /// offset, visibility or blend operation, or `None` if there is no such layer.
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut Layer<R, C>> {
+        self.layers.get_mut(index)
+    }
+
+    /// Returns the number of layers in the stack.
+    pub fn len(&self) -> usize {
+        self.layers.len()
+    }
+
+    /// Returns whether the stack has no layers.
+    pub fn is_empty(&self) -> bool {
+        self.layers.is_empty()
+    }
+
+    /// Returns the dots of the visible layers of the stack combined by their blend operations.
+    pub fn composite(&self) -> Box<Bitmap<C, R>> {
+        // Note: the vec must be R in length, so this cannot fail
+        let mut bm: Box<Bitmap<C, R>> = vec![[false; C]; R].into_boxed_slice().try_into().unwrap();
+        for layer in self.layers.iter().filter(|layer| layer.visible) {
+            for (row, row_bm) in bm.iter_mut().enumerate() {
+                for (col, dot) in row_bm.iter_mut().enumerate() {
+                    *dot = layer.blend.apply(*dot, layer.dot(row, col));
+                }
+            }
+        }
+        bm
+    }
+}
+
+impl<const R: usize, const C: usize> LcdScreen<R, C> {
+    /// Composites a [`LayerStack`] and draws the result to the screen. Like [`draw_bitmap`], only the dots
+    /// which have changed since the screen was last drawn are redrawn.
+    ///
+    /// # Errors
+    ///
+    /// - [`LcdError::Fill`] when there is an error filling one of the dots with the relevant color
+    /// - [`LcdError::Texture`] when there is an error drawing the dots through a texture
+    ///
+    /// [`draw_bitmap`]: crate::LcdScreen::draw_bitmap
+    ///
+    pub fn draw_layers(&mut self, stack: &LayerStack<R, C>) -> Result<(), LcdError> {
+        self.draw_bitmap(&*stack.composite())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_blends() {
+        let below = [[true, true, false, false]];
+        let layer = [[true, false, true, false]];
+        for (blend, expected) in [
+            (Blend::Or, [[true, true, true, false]]),
+            (Blend::Xor, [[false, true, true, false]]),
+            (Blend::And, [[true, false, false, false]]),
+        ] {
+            let mut stack = LayerStack::<1, 4>::new();
+            stack.push(Layer::new(Box::new(below)));
+            stack.push(Layer {
+                blend,
+                ..Layer::new(Box::new(layer))
+            });
+            assert_eq!(*stack.composite(), expected);
+        }
+    }
+
+    #[test]
+    fn test_offsets() {
+        let mut stack = LayerStack::<2, 3>::new();
+        let layer = stack.push(Layer::new(Box::new([[true, false, false], [false; 3]])));
+        stack.get_mut(layer).unwrap().row_offset = 1;
+        stack.get_mut(layer).unwrap().col_offset = 2;
+        assert_eq!(*stack.composite(), [[false; 3], [false, false, true]]);
+
+        // Dots shifted off the screen are not shown
+        stack.get_mut(layer).unwrap().col_offset = -1;
+        assert_eq!(*stack.composite(), [[false; 3]; 2]);
+        stack.get_mut(layer).unwrap().row_offset = isize::MIN;
+        stack.get_mut(layer).unwrap().col_offset = isize::MAX;
+        assert_eq!(*stack.composite(), [[false; 3]; 2]);
+
+        // A hidden layer leaves the layers above it combined with the layers below it
+        stack.get_mut(layer).unwrap().visible = false;
+        stack.push(Layer {
+            blend: Blend::Xor,
+            ..Layer::new(Box::new([[true; 3]; 2]))
+        });
+        assert_eq!(*stack.composite(), [[true; 3]; 2]);
+        assert_eq!(stack.len(), 2);
+        assert!(stack.pop().is_some());
+    }
+}
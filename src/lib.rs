@@ -21,17 +21,76 @@
 // Imports
 
 use std::{
+    cell::{Ref, RefCell, RefMut},
     error::Error,
+    ffi::CString,
     fmt::{Display, Formatter, Result as FmtResult},
+    rc::Rc,
+    time::{Duration, Instant},
 };
 
+use blink::Attributes;
+use channel::Inbox;
+use refresh::{ScanStep, Scanner};
 use sdl2::{
     pixels::Color,
     rect::Rect,
     render::Canvas,
+    surface::Surface,
     video::{Window, WindowBuildError},
     IntegerOrSdlError, Sdl,
 };
+use texture::TexelBuffer;
+
+// Modules
+
+mod bezel;
+mod blink;
+mod builder;
+mod channel;
+mod diff;
+mod dirty;
+mod dynamic;
+mod input;
+mod layers;
+mod orientation;
+mod panel;
+mod refresh;
+mod runner;
+#[cfg(feature = "server")]
+mod server;
+mod snapshot;
+#[cfg(feature = "async")]
+mod stream;
+mod terminal;
+mod texture;
+mod theme;
+mod viewport;
+mod window;
+
+pub use bezel::{Bezel, Border};
+pub use blink::{Blink, BlinkMask, BlinkStyle, Cursor, CursorShape};
+pub use builder::LcdScreenBuilder;
+pub use channel::{Delivery, DrawCommand, FrameSender};
+pub use diff::{BitmapDiff, DiffColors, DotBounds, DotDiff};
+pub use dirty::{BusLayout, BusModel, Coalescing, FrameStats};
+pub use dynamic::DynLcdScreen;
+pub use input::{Keypad, KeypadEvent, ResistiveTouch, TouchChannel};
+pub use layers::{Blend, Layer, LayerStack};
+pub use orientation::{Orientation, Rotation};
+pub use panel::{Button, ButtonId, DevicePanel, Encoder, EncoderId, Led, LedId, PanelEvent};
+pub use refresh::Refresh;
+pub use runner::{Control, ExitReason, Runner, Tick};
+#[cfg(feature = "server")]
+pub use server::FrameServer;
+pub use snapshot::{DynSnapshot, Snapshot, UPDATE_GOLDEN_VAR};
+#[cfg(feature = "async")]
+pub use stream::EventStream;
+pub use terminal::{Glyphs, TerminalColors, TerminalScreen};
+pub use texture::RenderPath;
+pub use theme::LcdTheme;
+pub use viewport::{ScrollDirection, VirtualCanvas};
+pub use window::{Backend, Fullscreen, LcdWindow, Scaling, WindowOptions, WindowPosition};
 
 // Constants
 
@@ -64,9 +123,18 @@ pub enum LcdError {
     /// error is a simple wrapper around the underlying SDL error. Please consult the [`sdl2`] documentation for
     /// more details.
     Fill(String),
+    /// Indicates that the width or height of the dots of the [`LcdScreen`] is zero. Dots must be at least
+    /// one pixel wide and one pixel high.
+    DotSize {
+        /// the pixel width of the dots of the undisplayed screen
+        dot_width: u32,
+        /// the pixel height of the dots of the undisplayed screen
+        dot_height: u32,
+    },
     /// Indicates that the [`LcdScreen`] is too wide to be displayed. The maximum width of a screen is [`i32::MAX`]
     /// pixels. As the width of the screen is set by the number of rows of dots it has multiplied by the
-    /// pixel width of each dot, one or both of those values must be reduced.
+    /// pixel width of each dot, one or both of those values must be reduced. The width also includes any
+    /// gaps between dots and the [`Bezel`] of the screen.
     ///
     /// [`i32::MAX`]: std::i32::MAX
     WindowWidth {
@@ -79,7 +147,8 @@ pub enum LcdError {
     },
     /// Indicates that the [`LcdScreen`] is too high to be displayed. The maximum height of a screen is [`i32::MAX`]
     /// pixels. As the height of the screen is set by the number of columns of dots it has multiplied by the
-    /// height of each dot, one or both of those values must be reduced.
+    /// height of each dot, one or both of those values must be reduced. The height also includes any
+    /// gaps between dots and the [`Bezel`] of the screen.
     ///
     /// [`i32::MAX`]: std::i32::MAX
     WindowHeight {
@@ -90,6 +159,69 @@ pub enum LcdError {
         /// the pixel height of the dots of the undisplayed screen
         dot_height: u32,
     },
+    /// Indicates that an error occurred when attempting to load or draw the overlay image of the [`Bezel`]
+    /// of the [`LcdScreen`], or the face image of a [`DevicePanel`]. This error is a simple wrapper around
+    /// the underlying SDL error. Please consult the [`sdl2`] documentation for more details.
+    Overlay(String),
+    /// Indicates that an error occurred when attempting to obtain the SDL event pump for a [`Runner`]. This
+    /// error is a simple wrapper around the underlying SDL error. Please consult the [`sdl2`] documentation
+    /// for more details.
+    EventPump(String),
+    /// Indicates that a [`FrameSender`] could not send a command because the [`LcdScreen`] it draws to has
+    /// been dropped, or has replaced its channel with a new one.
+    Disconnected,
+    /// Indicates that [`FrameSender::try_send`] could not send a command because the channel of the
+    /// [`LcdScreen`] is full.
+    ChannelFull {
+        /// the number of commands the channel holds
+        capacity: usize,
+    },
+    /// Indicates that an error occurred when attempting to create, update or copy the texture which the
+    /// dots of an [`LcdScreen`] drawn with [`RenderPath::Texture`] are uploaded to. This error is a simple
+    /// wrapper around the underlying SDL error. Please consult the [`sdl2`] documentation for more
+    /// details.
+    Texture(String),
+    /// Indicates that an error occurred when attempting to open or listen on the socket of a
+    /// `FrameServer`, which is available with the `server` feature. This error is a simple wrapper around
+    /// the underlying I/O error.
+    Socket(std::io::Error),
+    /// Indicates that the bitmap drawn to a [`DynLcdScreen`] does not have one dot for every dot of the
+    /// screen.
+    BitmapSize {
+        /// the number of dots of the screen
+        expected: usize,
+        /// the number of dots of the bitmap
+        actual: usize,
+    },
+    /// Indicates that an error occurred when attempting to write a [`TerminalScreen`] to its terminal.
+    /// This error is a simple wrapper around the underlying I/O error.
+    Terminal(std::io::Error),
+    /// Indicates that the text of a [`Snapshot`] could not be parsed.
+    ParseSnapshot {
+        /// the number of the line of the text with the error, counting from 1
+        line: usize,
+        /// the reason the line could not be parsed
+        reason: String,
+    },
+    /// Indicates that an [`LcdScreen`] added to an [`LcdWindow`] does not fit inside the window.
+    Placement {
+        /// the x coordinate in pixels of the top-left corner of the screen in the window
+        x: i32,
+        /// the y coordinate in pixels of the top-left corner of the screen in the window
+        y: i32,
+        /// the total width in pixels of the screen
+        width: u32,
+        /// the total height in pixels of the screen
+        height: u32,
+    },
+    /// Indicates that a lighting setting of the [`LcdScreen`], such as its brightness or contrast, or the
+    /// duty of a [`Blink`], was given a value outside of the range `0.0` to `1.0` inclusive.
+    OutOfRange {
+        /// the name of the setting
+        setting: &'static str,
+        /// the out of range value
+        value: f32,
+    },
 }
 
 impl Display for LcdError {
@@ -99,10 +231,28 @@ impl Display for LcdError {
             LcdError::WindowBuild(err) => write!(fmtr, "Error building window: {err}"),
             LcdError::CanvasBuild(err) => write!(fmtr, "Error building canvas: {err}"),
             LcdError::Fill(err) => write!(fmtr, "Error filling dot: {err}"),
+            LcdError::EventPump(err) => write!(fmtr, "Error obtaining event pump: {err}"),
+            LcdError::Socket(err) => write!(fmtr, "Error opening socket: {err}"),
+            LcdError::Disconnected => write!(fmtr, "The screen is no longer receiving commands."),
+            LcdError::ChannelFull { capacity }
+                => write!(fmtr, "The channel of the screen is full. It holds {capacity} commands."),
+            LcdError::Overlay(err) => write!(fmtr, "Error drawing overlay image: {err}"),
+            LcdError::Texture(err) => write!(fmtr, "Error drawing dot texture: {err}"),
+            LcdError::DotSize { dot_width, dot_height }
+                => write!(fmtr, "{dot_width}x{dot_height} pixels is not a valid dot size. Dots must be at least 1 pixel wide and 1 pixel high."),
             LcdError::WindowWidth { width, row, dot_width }
                 => write!(fmtr, "{width} pixels is too large for a window width. Window width cannot be larger than {}. Reduce either the number of dot rows {row} or the width {dot_width} of dots.", i32::MAX),
             LcdError::WindowHeight { height, col, dot_height }
                 => write!(fmtr, "{height} pixels is too large for a window height. Window height cannot be larger than {}. Reduce either the number of dot columns {col} or the height {dot_height} of dots.", i32::MAX),
+            LcdError::BitmapSize { expected, actual }
+                => write!(fmtr, "A bitmap of {actual} dots cannot be drawn to a screen of {expected} dots."),
+            LcdError::Terminal(err) => write!(fmtr, "Error writing to terminal: {err}"),
+            LcdError::ParseSnapshot { line, reason }
+                => write!(fmtr, "Error parsing snapshot on line {line}: {reason}."),
+            LcdError::Placement { x, y, width, height }
+                => write!(fmtr, "A {width}x{height} pixel screen at ({x}, {y}) does not fit inside its window."),
+            LcdError::OutOfRange { setting, value }
+                => write!(fmtr, "{value} is not a valid {setting}. The {setting} must be between 0.0 and 1.0 inclusive."),
         }
     }
 }
@@ -130,31 +280,683 @@ impl From<IntegerOrSdlError> for LcdError {
 /// [`draw_bitmap`]: crate::LcdScreen::draw_bitmap
 pub type Bitmap<const C: usize, const R: usize> = [[bool; C]; R];
 
-// LCD Dot
+/// A screen of `R` rows and `C` columns of dots which bitmaps can be drawn to. This trait lets code draw
+/// to an [`LcdScreen`] in a window or a [`TerminalScreen`] in a terminal without knowing which it has.
+///
+/// # Examples
+///
+/// ```
+/// # use simulate_lcd::{Bitmap, DotMatrix, LcdError, TerminalScreen};
+/// fn draw_border<const R: usize, const C: usize>(
+///     screen: &mut impl DotMatrix<R, C>,
+/// ) -> Result<(), LcdError> {
+///     let mut bm: Bitmap<C, R> = [[false; C]; R];
+///     for (row, row_bm) in bm.iter_mut().enumerate() {
+///         for (col, dot) in row_bm.iter_mut().enumerate() {
+///             *dot = row == 0 || row == R - 1 || col == 0 || col == C - 1;
+///         }
+///     }
+///     screen.draw_bitmap(&bm)
+/// }
+///
+/// let mut screen = TerminalScreen::<4, 4, Vec<u8>>::with_writer(
+///     Vec::new(),
+///     &Default::default(),
+///     Default::default(),
+///     Default::default(),
+/// );
+/// draw_border(&mut screen).unwrap();
+/// ```
+pub trait DotMatrix<const R: usize, const C: usize> {
+    /// Draws a bitmap to the screen, with `true` for a dot which is 'on'.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of the screen's own `draw_bitmap` method.
+    ///
+    fn draw_bitmap(&mut self, bm: &Bitmap<C, R>) -> Result<(), LcdError>;
+}
 
-#[derive(Debug)]
-struct LcdDot {
-    rect: Rect,
-    on: bool,
+// Dot Style
+
+/// The shape in which each dot of an [`LcdScreen`] is drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DotShape {
+    /// Each dot fills its whole `dot_width` by `dot_height` rectangle. This is the default shape.
+    #[default]
+    Square,
+    /// Each dot is a rectangle with its corners rounded off to the given radius in pixels. The radius is
+    /// limited to half of the smaller of the dot width and height.
+    Rounded(u32),
+    /// Each dot is a circle, or an ellipse if the dot width and height differ, as on LED matrices.
+    Circle,
 }
 
-impl LcdDot {
-    fn new(x: i32, y: i32, width: u32, height: u32) -> Self {
-        assert!((1..=(i32::MAX as u32)).contains(&width), "INTERNAL ERROR: the width of a TIDot must be > 0 and <= i32::MAX. If you are seeing this error then RusTI-BASIC has a bug.");
-        assert!((1..=(i32::MAX as u32)).contains(&height), "INTERNAL ERROR: the height of a TIDot must be > 0 and <= i32::MAX. If you are seeing this error then RusTI-BASIC has a bug.");
+/// Options controlling the physical appearance of the dots of an [`LcdScreen`].
+///
+/// The default style draws square dots with no gaps between them, which is how [`LcdScreen::new`] draws
+/// its screens. Use [`LcdScreenBuilder::style`] to create a screen with a different style.
+///
+/// # Examples
+///
+/// ```
+/// # use simulate_lcd::{DotShape, DotStyle};
+/// # use sdl2::pixels::Color;
+/// let style = DotStyle {
+///     gap: 1,
+///     shape: DotShape::Circle,
+///     grid_color: Some(Color::RGB(150, 162, 129)),
+///     ..Default::default()
+/// };
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DotStyle {
+    /// The size in pixels of the gap between adjacent dots, both horizontally and vertically
+    pub gap: u32,
+    /// The color of the gaps between dots, and of the area around non-square dots. If `None`, the 'off'
+    /// color of the screen is used.
+    pub gap_color: Option<Color>,
+    /// The shape of each dot
+    pub shape: DotShape,
+    /// If set, 'off' dots are drawn with a one pixel outline of this color, so that the dot matrix remains
+    /// faintly visible when no dots are 'on'. A color close to the 'off' color gives the most realistic
+    /// effect. The outline is not drawn on dots smaller than 3 by 3 pixels.
+    pub grid_color: Option<Color>,
+}
+
+// Computes the horizontal spans covered by a dot of the given shape, relative to the top-left corner
+// of the dot. Consecutive rows with identical spans are merged into a single rect.
+fn shape_spans(shape: DotShape, width: u32, height: u32) -> Vec<Rect> {
+    let inset = |y: u32| -> u32 {
+        let (w, h, y) = (width as f64, height as f64, y as f64 + 0.5);
+        match shape {
+            DotShape::Square => 0,
+            DotShape::Rounded(radius) => {
+                let r = radius.min(width / 2).min(height / 2) as f64;
+                let dy = if y < r {
+                    r - y
+                } else if y > h - r {
+                    y - (h - r)
+                } else {
+                    return 0;
+                };
+                (r - (r * r - dy * dy).max(0.0).sqrt()).round() as u32
+            }
+            DotShape::Circle => {
+                let dy = (y - h / 2.0) / (h / 2.0);
+                ((w / 2.0) * (1.0 - (1.0 - dy * dy).max(0.0).sqrt())).round() as u32
+            }
+        }
+    };
+
+    let mut spans: Vec<Rect> = Vec::new();
+    for y in 0..height {
+        let x = inset(y);
+        if 2 * x >= width {
+            continue;
+        }
+        // Note: width and height have been checked to be <= i32::MAX, so these are true casts
+        match spans.last_mut() {
+            Some(last) if last.x() == x as i32 && last.bottom() == y as i32 => {
+                last.set_height(last.height() + 1)
+            }
+            _ => spans.push(Rect::new(x as i32, y as i32, width - 2 * x, 1)),
+        }
+    }
+    spans
+}
+
+// Colors
+
+// The set of colors used to paint a screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Palette {
+    on: Color,
+    off: Color,
+    gap: Color,
+    grid: Option<Color>,
+}
 
+impl Palette {
+    fn new(on_color: Color, off_color: Color, style: &DotStyle) -> Self {
         Self {
-            rect: Rect::new(
-                x * width as i32, // Note: as this has been checked to be positive, this is a true cast
-                y * height as i32, // Note: as this has been checked to be positive, this is a true cast
-                width,
-                height,
+            on: on_color,
+            off: off_color,
+            gap: style.gap_color.unwrap_or(off_color),
+            grid: style.grid_color,
+        }
+    }
+}
+
+fn lerp_channel(from: u8, to: u8, t: f32) -> u8 {
+    (from as f32 + (to as f32 - from as f32) * t)
+        .round()
+        .clamp(0.0, 255.0) as u8
+}
+
+fn lerp_color(from: Color, to: Color, t: f32) -> Color {
+    Color::RGBA(
+        lerp_channel(from.r, to.r, t),
+        lerp_channel(from.g, to.g, t),
+        lerp_channel(from.b, to.b, t),
+        from.a,
+    )
+}
+
+// Scales each channel of `color` by the corresponding channel of `light`.
+fn light_color(color: Color, light: [f32; 3]) -> Color {
+    let scale = |c: u8, factor: f32| lerp_channel(0, c, factor);
+    Color::RGBA(
+        scale(color.r, light[0]),
+        scale(color.g, light[1]),
+        scale(color.b, light[2]),
+        color.a,
+    )
+}
+
+// Moves `color` by the difference between `from` and `to`.
+fn shift_color(color: Color, from: Color, to: Color) -> Color {
+    let shift = |c: u8, f: u8, t: u8| (c as i16 - f as i16 + t as i16).clamp(0, 255) as u8;
+    Color::RGBA(
+        shift(color.r, from.r, to.r),
+        shift(color.g, from.g, to.g),
+        shift(color.b, from.b, to.b),
+        color.a,
+    )
+}
+
+// Lighting
+
+/// The polarity of an [`LcdScreen`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Polarity {
+    /// 'On' dots are drawn in the 'on' color against the 'off' color, as on positive-mode (e.g.
+    /// transflective) displays. This is the default polarity.
+    #[default]
+    Positive,
+    /// The 'on' and 'off' colors are swapped, so that 'on' dots appear light against a dark
+    /// background, as on negative-mode (e.g. inverted blue/white) displays.
+    Negative,
+}
+
+/// The proportion of full brightness that an [`LcdScreen`] shows when its backlight is switched off,
+/// simulating the ambient light reflected by the panel.
+pub const UNLIT_LEVEL: f32 = 0.35;
+
+// The backlight, contrast and polarity settings of a screen.
+#[derive(Debug, Clone, Copy)]
+struct Lighting {
+    backlight: bool,
+    backlight_color: Color,
+    brightness: f32,
+    contrast: f32,
+    polarity: Polarity,
+}
+
+impl Default for Lighting {
+    fn default() -> Self {
+        Self {
+            backlight: true,
+            backlight_color: Color::WHITE,
+            brightness: 1.0,
+            contrast: 1.0,
+            polarity: Polarity::Positive,
+        }
+    }
+}
+
+impl Lighting {
+    // Computes the colors a screen is displayed with from its base colors.
+    fn apply(&self, base: &Palette) -> Palette {
+        let (on, off) = match self.polarity {
+            Polarity::Positive => (base.on, base.off),
+            Polarity::Negative => (base.off, base.on),
+        };
+        let gap = if base.gap == base.off { off } else { base.gap };
+        let grid = base.grid.map(|grid| shift_color(grid, base.off, off));
+        let on = lerp_color(off, on, self.contrast);
+
+        let level = if self.backlight { self.brightness } else { 0.0 };
+        let lit = |c: u8| UNLIT_LEVEL + (1.0 - UNLIT_LEVEL) * level * (c as f32 / 255.0);
+        let light = [
+            lit(self.backlight_color.r),
+            lit(self.backlight_color.g),
+            lit(self.backlight_color.b),
+        ];
+        Palette {
+            on: light_color(on, light),
+            off: light_color(off, light),
+            gap: light_color(gap, light),
+            grid: grid.map(|grid| light_color(grid, light)),
+        }
+    }
+}
+
+// Checks that a lighting level is between 0.0 and 1.0 inclusive.
+fn check_level(setting: &'static str, value: f32) -> Result<f32, LcdError> {
+    if (0.0..=1.0).contains(&value) {
+        Ok(value)
+    } else {
+        Err(LcdError::OutOfRange { setting, value })
+    }
+}
+
+// Paints dots onto a canvas according to a dot style.
+#[derive(Debug)]
+struct DotPainter {
+    palette: Palette,
+    square: bool,
+    outer: Vec<Rect>,
+    inner: Option<Vec<Rect>>,
+}
+
+impl DotPainter {
+    fn new(palette: Palette, style: &DotStyle, width: u32, height: u32) -> Self {
+        let inner = match style.grid_color {
+            Some(_) if width >= 3 && height >= 3 => {
+                let mut spans = shape_spans(style.shape, width - 2, height - 2);
+                spans.iter_mut().for_each(|span| span.offset(1, 1));
+                Some(spans)
+            }
+            _ => None,
+        };
+        Self {
+            palette,
+            square: style.shape == DotShape::Square,
+            outer: shape_spans(style.shape, width, height),
+            inner,
+        }
+    }
+
+    // Whether the dots need to be painted individually after the window is cleared to the gap color.
+    fn needs_full_paint(&self) -> bool {
+        self.palette.gap != self.palette.off || !self.square || self.inner.is_some()
+    }
+
+    fn fill(canvas: &mut Canvas<Window>, spans: &[Rect], x: i32, y: i32) -> Result<(), LcdError> {
+        let rects: Vec<Rect> = spans
+            .iter()
+            .map(|span| Rect::new(span.x() + x, span.y() + y, span.width(), span.height()))
+            .collect();
+        canvas.fill_rects(&rects).map_err(LcdError::Fill)
+    }
+
+    // Returns the color of a dot which is 'on' or 'off', as drawn through a texture.
+    fn color(&self, on: bool) -> Color {
+        if on {
+            self.palette.on
+        } else {
+            self.palette.off
+        }
+    }
+
+    // Queues the rects which paint a dot in its current state.
+    fn queue(&self, batch: &mut DotBatch, dot: &LcdDot) {
+        let (x, y) = (dot.rect.x(), dot.rect.y());
+        let offset =
+            |span: &Rect| Rect::new(span.x() + x, span.y() + y, span.width(), span.height());
+        match (&self.inner, self.palette.grid, dot.on) {
+            (Some(inner), Some(_), false) => {
+                self.outer
+                    .iter()
+                    .for_each(|span| DotBatch::push(&mut batch.grid, offset(span)));
+                inner
+                    .iter()
+                    .for_each(|span| DotBatch::push(&mut batch.off, offset(span)));
+            }
+            _ => {
+                let rects = if dot.on {
+                    &mut batch.on
+                } else {
+                    &mut batch.off
+                };
+                if self.square {
+                    DotBatch::push(rects, dot.rect)
+                } else {
+                    self.outer
+                        .iter()
+                        .for_each(|span| DotBatch::push(rects, offset(span)));
+                }
+            }
+        }
+    }
+
+    // Fills every queued rect with a single SDL call per color, emptying the batch and returning the
+    // number of rects filled. Grid outlines are filled before the 'off' centres which sit inside them.
+    fn flush(&self, canvas: &mut Canvas<Window>, batch: &mut DotBatch) -> Result<usize, LcdError> {
+        let mut filled = 0;
+        for (color, rects) in [
+            (
+                self.palette.grid.unwrap_or(self.palette.off),
+                &mut batch.grid,
             ),
-            on: false,
+            (self.palette.off, &mut batch.off),
+            (self.palette.on, &mut batch.on),
+        ] {
+            if !rects.is_empty() {
+                canvas.set_draw_color(color);
+                canvas.fill_rects(rects).map_err(LcdError::Fill)?;
+                filled += rects.len();
+                rects.clear();
+            }
+        }
+        Ok(filled)
+    }
+
+    // Paints every dot of a screen whose dot area has just been cleared to the gap color.
+    fn paint_all<'a>(
+        &self,
+        canvas: &mut Canvas<Window>,
+        dots: impl IntoIterator<Item = &'a LcdDot>,
+    ) -> Result<(), LcdError> {
+        // Note: 'off' dots are already the gap color, unless they need painting individually
+        let full_paint = self.needs_full_paint();
+        let mut batch = DotBatch::default();
+        for dot in dots {
+            if full_paint || dot.on {
+                self.queue(&mut batch, dot);
+            }
+        }
+        self.flush(canvas, &mut batch).map(|_| ())
+    }
+
+    // Sets each dot to its new state, and paints only the dots which have changed. Returns the number of
+    // rects filled.
+    fn paint_changes<'a>(
+        &self,
+        canvas: &mut Canvas<Window>,
+        changes: impl IntoIterator<Item = (&'a mut LcdDot, bool)>,
+    ) -> Result<usize, LcdError> {
+        let mut batch = DotBatch::default();
+        for (dot, on) in changes {
+            if dot.on != on {
+                dot.on = on;
+                self.queue(&mut batch, dot);
+            }
+        }
+        self.flush(canvas, &mut batch)
+    }
+}
+
+// Rects queued by a painter, grouped by color.
+#[derive(Debug, Default)]
+struct DotBatch {
+    grid: Vec<Rect>,
+    off: Vec<Rect>,
+    on: Vec<Rect>,
+}
+
+impl DotBatch {
+    // Adds a rect, merging it into the previous rect if they touch along a whole side. Dots are queued
+    // in order, so runs of touching dots of the same color become one rect.
+    fn push(rects: &mut Vec<Rect>, rect: Rect) {
+        if let Some(last) = rects.last_mut() {
+            let same_row = last.y() == rect.y() && last.height() == rect.height();
+            let same_col = last.x() == rect.x() && last.width() == rect.width();
+            if (same_row && (last.right() == rect.x() || rect.right() == last.x()))
+                || (same_col && (last.bottom() == rect.y() || rect.bottom() == last.y()))
+            {
+                let (x, y) = (last.x().min(rect.x()), last.y().min(rect.y()));
+                // Note: the rects touch along a whole side, so the sums are the size of their union
+                *last = if same_row {
+                    Rect::new(x, y, last.width() + rect.width(), last.height())
+                } else {
+                    Rect::new(x, y, last.width(), last.height() + rect.height())
+                };
+                return;
+            }
+        }
+        rects.push(rect);
+    }
+}
+
+// Screen Config
+
+// The full set of options a screen is created with.
+struct ScreenConfig<'a> {
+    title: &'a str,
+    theme: LcdTheme,
+    dot_width: u32,
+    dot_height: u32,
+    style: DotStyle,
+    bezel: Bezel,
+    orientation: Orientation,
+    window: WindowOptions,
+    backend: Backend,
+    render_path: RenderPath,
+}
+
+impl ScreenConfig<'_> {
+    fn geometry(&self) -> Geometry {
+        Geometry {
+            dot_width: self.dot_width,
+            dot_height: self.dot_height,
+            gap: self.style.gap,
+            outer: self.bezel.outer(),
+            frame: self.bezel.frame,
+            orientation: self.orientation,
+            origin: (0, 0),
+        }
+    }
+
+    // Checks that a screen with `rows` rows and `cols` columns of dots can be created with this
+    // configuration, without making any SDL calls, and returns the size of its window in pixels.
+    fn validate(&self, rows: usize, cols: usize) -> Result<(u32, u32), LcdError> {
+        if let Err(err) = CString::new(self.title) {
+            Err(LcdError::WindowBuild(WindowBuildError::InvalidTitle(err)))?
+        }
+        if self.dot_width == 0 || self.dot_height == 0 {
+            Err(LcdError::DotSize {
+                dot_width: self.dot_width,
+                dot_height: self.dot_height,
+            })?
+        }
+        if let Some(path) = &self.bezel.overlay {
+            if !path.is_file() {
+                Err(LcdError::Overlay(format!(
+                    "{} is not a file",
+                    path.display()
+                )))?
+            }
+        }
+
+        self.geometry().window_size(rows, cols)
+    }
+
+    // Opens a window of the given size with this configuration, and returns its canvas.
+    fn open_canvas(
+        &self,
+        sdl_context: &Sdl,
+        window_width: u32,
+        window_height: u32,
+    ) -> Result<Canvas<Window>, LcdError> {
+        let video_subsystem = sdl_context.video().map_err(LcdError::Video)?;
+
+        let window =
+            self.window
+                .build_window(&video_subsystem, self.title, window_width, window_height)?;
+
+        let mut canvas = self.backend.build_canvas(window)?;
+        self.window
+            .configure_canvas(&mut canvas, window_width, window_height)?;
+        Ok(canvas)
+    }
+
+    // Creates the palette and lighting of a screen with this configuration, and the painter of its dots.
+    fn painter(&self) -> (Palette, Lighting, DotPainter) {
+        let palette = Palette::new(self.theme.on_color, self.theme.off_color, &self.style);
+        let lighting = Lighting {
+            backlight_color: self.theme.backlight_color,
+            ..Default::default()
+        };
+        let painter = DotPainter::new(
+            lighting.apply(&palette),
+            &self.style,
+            self.dot_width,
+            self.dot_height,
+        );
+        (palette, lighting, painter)
+    }
+}
+
+// Geometry
+
+// The layout of the dots of a screen within its window.
+#[derive(Debug, Clone, Copy)]
+struct Geometry {
+    dot_width: u32,
+    dot_height: u32,
+    gap: u32,
+    outer: Border,
+    frame: Border,
+    orientation: Orientation,
+    origin: (i32, i32),
+}
+
+impl Geometry {
+    // Computes the size in pixels of the window containing a screen with `rows` rows and `cols` columns
+    // of dots, checking that the window can be displayed.
+    fn window_size(&self, rows: usize, cols: usize) -> Result<(u32, u32), LcdError> {
+        let (prows, pcols) = self.orientation.physical_size(rows, cols);
+        let window_width =
+            span_length(pcols, self.dot_width, self.gap).saturating_add(self.outer.horizontal());
+        let window_height =
+            span_length(prows, self.dot_height, self.gap).saturating_add(self.outer.vertical());
+
+        // Note: if window_width/window_height are between 1 and i32::MAX then both R/C and
+        //   dot_width/dot_height must be between 1 and i32::MAX. Also, i32::MAX can be truly cast to u32.
+        if pcols == 0 || !(1..=(i32::MAX as u32)).contains(&window_width) {
+            Err(LcdError::WindowWidth {
+                width: window_width,
+                row: prows,
+                dot_width: self.dot_width,
+            })?
+        };
+        if prows == 0 || !(1..=(i32::MAX as u32)).contains(&window_height) {
+            Err(LcdError::WindowHeight {
+                height: window_height,
+                col: pcols,
+                dot_height: self.dot_height,
+            })?
+        };
+        Ok((window_width, window_height))
+    }
+
+    // Computes the rect of the dot at logical position `(row, col)` on a screen with `rows` rows and
+    // `cols` columns of dots. The window size of the screen must have been checked by `window_size`.
+    fn dot_rect(&self, row: usize, col: usize, rows: usize, cols: usize) -> Rect {
+        assert!((1..=(i32::MAX as u32)).contains(&self.dot_width), "INTERNAL ERROR: the width of a TIDot must be > 0 and <= i32::MAX. If you are seeing this error then RusTI-BASIC has a bug.");
+        assert!((1..=(i32::MAX as u32)).contains(&self.dot_height), "INTERNAL ERROR: the height of a TIDot must be > 0 and <= i32::MAX. If you are seeing this error then RusTI-BASIC has a bug.");
+
+        let (prow, pcol) = self.orientation.to_physical(row, col, rows, cols);
+        // Note: as the window width and height have been checked to be <= i32::MAX, these are true casts
+        Rect::new(
+            self.origin.0
+                + self.outer.left as i32
+                + pcol as i32 * self.dot_width.saturating_add(self.gap) as i32,
+            self.origin.1
+                + self.outer.top as i32
+                + prow as i32 * self.dot_height.saturating_add(self.gap) as i32,
+            self.dot_width,
+            self.dot_height,
+        )
+    }
+
+    // Finds the logical position of the dot containing the point `(x, y)` of the window, on a screen with
+    // `rows` rows and `cols` columns of dots. Points in the gaps between dots are not in any dot.
+    fn dot_at(&self, x: i32, y: i32, rows: usize, cols: usize) -> Option<(usize, usize)> {
+        let (prows, pcols) = self.orientation.physical_size(rows, cols);
+        let locate = |pos: i32, start: i32, dot: u32, count: usize| -> Option<usize> {
+            let offset = u64::try_from(i64::from(pos) - i64::from(start)).ok()?;
+            let pitch = u64::from(dot) + u64::from(self.gap);
+            let index = usize::try_from(offset / pitch).ok()?;
+            (index < count && offset % pitch < u64::from(dot)).then_some(index)
+        };
+        let area = self.dot_area(rows, cols);
+        let pcol = locate(x, area.x(), self.dot_width, pcols)?;
+        let prow = locate(y, area.y(), self.dot_height, prows)?;
+        Some(self.orientation.to_logical(prow, pcol, rows, cols))
+    }
+
+    // Computes the rect covered by the dots of a screen with `rows` rows and `cols` columns of dots,
+    // including the gaps between them.
+    fn dot_area(&self, rows: usize, cols: usize) -> Rect {
+        let (prows, pcols) = self.orientation.physical_size(rows, cols);
+        // Note: the window width and height have been checked to be <= i32::MAX, so these are true casts
+        Rect::new(
+            self.origin.0 + self.outer.left as i32,
+            self.origin.1 + self.outer.top as i32,
+            span_length(pcols, self.dot_width, self.gap),
+            span_length(prows, self.dot_height, self.gap),
+        )
+    }
+
+    // Computes the rect of the glass inside the frame of a window of the given size.
+    fn glass_rect(&self, window_width: u32, window_height: u32) -> Rect {
+        // Note: the sides of the frame are less than the window width/height, so these are true casts
+        Rect::new(
+            self.origin.0 + self.frame.left as i32,
+            self.origin.1 + self.frame.top as i32,
+            window_width - self.frame.horizontal(),
+            window_height - self.frame.vertical(),
+        )
+    }
+
+    // Computes the rect covered by a screen of the given size, including its bezel.
+    fn bounds(&self, width: u32, height: u32) -> Rect {
+        Rect::new(self.origin.0, self.origin.1, width, height)
+    }
+}
+
+// Bezel Frame
+
+// The frame of a bezel, as drawn around the glass of a screen.
+struct BezelFrame {
+    color: Color,
+    glass: Rect,
+    overlay: Option<Surface<'static>>,
+}
+
+impl BezelFrame {
+    fn paint_overlay(&self, canvas: &mut Canvas<Window>, bounds: Rect) -> Result<(), LcdError> {
+        if let Some(overlay) = &self.overlay {
+            let texture_creator = canvas.texture_creator();
+            let texture = texture_creator
+                .create_texture_from_surface(overlay)
+                .map_err(|err| LcdError::Overlay(err.to_string()))?;
+            canvas
+                .copy(&texture, None, bounds)
+                .map_err(LcdError::Overlay)?;
         }
+        Ok(())
     }
 }
 
+// Scan Out
+
+// The display RAM of a screen which is scanned out to its dots at a refresh rate, and the state of the scan.
+struct ScanOut<const R: usize, const C: usize> {
+    gram: Box<Bitmap<C, R>>,
+    scanner: Scanner,
+    last_update: Instant,
+}
+
+// LCD Dot
+
+#[derive(Debug)]
+struct LcdDot {
+    rect: Rect,
+    on: bool,
+}
+
+// Computes the length in pixels of `count` dots of length `dot` separated by gaps of length `gap`.
+fn span_length(count: usize, dot: u32, gap: u32) -> u32 {
+    let count = u32::try_from(count).unwrap_or(u32::MAX);
+    count
+        .saturating_mul(dot)
+        .saturating_add(count.saturating_sub(1).saturating_mul(gap))
+}
+
 // * LCD Screen *
 
 ///
@@ -218,9 +1020,21 @@ impl LcdDot {
 /// [`new`]: crate::LcdScreen::new
 pub struct LcdScreen<const R: usize, const C: usize> {
     dots: Box<[[LcdDot; C]; R]>,
-    canvas: Canvas<Window>,
-    on_color: Color,
-    off_color: Color,
+    canvas: Rc<RefCell<Canvas<Window>>>,
+    shared: bool,
+    bounds: Rect,
+    base_palette: Palette,
+    lighting: Lighting,
+    painter: DotPainter,
+    frame: Option<BezelFrame>,
+    geometry: Geometry,
+    scan_out: Option<ScanOut<R, C>>,
+    tearing_effect: Option<Box<dyn FnMut()>>,
+    bus: BusModel,
+    stats: FrameStats,
+    texels: Option<TexelBuffer>,
+    inbox: Option<Inbox<R, C>>,
+    attributes: Option<Attributes<R, C>>,
 }
 
 impl<const R: usize, const C: usize> LcdScreen<R, C> {
@@ -260,6 +1074,7 @@ impl<const R: usize, const C: usize> LcdScreen<R, C> {
     /// - [`LcdError::Video`] when there is an error initializing the SDL video subsystem
     /// - [`LcdError::WindowBuild`] when there is an error building the window
     /// - [`LcdError::CanvasBuild`] when there is an error building the window canvas
+    /// - [`LcdError::DotSize`] when `dot_width` or `dot_height` is zero
     /// - [`LcdError::WindowWidth`] when the total window width, in pixels, would exceed [`i32::MAX`]
     /// - [`LcdError::WindowHeight`] when the total window width, in pixels, would exceed [`i32::MAX`]
     ///
@@ -275,74 +1090,865 @@ impl<const R: usize, const C: usize> LcdScreen<R, C> {
         dot_width: u32,
         dot_height: u32,
     ) -> Result<LcdScreen<R, C>, LcdError> {
-        // Note: usize can be truly cast to u32.
-        let window_width = (C as u32) * dot_width;
-        let window_height = (R as u32) * dot_height;
-
-        // Note: if window_width/window_height are between 1 and i32::MAX then both R/C and
-        //   dot_width/dot_height must be between 1 and i32::MAX. Also, i32::MAX can be truly cast to u32.
-        if !(1..=(i32::MAX as u32)).contains(&window_width) {
-            Err(LcdError::WindowWidth {
-                width: window_width,
-                row: R,
+        Self::create(
+            sdl_context,
+            ScreenConfig {
+                title,
+                theme: LcdTheme {
+                    on_color,
+                    off_color,
+                    ..Default::default()
+                },
                 dot_width,
-            })?
-        };
-        if !(1..=(i32::MAX as u32)).contains(&window_height) {
-            Err(LcdError::WindowHeight {
-                height: window_height,
-                col: C,
                 dot_height,
-            })?
-        };
+                style: DotStyle::default(),
+                bezel: Bezel::default(),
+                orientation: Orientation::default(),
+                window: WindowOptions::default(),
+                backend: Backend::default(),
+                render_path: RenderPath::default(),
+            },
+        )
+    }
+
+    /// Returns an [`LcdScreenBuilder`] with the default options, for creating a screen with a theme,
+    /// dot style, bezel, orientation, window options or any other option that [`new`] does not take.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simulate_lcd::{LcdScreen, LcdTheme};
+    /// # let sdl_context = sdl2::init().unwrap();
+    /// let mut screen = LcdScreen::<64, 128>::builder()
+    ///     .title("LCD Example: Builder")
+    ///     .theme(&LcdTheme::WHITE_OLED)
+    ///     .dot_size(5, 5)
+    ///     .build(&sdl_context)
+    ///     .unwrap();
+    /// # std::thread::sleep(std::time::Duration::from_secs(1));
+    /// ```
+    ///
+    /// [`new`]: crate::LcdScreen::new
+    pub fn builder() -> LcdScreenBuilder<R, C> {
+        LcdScreenBuilder::new()
+    }
+
+    // Creates a screen in its own window with the given configuration.
+    fn create(sdl_context: &Sdl, config: ScreenConfig) -> Result<LcdScreen<R, C>, LcdError> {
+        let (window_width, window_height) = config.validate(R, C)?;
 
         // Set up window
 
-        let video_subsystem = sdl_context.video().map_err(LcdError::Video)?;
+        let overlay = match &config.bezel.overlay {
+            Some(path) => Some(Surface::load_bmp(path).map_err(LcdError::Overlay)?),
+            None => None,
+        };
+
+        let canvas = config.open_canvas(sdl_context, window_width, window_height)?;
+
+        Self::assemble(
+            Rc::new(RefCell::new(canvas)),
+            false,
+            config.geometry(),
+            config,
+            overlay,
+        )
+    }
 
-        let window = video_subsystem
-            .window(title, window_width, window_height)
-            .position_centered()
-            .build()?; //TODO: provide more options than just centered
+    // Creates a screen with the given configuration inside a shared window, with its top-left corner
+    // at `(x, y)`.
+    fn create_in(
+        window: &LcdWindow,
+        x: i32,
+        y: i32,
+        config: ScreenConfig,
+    ) -> Result<LcdScreen<R, C>, LcdError> {
+        let (width, height) = config.validate(R, C)?;
+        let geometry = Geometry {
+            origin: (x, y),
+            ..config.geometry()
+        };
+        if !window.contains(geometry.bounds(width, height)) {
+            Err(LcdError::Placement {
+                x,
+                y,
+                width,
+                height,
+            })?
+        }
 
-        let mut canvas = window.into_canvas().build()?;
+        let overlay = match &config.bezel.overlay {
+            Some(path) => Some(Surface::load_bmp(path).map_err(LcdError::Overlay)?),
+            None => None,
+        };
 
-        canvas.set_draw_color(off_color);
-        canvas.clear();
-        canvas.present();
+        Self::assemble(window.canvas.clone(), true, geometry, config, overlay)
+    }
 
-        // Create screen
+    // Creates the dots of a screen drawn on the given canvas, and paints them.
+    fn assemble(
+        canvas: Rc<RefCell<Canvas<Window>>>,
+        shared: bool,
+        geometry: Geometry,
+        config: ScreenConfig,
+        overlay: Option<Surface<'static>>,
+    ) -> Result<LcdScreen<R, C>, LcdError> {
+        let (palette, lighting, painter) = config.painter();
+        let ScreenConfig {
+            theme,
+            bezel,
+            render_path,
+            ..
+        } = config;
+        // Note: the size has already been checked by the caller, so this cannot fail
+        let (width, height) = geometry.window_size(R, C)?;
 
-        //Note: R and C can be truly cast to i32 as they have been proved to be less than i32::MAX
-        let dots_vec: Vec<[LcdDot; C]> = (0..R as i32)
-            .map(|y| {
-                let row_vec: Vec<LcdDot> = (0..C as i32)
-                    .map(|x| LcdDot::new(x, y, dot_width, dot_height))
+        let dots_vec: Vec<[LcdDot; C]> = (0..R)
+            .map(|row| {
+                let row_vec: Vec<LcdDot> = (0..C)
+                    .map(|col| LcdDot {
+                        rect: geometry.dot_rect(row, col, R, C),
+                        on: false,
+                    })
                     .collect();
                 row_vec.try_into().unwrap() // Note: every row_vec must be C in length, so this cannot fail
             })
             .collect();
 
+        let frame = bezel.is_framed().then(|| BezelFrame {
+            color: bezel.color.unwrap_or(theme.bezel_color),
+            glass: geometry.glass_rect(width, height),
+            overlay,
+        });
+
         // Note: dots_vec must be R in length, so this cannot fail
-        Ok(Self {
+        let mut screen = Self {
             dots: dots_vec.try_into().unwrap(),
             canvas,
-            on_color,
-            off_color,
+            shared,
+            bounds: geometry.bounds(width, height),
+            base_palette: palette,
+            texels: Self::texel_buffer(render_path, &painter, &geometry),
+            inbox: None,
+            attributes: None,
+            painter,
+            lighting,
+            frame,
+            geometry,
+            scan_out: None,
+            tearing_effect: None,
+            bus: BusModel::default(),
+            stats: FrameStats::default(),
+        };
+        screen.repaint()?;
+        Ok(screen)
+    }
+
+    // Creates the texel buffer of a screen drawn with `path`, or returns `None` if the screen is drawn
+    // with rects, either because of `path` or because its dots cannot be drawn as one texel each.
+    fn texel_buffer(
+        path: RenderPath,
+        painter: &DotPainter,
+        geometry: &Geometry,
+    ) -> Option<TexelBuffer> {
+        let plain = painter.square && painter.inner.is_none() && geometry.gap == 0;
+        (path == RenderPath::Texture && plain).then(|| {
+            let (prows, pcols) = geometry.orientation.physical_size(R, C);
+            TexelBuffer::new(pcols, prows)
         })
     }
 
-    /// Draws a bitmap to a simulated LCD screen.
+    // Presents the window of the screen, unless the window is shared with other screens.
+    fn present(&self) {
+        if !self.shared {
+            self.canvas.borrow_mut().present();
+        }
+    }
+
+    /// Clears the whole window and redraws every dot of the screen in its current state.
     ///
-    /// # Arguments
+    /// The contents of a window may be lost when it is resized, moved between displays or uncovered, so
+    /// this method should be called on receiving an [`Event::Window`] event for the window of the screen.
     ///
-    /// * `bm` - A [`Bitmap`], or something that can be converted into a bitmap, to write to the LCD screen
+    /// If the screen is in an [`LcdWindow`] shared with other screens, only the area of the window covered
+    /// by this screen is cleared, and the window is not presented.
     ///
-    /// # Examples
+    /// # Errors
     ///
-    /// ```
-    /// # use simulate_lcd::{LcdScreen, LCD_DARK_GREEN, LCD_LIGHT_GREEN};
-    /// # let sdl_context = sdl2::init().unwrap();
+    /// - [`LcdError::Fill`] when there is an error filling one of the dots with the relevant color
+    /// - [`LcdError::Texture`] when there is an error drawing the dots through a texture
+    /// - [`LcdError::Overlay`] when there is an error drawing the overlay image of the bezel
+    ///
+    /// [`Event::Window`]: sdl2::event::Event::Window
+    ///
+    pub fn repaint(&mut self) -> Result<(), LcdError> {
+        let mut canvas = self.canvas.borrow_mut();
+        canvas.set_draw_color(
+            self.frame
+                .as_ref()
+                .map_or(self.painter.palette.gap, |frame| frame.color),
+        );
+        if self.shared {
+            canvas.fill_rect(self.bounds).map_err(LcdError::Fill)?;
+        } else {
+            canvas.clear();
+        }
+        if let Some(frame) = &self.frame {
+            frame.paint_overlay(&mut canvas, self.bounds)?;
+            canvas.set_draw_color(self.painter.palette.gap);
+            canvas.fill_rect(frame.glass).map_err(LcdError::Fill)?;
+        }
+        if let Some(texels) = &mut self.texels {
+            for (row, row_dots) in self.dots.iter().enumerate() {
+                for (col, dot) in row_dots.iter().enumerate() {
+                    let (prow, pcol) = self.geometry.orientation.to_physical(row, col, R, C);
+                    texels.set(pcol, prow, self.painter.color(dot.on));
+                }
+            }
+            texels.draw(&mut canvas, self.geometry.dot_area(R, C))?;
+        } else {
+            self.painter
+                .paint_all(&mut canvas, self.dots.iter().flatten())?;
+        }
+        drop(canvas);
+        self.present();
+        Ok(())
+    }
+
+    /// Returns the [`Orientation`] of the screen.
+    pub fn orientation(&self) -> Orientation {
+        self.geometry.orientation
+    }
+
+    /// Sets the [`Orientation`] of the screen, and repaints the screen.
+    ///
+    /// If the new orientation swaps the width and height of the screen, the window containing the
+    /// screen is resized to match, unless the window is an [`LcdWindow`] shared with other screens. In that
+    /// case the window keeps its size, and any area of it no longer covered by the screen is left as it
+    /// was until the window is cleared with [`LcdWindow::clear`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simulate_lcd::{LcdScreen, Orientation, Rotation, LCD_DARK_GREEN, LCD_LIGHT_GREEN};
+    /// # let sdl_context = sdl2::init().unwrap();
+    /// let mut screen = LcdScreen::<64, 128>::new(
+    ///         &sdl_context,
+    ///         "LCD Example: Portrait",
+    ///         LCD_DARK_GREEN,
+    ///         LCD_LIGHT_GREEN,
+    ///         4,
+    ///         4,
+    ///      )
+    ///      .unwrap();
+    ///
+    /// screen
+    ///     .set_orientation(Orientation {
+    ///         rotation: Rotation::Deg90,
+    ///         ..Default::default()
+    ///     })
+    ///     .unwrap();
+    /// # std::thread::sleep(std::time::Duration::from_secs(1));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// - [`LcdError::WindowWidth`] when the new window width, in pixels, would exceed [`i32::MAX`]
+    /// - [`LcdError::WindowHeight`] when the new window width, in pixels, would exceed [`i32::MAX`]
+    /// - [`LcdError::CanvasBuild`] when there is an error resizing the window or its canvas
+    /// - [`LcdError::Placement`] when the screen is in an [`LcdWindow`] and would no longer fit inside it
+    /// - [`LcdError::Fill`] when there is an error filling one of the dots with the relevant color
+    /// - [`LcdError::Texture`] when there is an error drawing the dots through a texture
+    ///
+    /// [`i32::MAX`]: std::i32::MAX
+    ///
+    pub fn set_orientation(&mut self, orientation: Orientation) -> Result<(), LcdError> {
+        let geometry = Geometry {
+            orientation,
+            ..self.geometry
+        };
+        let (width, height) = geometry.window_size(R, C)?;
+        let bounds = geometry.bounds(width, height);
+        if bounds != self.bounds {
+            if self.shared {
+                let (window_width, window_height) = self.canvas.borrow().logical_size();
+                let window = Rect::new(0, 0, window_width, window_height);
+                if !window.contains_rect(bounds) {
+                    Err(LcdError::Placement {
+                        x: bounds.x(),
+                        y: bounds.y(),
+                        width,
+                        height,
+                    })?
+                }
+            } else {
+                let mut canvas = self.canvas.borrow_mut();
+                canvas.window_mut().set_size(width, height)?;
+                canvas.set_logical_size(width, height)?;
+            }
+            if let Some(frame) = &mut self.frame {
+                frame.glass = geometry.glass_rect(width, height);
+            }
+            self.bounds = bounds;
+        }
+        for (row, row_dots) in self.dots.iter_mut().enumerate() {
+            for (col, dot) in row_dots.iter_mut().enumerate() {
+                dot.rect = geometry.dot_rect(row, col, R, C);
+            }
+        }
+        if self.texels.is_some() {
+            self.texels = Self::texel_buffer(RenderPath::Texture, &self.painter, &geometry);
+        }
+        self.geometry = geometry;
+        if let Some(scan_out) = &mut self.scan_out {
+            let (lines, _) = orientation.physical_size(R, C);
+            scan_out.scanner = Scanner::new(scan_out.scanner.refresh(), lines);
+        }
+        self.repaint()
+    }
+
+    /// Returns the logical `(row, col)` position of the dot at the point `(x, y)` of the window, or `None`
+    /// if the point is not on a dot, for example because it is in a gap between dots or outside the
+    /// screen.
+    ///
+    /// The point is in the coordinates of SDL mouse events, which are the pixel coordinates of the window
+    /// before it is scaled, so clicks map onto the same dot however the window has been resized. The
+    /// position takes the [`Orientation`] of the screen into account, so it can be used to index the
+    /// bitmaps drawn with [`draw_bitmap`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use simulate_lcd::LcdScreen;
+    /// # use sdl2::event::Event;
+    /// # let sdl_context = sdl2::init().unwrap();
+    /// # let screen = LcdScreen::<32, 64>::builder().build(&sdl_context).unwrap();
+    /// # let mut event_pump = sdl_context.event_pump().unwrap();
+    /// for event in event_pump.poll_iter() {
+    ///     if let Event::MouseButtonDown { x, y, .. } = event {
+    ///         if let Some((row, col)) = screen.dot_at(x, y) {
+    ///             println!("Clicked dot ({row}, {col})");
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// [`draw_bitmap`]: crate::LcdScreen::draw_bitmap
+    pub fn dot_at(&self, x: i32, y: i32) -> Option<(usize, usize)> {
+        self.geometry.dot_at(x, y, R, C)
+    }
+
+    /// Returns the area of the window covered by the dots of the screen, including the gaps between them
+    /// but not its [`Bezel`]. This is the area a touch panel such as a [`ResistiveTouch`] covers.
+    pub fn dot_area(&self) -> Rect {
+        self.geometry.dot_area(R, C)
+    }
+
+    /// Returns the [`RenderPath`] used to draw the dots of the screen. This is [`RenderPath::Rects`] if
+    /// the screen was set to draw through a texture, but its [`DotStyle`] cannot be drawn that way.
+    pub fn render_path(&self) -> RenderPath {
+        if self.texels.is_some() {
+            RenderPath::Texture
+        } else {
+            RenderPath::Rects
+        }
+    }
+
+    /// Sets the [`RenderPath`] used to draw the dots of the screen, and repaints the screen. Screens are
+    /// drawn with [`RenderPath::Rects`] by default.
+    ///
+    /// [`RenderPath::Texture`] is only used if the dots are square with no gap between them and no
+    /// visible grid. Otherwise the screen keeps drawing with rects, which [`render_path`] reports.
+    ///
+    /// # Errors
+    ///
+    /// - [`LcdError::Fill`] when there is an error filling one of the dots with the relevant color
+    /// - [`LcdError::Texture`] when there is an error drawing the dots through a texture
+    /// - [`LcdError::Overlay`] when there is an error drawing the overlay image of the bezel
+    ///
+    /// [`render_path`]: crate::LcdScreen::render_path
+    ///
+    pub fn set_render_path(&mut self, path: RenderPath) -> Result<(), LcdError> {
+        self.texels = Self::texel_buffer(path, &self.painter, &self.geometry);
+        self.repaint()
+    }
+
+    /// Returns the window containing the screen.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window is an [`LcdWindow`] whose window is currently borrowed mutably.
+    pub fn window(&self) -> Ref<'_, Window> {
+        Ref::map(self.canvas.borrow(), |canvas| canvas.window())
+    }
+
+    /// Returns the window containing the screen mutably, for example to show a window created hidden or
+    /// to change its title.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window is an [`LcdWindow`] whose window is currently borrowed.
+    pub fn window_mut(&mut self) -> RefMut<'_, Window> {
+        RefMut::map(self.canvas.borrow_mut(), |canvas| canvas.window_mut())
+    }
+
+    // Recomputes the displayed colors from the base colors and the lighting, and repaints the screen.
+    fn relight(&mut self) -> Result<(), LcdError> {
+        self.painter.palette = self.lighting.apply(&self.base_palette);
+        self.repaint()
+    }
+
+    /// Returns whether the backlight of the screen is switched on.
+    pub fn backlight(&self) -> bool {
+        self.lighting.backlight
+    }
+
+    /// Switches the backlight of the screen on or off, and repaints the screen.
+    ///
+    /// While the backlight is off, the screen is displayed at [`UNLIT_LEVEL`] of its full brightness,
+    /// regardless of the brightness set with [`set_brightness`]. Screens are created with the backlight
+    /// switched on.
+    ///
+    /// # Errors
+    ///
+    /// - [`LcdError::Fill`] when there is an error filling one of the dots with the relevant color
+    /// - [`LcdError::Texture`] when there is an error drawing the dots through a texture
+    ///
+    /// [`set_brightness`]: crate::LcdScreen::set_brightness
+    ///
+    pub fn set_backlight(&mut self, on: bool) -> Result<(), LcdError> {
+        self.lighting.backlight = on;
+        self.relight()
+    }
+
+    /// Returns the brightness of the backlight of the screen.
+    pub fn brightness(&self) -> f32 {
+        self.lighting.brightness
+    }
+
+    /// Sets the brightness of the backlight of the screen, and repaints the screen.
+    ///
+    /// A brightness of `1.0` displays the 'on' and 'off' colors of the screen unchanged, while a
+    /// brightness of `0.0` displays the screen as if the backlight were switched off. Screens are created
+    /// with a brightness of `1.0`.
+    ///
+    /// # Arguments
+    ///
+    /// * `brightness` - The brightness of the backlight, between `0.0` and `1.0` inclusive
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simulate_lcd::{LcdScreen, LCD_DARK_GREEN, LCD_LIGHT_GREEN};
+    /// # let sdl_context = sdl2::init().unwrap();
+    /// let mut screen = LcdScreen::<64, 96>::new(
+    ///         &sdl_context,
+    ///         "LCD Example: Dimmed",
+    ///         LCD_DARK_GREEN,
+    ///         LCD_LIGHT_GREEN,
+    ///         10,
+    ///         10,
+    ///      )
+    ///      .unwrap();
+    ///
+    /// screen.set_brightness(0.3).unwrap();
+    /// # std::thread::sleep(std::time::Duration::from_secs(1));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// - [`LcdError::OutOfRange`] when `brightness` is not between `0.0` and `1.0` inclusive
+    /// - [`LcdError::Fill`] when there is an error filling one of the dots with the relevant color
+    /// - [`LcdError::Texture`] when there is an error drawing the dots through a texture
+    ///
+    pub fn set_brightness(&mut self, brightness: f32) -> Result<(), LcdError> {
+        self.lighting.brightness = check_level("brightness", brightness)?;
+        self.relight()
+    }
+
+    /// Returns the contrast of the screen.
+    pub fn contrast(&self) -> f32 {
+        self.lighting.contrast
+    }
+
+    /// Sets the contrast of the screen, and repaints the screen.
+    ///
+    /// The contrast sets how far the color of an 'on' dot is from the color of an 'off' dot. A contrast
+    /// of `1.0` draws 'on' dots in the full 'on' color, while a contrast of `0.0` makes 'on' dots
+    /// indistinguishable from 'off' dots. Screens are created with a contrast of `1.0`.
+    ///
+    /// # Arguments
+    ///
+    /// * `contrast` - The contrast of the screen, between `0.0` and `1.0` inclusive
+    ///
+    /// # Errors
+    ///
+    /// - [`LcdError::OutOfRange`] when `contrast` is not between `0.0` and `1.0` inclusive
+    /// - [`LcdError::Fill`] when there is an error filling one of the dots with the relevant color
+    /// - [`LcdError::Texture`] when there is an error drawing the dots through a texture
+    ///
+    pub fn set_contrast(&mut self, contrast: f32) -> Result<(), LcdError> {
+        self.lighting.contrast = check_level("contrast", contrast)?;
+        self.relight()
+    }
+
+    /// Returns the polarity of the screen.
+    pub fn polarity(&self) -> Polarity {
+        self.lighting.polarity
+    }
+
+    /// Sets the [`Polarity`] of the screen, and repaints the screen.
+    ///
+    /// Screens are created with [`Polarity::Positive`].
+    ///
+    /// # Errors
+    ///
+    /// - [`LcdError::Fill`] when there is an error filling one of the dots with the relevant color
+    /// - [`LcdError::Texture`] when there is an error drawing the dots through a texture
+    ///
+    pub fn set_polarity(&mut self, polarity: Polarity) -> Result<(), LcdError> {
+        self.lighting.polarity = polarity;
+        self.relight()
+    }
+
+    /// Returns the color of the light emitted by the backlight of the screen.
+    pub fn backlight_color(&self) -> Color {
+        self.lighting.backlight_color
+    }
+
+    /// Sets the color of the light emitted by the backlight of the screen, and repaints the screen.
+    ///
+    /// While the backlight is lit, the displayed colors of the screen are multiplied by this color, so
+    /// [`Color::WHITE`] leaves them unchanged. Screens created with [`new`] have a white backlight, while
+    /// screens created with [`LcdScreenBuilder::theme`] use the [`LcdTheme::backlight_color`] of their
+    /// theme.
+    ///
+    /// # Errors
+    ///
+    /// - [`LcdError::Fill`] when there is an error filling one of the dots with the relevant color
+    /// - [`LcdError::Texture`] when there is an error drawing the dots through a texture
+    ///
+    /// [`Color::WHITE`]: sdl2::pixels::Color::WHITE
+    /// [`new`]: crate::LcdScreen::new
+    ///
+    pub fn set_backlight_color(&mut self, color: Color) -> Result<(), LcdError> {
+        self.lighting.backlight_color = color;
+        self.relight()
+    }
+
+    /// Returns the [`Refresh`] timing of the screen, or `None` if bitmaps are shown as soon as they are
+    /// drawn.
+    pub fn refresh(&self) -> Option<Refresh> {
+        self.scan_out
+            .as_ref()
+            .map(|scan_out| scan_out.scanner.refresh())
+    }
+
+    /// Sets the [`Refresh`] timing of the screen, or `None` to show bitmaps as soon as they are drawn, as
+    /// screens do by default.
+    ///
+    /// With a refresh timing, the screen simulates a panel which scans its display RAM out to the glass
+    /// line by line, independently of when the RAM is written. [`draw_bitmap`] only writes the display
+    /// RAM, and the rows of dots are updated from it as the scan reaches them, each time [`update`] or
+    /// [`advance`] is called. A bitmap drawn while the scan is part way down the screen is therefore only
+    /// shown below the scan until the next refresh, tearing the image exactly as it would on hardware.
+    /// The scan follows the physical rows of dots, so on a rotated screen it runs across the bitmap.
+    ///
+    /// Turning the refresh timing off shows the display RAM at once.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use std::{cell::Cell, rc::Rc};
+    /// # use simulate_lcd::{LcdScreen, Refresh};
+    /// # let sdl_context = sdl2::init().unwrap();
+    /// # let mut screen = LcdScreen::<64, 128>::builder().dot_size(4, 4).build(&sdl_context).unwrap();
+    /// screen.set_refresh(Some(Refresh::default())).unwrap();
+    ///
+    /// // Only draw new frames in the blanking period, as firmware waiting for TE would
+    /// let te = Rc::new(Cell::new(false));
+    /// let te_flag = te.clone();
+    /// screen.on_tearing_effect(move || te_flag.set(true));
+    ///
+    /// let mut on = false;
+    /// loop {
+    ///     screen.update().unwrap();
+    ///     if te.replace(false) {
+    ///         on = !on;
+    ///         screen.draw_bitmap(&[[on; 128]; 64]).unwrap();
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// - [`LcdError::Fill`] when there is an error filling one of the dots with the relevant color, while
+    ///   showing the display RAM
+    /// - [`LcdError::Texture`] when there is an error drawing the dots through a texture, while showing
+    ///   the display RAM
+    ///
+    /// [`draw_bitmap`]: crate::LcdScreen::draw_bitmap
+    /// [`update`]: crate::LcdScreen::update
+    /// [`advance`]: crate::LcdScreen::advance
+    ///
+    pub fn set_refresh(&mut self, refresh: Option<Refresh>) -> Result<(), LcdError> {
+        let Some(refresh) = refresh else {
+            return match self.scan_out.take() {
+                Some(scan_out) => self.show_bitmap(&scan_out.gram),
+                None => Ok(()),
+            };
+        };
+        let gram = match self.scan_out.take() {
+            Some(scan_out) => scan_out.gram,
+            None => self.shown_bitmap(),
+        };
+        let (lines, _) = self.geometry.orientation.physical_size(R, C);
+        self.scan_out = Some(ScanOut {
+            gram,
+            scanner: Scanner::new(refresh, lines),
+            last_update: Instant::now(),
+        });
+        Ok(())
+    }
+
+    /// Sets a callback which is called each time the tearing effect (TE) signal of the screen is raised,
+    /// at the start of the blanking period of each refresh. It is only called while the screen has a
+    /// [`Refresh`] timing, from within [`update`] or [`advance`].
+    ///
+    /// The callback cannot draw to the screen itself, but can signal the code which does, for example by
+    /// setting a flag. See [`set_refresh`] for an example.
+    ///
+    /// [`update`]: crate::LcdScreen::update
+    /// [`advance`]: crate::LcdScreen::advance
+    /// [`set_refresh`]: crate::LcdScreen::set_refresh
+    pub fn on_tearing_effect<F: FnMut() + 'static>(&mut self, callback: F) {
+        self.tearing_effect = Some(Box::new(callback));
+    }
+
+    /// Removes the callback set by [`on_tearing_effect`].
+    ///
+    /// [`on_tearing_effect`]: crate::LcdScreen::on_tearing_effect
+    pub fn clear_tearing_effect(&mut self) {
+        self.tearing_effect = None;
+    }
+
+    /// Returns the physical row of dots currently being scanned, or `None` if the scan is in its blanking
+    /// period or the screen has no [`Refresh`] timing.
+    pub fn scan_line(&self) -> Option<usize> {
+        self.scan_out
+            .as_ref()
+            .and_then(|scan_out| scan_out.scanner.line())
+    }
+
+    /// Moves the scan of the screen on by the time since it was last updated, showing the rows of dots it
+    /// reaches, and animates its blinking attributes, such as a blinking [`Cursor`]. This should be called
+    /// frequently, such as on every iteration of the main loop. It does nothing if the screen has no
+    /// [`Refresh`] timing and no blinking attributes.
+    ///
+    /// # Errors
+    ///
+    /// - [`LcdError::Fill`] when there is an error filling one of the dots with the relevant color
+    /// - [`LcdError::Texture`] when there is an error drawing the dots through a texture
+    ///
+    pub fn update(&mut self) -> Result<(), LcdError> {
+        let now = Instant::now();
+        if let Some(attributes) = &mut self.attributes {
+            let elapsed = now.saturating_duration_since(attributes.last_update);
+            attributes.last_update = now;
+            self.advance_attributes(elapsed)?;
+        }
+        let Some(scan_out) = &mut self.scan_out else {
+            return Ok(());
+        };
+        let elapsed = now.saturating_duration_since(scan_out.last_update);
+        scan_out.last_update = now;
+        self.advance_scan(elapsed)
+    }
+
+    /// Moves the scan of the screen and its blinking attributes on by `elapsed`, showing the rows of dots
+    /// the scan reaches. This allows the screen to follow a simulated clock instead of the real time used
+    /// by [`update`]. It does nothing if the screen has no [`Refresh`] timing and no blinking attributes.
+    ///
+    /// If more than a whole refresh has elapsed, only the last refresh is scanned, so the tearing effect
+    /// callback is called once.
+    ///
+    /// # Errors
+    ///
+    /// - [`LcdError::Fill`] when there is an error filling one of the dots with the relevant color
+    /// - [`LcdError::Texture`] when there is an error drawing the dots through a texture
+    ///
+    /// [`update`]: crate::LcdScreen::update
+    ///
+    pub fn advance(&mut self, elapsed: Duration) -> Result<(), LcdError> {
+        self.advance_attributes(elapsed)?;
+        self.advance_scan(elapsed)
+    }
+
+    // Moves the scan of the screen on by `elapsed`, showing the rows of dots it reaches.
+    fn advance_scan(&mut self, elapsed: Duration) -> Result<(), LcdError> {
+        let Some(scan_out) = &mut self.scan_out else {
+            return Ok(());
+        };
+        let (_, pcols) = self.geometry.orientation.physical_size(R, C);
+        let mut canvas = self.canvas.borrow_mut();
+        let mut batch = DotBatch::default();
+        let mut changed = false;
+        for step in scan_out.scanner.advance(elapsed) {
+            match step {
+                ScanStep::TearingEffect => {
+                    if let Some(callback) = &mut self.tearing_effect {
+                        callback();
+                    }
+                }
+                ScanStep::Line(prow) => {
+                    for pcol in 0..pcols {
+                        let (row, col) = self.geometry.orientation.to_logical(prow, pcol, R, C);
+                        let dot = &mut self.dots[row][col];
+                        let bit = scan_out.gram[row][col];
+                        if dot.on != bit {
+                            dot.on = bit;
+                            match &mut self.texels {
+                                Some(texels) => texels.set(pcol, prow, self.painter.color(bit)),
+                                None => self.painter.queue(&mut batch, dot),
+                            }
+                            changed = true;
+                        }
+                    }
+                }
+            }
+        }
+        match &mut self.texels {
+            Some(texels) if changed => texels.draw(&mut canvas, self.geometry.dot_area(R, C))?,
+            Some(_) => {}
+            None => {
+                self.painter.flush(&mut canvas, &mut batch)?;
+            }
+        }
+        drop(canvas);
+        if changed {
+            self.present();
+        }
+        Ok(())
+    }
+
+    /// Returns the [`FrameStats`] of the last bitmap drawn with [`draw_bitmap`].
+    ///
+    /// [`draw_bitmap`]: crate::LcdScreen::draw_bitmap
+    pub fn frame_stats(&self) -> FrameStats {
+        self.stats
+    }
+
+    /// Returns the [`BusModel`] used to estimate the bus traffic of each frame.
+    pub fn bus_model(&self) -> BusModel {
+        self.bus
+    }
+
+    /// Sets the [`BusModel`] used to estimate the bus traffic of each frame drawn from now on, in the
+    /// [`FrameStats`] returned by [`frame_stats`]. By default, the screen is modelled as a controller with
+    /// vertical pages of RAM, such as the SSD1306, updated one run of changed bytes at a time.
+    ///
+    /// [`frame_stats`]: crate::LcdScreen::frame_stats
+    pub fn set_bus_model(&mut self, bus: BusModel) {
+        self.bus = bus;
+    }
+
+    /// Returns the bitmap drawn on the screen. If the screen has a [`Refresh`] timing, this is the
+    /// contents of its display RAM, which may not have been scanned out to the dots yet. If the screen
+    /// has blinking attributes, such as a [`Cursor`], they are not applied to the bitmap.
+    pub fn bitmap(&self) -> Box<Bitmap<C, R>> {
+        match &self.attributes {
+            Some(attributes) => attributes.base.clone(),
+            None => self.shown_bitmap(),
+        }
+    }
+
+    // Returns the bitmap shown on the screen, or in its display RAM if it has one, with any blinking
+    // attributes applied.
+    fn shown_bitmap(&self) -> Box<Bitmap<C, R>> {
+        if let Some(scan_out) = &self.scan_out {
+            return scan_out.gram.clone();
+        }
+        // Note: the vec must be R in length, so this cannot fail
+        let mut bm: Box<Bitmap<C, R>> = vec![[false; C]; R].into_boxed_slice().try_into().unwrap();
+        for (bm_row, row_dots) in bm.iter_mut().zip(self.dots.iter()) {
+            for (bit, dot) in bm_row.iter_mut().zip(row_dots) {
+                *bit = dot.on;
+            }
+        }
+        bm
+    }
+
+    /// Returns a [`Snapshot`] of the bitmap drawn on the screen, as returned by [`bitmap`], for example to
+    /// compare against a golden file in a test.
+    ///
+    /// [`bitmap`]: crate::LcdScreen::bitmap
+    pub fn snapshot(&self) -> Snapshot<R, C> {
+        self.bitmap().into()
+    }
+
+    /// Opens a channel through which other threads can draw to the screen, returning a [`FrameSender`]
+    /// for it. The channel holds up to `capacity` [`DrawCommand`]s, and treats commands sent faster than
+    /// they are drawn according to `delivery`. A capacity of zero is treated as one.
+    ///
+    /// Commands are only drawn when [`drain`] is called on the main thread. Opening a new channel
+    /// disconnects the senders of the previous one, and discards any commands waiting in it.
+    ///
+    /// See [`FrameSender`] for an example.
+    ///
+    /// [`drain`]: crate::LcdScreen::drain
+    pub fn frame_channel(&mut self, capacity: usize, delivery: Delivery) -> FrameSender<R, C> {
+        let (inbox, sender) = Inbox::new(capacity, delivery);
+        self.inbox = Some(inbox);
+        sender
+    }
+
+    /// Draws every command waiting in the channel opened by [`frame_channel`], as a single bitmap drawn
+    /// with [`draw_bitmap`], and returns the number of commands drawn. Nothing is drawn if there are no
+    /// commands waiting, or no channel has been opened. This should be called frequently on the main
+    /// thread, such as on every iteration of the main loop.
+    ///
+    /// # Errors
+    ///
+    /// - [`LcdError::Fill`] when there is an error filling one of the dots with the relevant color
+    /// - [`LcdError::Texture`] when there is an error drawing the dots through a texture
+    ///
+    /// [`frame_channel`]: crate::LcdScreen::frame_channel
+    /// [`draw_bitmap`]: crate::LcdScreen::draw_bitmap
+    ///
+    pub fn drain(&mut self) -> Result<usize, LcdError> {
+        let Some(inbox) = &self.inbox else {
+            return Ok(0);
+        };
+        let commands = inbox.take();
+        if commands.is_empty() {
+            return Ok(0);
+        }
+        let mut bm = self.bitmap();
+        commands.iter().for_each(|command| command.apply(&mut bm));
+        self.draw_bitmap(bm.as_ref())?;
+        Ok(commands.len())
+    }
+
+    /// Draws a bitmap to a simulated LCD screen.
+    ///
+    /// If the screen is in an [`LcdWindow`] shared with other screens, the bitmap is not shown until the
+    /// window is presented with [`LcdWindow::present`]. If the screen has a [`Refresh`] timing, the bitmap
+    /// is only written to the display RAM of the screen, and is shown as it is scanned out by [`update`].
+    ///
+    /// Only the dots which have changed are redrawn, with runs of adjacent changed dots of the same color
+    /// merged into single rects. With [`RenderPath::Texture`], the changed dots are written to the texel
+    /// buffer of the screen instead, which is uploaded and drawn in one go. The [`FrameStats`] of the
+    /// bitmap can be read with [`frame_stats`].
+    ///
+    /// # Arguments
+    ///
+    /// * `bm` - A [`Bitmap`], or something that can be converted into a bitmap, to write to the LCD screen
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simulate_lcd::{LcdScreen, LCD_DARK_GREEN, LCD_LIGHT_GREEN};
+    /// # let sdl_context = sdl2::init().unwrap();
     /// let mut screen = LcdScreen::<2, 2>::new(
     ///         &sdl_context,
     ///         "LCD Example: Checkerboard",
@@ -361,27 +1967,98 @@ impl<const R: usize, const C: usize> LcdScreen<R, C> {
     /// # Errors
     ///
     /// - [`LcdError::Fill`] when there is an error filling one of the dots with the relevant color
+    /// - [`LcdError::Texture`] when there is an error drawing the dots through a texture
+    ///
+    /// [`update`]: crate::LcdScreen::update
+    /// [`frame_stats`]: crate::LcdScreen::frame_stats
     ///
     pub fn draw_bitmap<'a, BM: Into<&'a Bitmap<C, R>>>(&mut self, bm: BM) -> Result<(), LcdError> {
         let bm_array: &[[bool; C]; R] = bm.into();
-        for (row_dots, row_bm) in self.dots.iter_mut().zip(bm_array) {
-            for (dot, bit) in row_dots.iter_mut().zip(row_bm) {
-                if dot.on != *bit {
-                    dot.on = *bit;
-                    self.canvas.set_draw_color(if dot.on {
-                        self.on_color
-                    } else {
-                        self.off_color
-                    });
-                    self.canvas.fill_rect(dot.rect).map_err(LcdError::Fill)?;
+        if let Some(attributes) = &mut self.attributes {
+            *attributes.base = *bm_array;
+            let shown = attributes.compose();
+            return self.show_bitmap(&shown);
+        }
+        self.show_bitmap(bm_array)
+    }
+
+    // Draws a bitmap to the screen as it is shown, with any blinking attributes already applied.
+    fn show_bitmap(&mut self, bm_array: &Bitmap<C, R>) -> Result<(), LcdError> {
+        // Find the runs of changed dots in each row, against the display RAM if the screen has one
+        let mut runs: Vec<(usize, usize, usize)> = Vec::new();
+        let mut dots_changed = 0;
+        for (row, row_bm) in bm_array.iter().enumerate() {
+            let mut run: Option<(usize, usize)> = None;
+            for (col, bit) in row_bm.iter().enumerate() {
+                let old = match &self.scan_out {
+                    Some(scan_out) => scan_out.gram[row][col],
+                    None => self.dots[row][col].on,
+                };
+                if old != *bit {
+                    dots_changed += 1;
+                    run = Some((run.map_or(col, |(first, _)| first), col));
+                } else if let Some((first, last)) = run.take() {
+                    runs.push((row, first, last));
                 }
             }
+            if let Some((first, last)) = run {
+                runs.push((row, first, last));
+            }
         }
-        self.canvas.present();
+        let (regions, bus_bytes) = self.bus.regions(&runs);
+        self.stats = FrameStats {
+            dots_changed,
+            rects_filled: 0,
+            regions: regions.len(),
+            bus_bytes,
+        };
+
+        if let Some(scan_out) = &mut self.scan_out {
+            *scan_out.gram = *bm_array;
+            return Ok(());
+        }
+        let mut canvas = self.canvas.borrow_mut();
+        match &mut self.texels {
+            Some(texels) => {
+                for &(row, first, last) in &runs {
+                    for (col, (dot, bit)) in (first..=last).zip(
+                        self.dots[row][first..=last]
+                            .iter_mut()
+                            .zip(&bm_array[row][first..=last]),
+                    ) {
+                        if dot.on != *bit {
+                            dot.on = *bit;
+                            let (prow, pcol) =
+                                self.geometry.orientation.to_physical(row, col, R, C);
+                            texels.set(pcol, prow, self.painter.color(*bit));
+                        }
+                    }
+                }
+                if !runs.is_empty() {
+                    texels.draw(&mut canvas, self.geometry.dot_area(R, C))?;
+                }
+            }
+            None => {
+                let changes = self
+                    .dots
+                    .iter_mut()
+                    .zip(bm_array)
+                    .flat_map(|(row_dots, row_bm)| row_dots.iter_mut().zip(row_bm.iter().copied()));
+                self.stats.rects_filled = self.painter.paint_changes(&mut canvas, changes)?;
+            }
+        }
+        drop(canvas);
+        self.present();
         Ok(())
     }
 }
 
+impl<const R: usize, const C: usize> DotMatrix<R, C> for LcdScreen<R, C> {
+    fn draw_bitmap(&mut self, bm: &Bitmap<C, R>) -> Result<(), LcdError> {
+        LcdScreen::draw_bitmap(self, bm)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -400,4 +2077,115 @@ mod tests {
         )
         .unwrap();
     }
+
+    #[test]
+    fn test_shape_spans() {
+        assert_eq!(
+            shape_spans(DotShape::Square, 4, 3),
+            vec![Rect::new(0, 0, 4, 3)]
+        );
+
+        let circle = shape_spans(DotShape::Circle, 10, 10);
+        let widest = circle.iter().map(|span| span.width()).max().unwrap();
+        assert_eq!(widest, 10);
+        assert!(circle[0].width() < widest);
+        for span in &circle {
+            assert_eq!(span.x() * 2 + span.width() as i32, 10);
+        }
+        let covered: u32 = circle.iter().map(|span| span.height()).sum();
+        assert_eq!(covered, 10);
+
+        let rounded = shape_spans(DotShape::Rounded(100), 6, 10);
+        assert_eq!(rounded, shape_spans(DotShape::Rounded(3), 6, 10));
+    }
+
+    #[test]
+    fn test_lighting() {
+        let base = Palette::new(LCD_DARK_GREEN, LCD_LIGHT_GREEN, &DotStyle::default());
+        assert_eq!(Lighting::default().apply(&base), base);
+
+        let negative = Lighting {
+            polarity: Polarity::Negative,
+            ..Default::default()
+        }
+        .apply(&base);
+        assert_eq!(negative.on, LCD_LIGHT_GREEN);
+        assert_eq!(negative.off, LCD_DARK_GREEN);
+        assert_eq!(negative.gap, LCD_DARK_GREEN);
+
+        let unlit = Lighting {
+            backlight: false,
+            ..Default::default()
+        }
+        .apply(&base);
+        assert_eq!(
+            unlit.off,
+            light_color(LCD_LIGHT_GREEN, [UNLIT_LEVEL, UNLIT_LEVEL, UNLIT_LEVEL])
+        );
+
+        let no_contrast = Lighting {
+            contrast: 0.0,
+            ..Default::default()
+        }
+        .apply(&base);
+        assert_eq!(no_contrast.on, no_contrast.off);
+
+        assert!(check_level("contrast", 1.5).is_err());
+        assert!(check_level("contrast", f32::NAN).is_err());
+    }
+
+    #[test]
+    fn test_dot_at() {
+        let geometry = Geometry {
+            dot_width: 4,
+            dot_height: 3,
+            gap: 1,
+            outer: Border::uniform(2),
+            frame: Border::default(),
+            orientation: Orientation::default(),
+            origin: (10, 20),
+        };
+        assert_eq!(geometry.dot_at(12, 22, 2, 3), Some((0, 0)));
+        assert_eq!(geometry.dot_at(16, 22, 2, 3), None);
+        assert_eq!(geometry.dot_at(17, 26, 2, 3), Some((1, 1)));
+        assert_eq!(geometry.dot_at(26, 22, 2, 3), None);
+        assert_eq!(geometry.dot_at(11, 22, 2, 3), None);
+        assert_eq!(geometry.dot_area(2, 3), Rect::new(12, 22, 14, 7));
+
+        let rotated = Geometry {
+            orientation: Orientation {
+                rotation: Rotation::Deg90,
+                ..Default::default()
+            },
+            ..geometry
+        };
+        for row in 0..2 {
+            for col in 0..3 {
+                let rect = rotated.dot_rect(row, col, 2, 3);
+                assert_eq!(
+                    rotated.dot_at(rect.right() - 1, rect.bottom() - 1, 2, 3),
+                    Some((row, col))
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_dot_batch() {
+        let mut rects = Vec::new();
+        DotBatch::push(&mut rects, Rect::new(0, 0, 2, 2));
+        DotBatch::push(&mut rects, Rect::new(2, 0, 2, 2));
+        DotBatch::push(&mut rects, Rect::new(6, 0, 2, 2));
+        DotBatch::push(&mut rects, Rect::new(6, 2, 2, 2));
+        DotBatch::push(&mut rects, Rect::new(6, -2, 2, 2));
+        DotBatch::push(&mut rects, Rect::new(8, 1, 2, 2));
+        assert_eq!(
+            rects,
+            vec![
+                Rect::new(0, 0, 4, 2),
+                Rect::new(6, -2, 2, 6),
+                Rect::new(8, 1, 2, 2)
+            ]
+        );
+    }
 }
@@ -0,0 +1,197 @@
+// * Simulate LCD: A Simple LCD Screen Simulator *
+
+// Copyright 2023 Simon Varey - github.com/simonvarey
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// Rotation
+
+/// A clockwise rotation of the dots of an [`LcdScreen`] within its window.
+///
+/// [`LcdScreen`]: crate::LcdScreen
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Rotation {
+    /// No rotation. This is the default rotation.
+    #[default]
+    Deg0,
+    /// A quarter turn clockwise. The window is as wide as the screen is high, and vice versa.
+    Deg90,
+    /// A half turn.
+    Deg180,
+    /// Three quarter turns clockwise, or a quarter turn anticlockwise. The window is as wide as the screen
+    /// is high, and vice versa.
+    Deg270,
+}
+
+impl Rotation {
+    /// Returns whether this rotation swaps the width and height of the screen.
+    pub fn is_sideways(&self) -> bool {
+        matches!(self, Rotation::Deg90 | Rotation::Deg270)
+    }
+}
+
+// * Orientation *
+
+/// How the dots of an [`LcdScreen`] are mounted in its window.
+///
+/// Bitmaps drawn to a screen are always in the logical coordinates of the screen, with `R` rows and `C`
+/// columns. The orientation maps those coordinates onto the physical grid of dots in the window: first
+/// the screen is rotated by [`rotation`], then the rotated grid is flipped horizontally and/or
+/// vertically, as some display controllers do when mirroring their segment or common outputs.
+///
+/// # Examples
+///
+/// ```
+/// # use simulate_lcd::{Orientation, Rotation};
+/// let orientation = Orientation {
+///     rotation: Rotation::Deg90,
+///     flip_horizontal: true,
+///     ..Default::default()
+/// };
+/// ```
+///
+/// [`LcdScreen`]: crate::LcdScreen
+/// [`rotation`]: crate::Orientation::rotation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Orientation {
+    /// The clockwise rotation of the screen
+    pub rotation: Rotation,
+    /// Whether the physical columns of dots are mirrored left to right, after rotation
+    pub flip_horizontal: bool,
+    /// Whether the physical rows of dots are mirrored top to bottom, after rotation
+    pub flip_vertical: bool,
+}
+
+impl Orientation {
+    /// Returns the number of physical rows and columns of dots of a screen with `rows` logical rows and
+    /// `cols` logical columns of dots.
+    pub fn physical_size(&self, rows: usize, cols: usize) -> (usize, usize) {
+        if self.rotation.is_sideways() {
+            (cols, rows)
+        } else {
+            (rows, cols)
+        }
+    }
+
+    /// Maps the logical `(row, col)` position of a dot on a screen with `rows` logical rows and `cols`
+    /// logical columns of dots onto its physical `(row, col)` position in the window.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simulate_lcd::{Orientation, Rotation};
+    /// let orientation = Orientation {
+    ///     rotation: Rotation::Deg90,
+    ///     ..Default::default()
+    /// };
+    /// // The top-left dot of a 2 by 3 screen ends up in the top-right corner.
+    /// assert_eq!(orientation.to_physical(0, 0, 2, 3), (0, 1));
+    /// ```
+    pub fn to_physical(&self, row: usize, col: usize, rows: usize, cols: usize) -> (usize, usize) {
+        let (prow, pcol) = match self.rotation {
+            Rotation::Deg0 => (row, col),
+            Rotation::Deg90 => (col, rows - 1 - row),
+            Rotation::Deg180 => (rows - 1 - row, cols - 1 - col),
+            Rotation::Deg270 => (cols - 1 - col, row),
+        };
+        let (prows, pcols) = self.physical_size(rows, cols);
+        (
+            if self.flip_vertical {
+                prows - 1 - prow
+            } else {
+                prow
+            },
+            if self.flip_horizontal {
+                pcols - 1 - pcol
+            } else {
+                pcol
+            },
+        )
+    }
+
+    /// Maps the physical `(row, col)` position of a dot in the window back onto its logical `(row, col)`
+    /// position on a screen with `rows` logical rows and `cols` logical columns of dots. This is the
+    /// inverse of [`to_physical`].
+    ///
+    /// [`to_physical`]: crate::Orientation::to_physical
+    pub fn to_logical(&self, prow: usize, pcol: usize, rows: usize, cols: usize) -> (usize, usize) {
+        let (prows, pcols) = self.physical_size(rows, cols);
+        let prow = if self.flip_vertical {
+            prows - 1 - prow
+        } else {
+            prow
+        };
+        let pcol = if self.flip_horizontal {
+            pcols - 1 - pcol
+        } else {
+            pcol
+        };
+        match self.rotation {
+            Rotation::Deg0 => (prow, pcol),
+            Rotation::Deg90 => (rows - 1 - pcol, prow),
+            Rotation::Deg180 => (rows - 1 - prow, cols - 1 - pcol),
+            Rotation::Deg270 => (pcol, cols - 1 - prow),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let (rows, cols) = (3, 5);
+        for rotation in [
+            Rotation::Deg0,
+            Rotation::Deg90,
+            Rotation::Deg180,
+            Rotation::Deg270,
+        ] {
+            for flip_horizontal in [false, true] {
+                for flip_vertical in [false, true] {
+                    let orientation = Orientation {
+                        rotation,
+                        flip_horizontal,
+                        flip_vertical,
+                    };
+                    let (prows, pcols) = orientation.physical_size(rows, cols);
+                    let mut seen = vec![false; rows * cols];
+                    for row in 0..rows {
+                        for col in 0..cols {
+                            let (prow, pcol) = orientation.to_physical(row, col, rows, cols);
+                            assert!(prow < prows && pcol < pcols);
+                            assert!(!seen[prow * pcols + pcol]);
+                            seen[prow * pcols + pcol] = true;
+                            assert_eq!(orientation.to_logical(prow, pcol, rows, cols), (row, col));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_rotation() {
+        let deg270 = Orientation {
+            rotation: Rotation::Deg270,
+            ..Default::default()
+        };
+        assert_eq!(deg270.to_physical(0, 0, 2, 3), (2, 0));
+        let flipped = Orientation {
+            flip_horizontal: true,
+            ..Default::default()
+        };
+        assert_eq!(flipped.to_physical(0, 0, 2, 3), (0, 2));
+    }
+}
@@ -0,0 +1,925 @@
+// * Simulate LCD: A Simple LCD Screen Simulator *
+
+// Copyright 2023 Simon Varey - github.com/simonvarey
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// Imports
+
+use std::{collections::VecDeque, f64::consts::TAU, path::Path};
+
+use sdl2::{
+    event::{Event, WindowEvent},
+    keyboard::Keycode,
+    mouse::{MouseButton, MouseWheelDirection},
+    pixels::Color,
+    rect::Rect,
+    render::{Canvas, Texture},
+    surface::Surface,
+    video::Window,
+    Sdl,
+};
+
+use crate::{shape_spans, DotPainter, DotShape, LcdError, LcdWindow};
+
+// Widgets
+
+/// A push button on a [`DevicePanel`], which can be pressed by clicking it or by holding down its key.
+///
+/// # Examples
+///
+/// ```
+/// # use simulate_lcd::Button;
+/// # use sdl2::keyboard::Keycode;
+/// let ok = Button::new(40, 300, 60, 30).rounded(8).key(Keycode::Return);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Button {
+    rect: Rect,
+    color: Color,
+    pressed_color: Color,
+    radius: u32,
+    key: Option<Keycode>,
+}
+
+impl Button {
+    /// Creates a square-cornered button with its top-left corner at (`x`, `y`) in the panel.
+    pub fn new(x: i32, y: i32, width: u32, height: u32) -> Self {
+        Self {
+            rect: Rect::new(x, y, width, height),
+            color: Color::RGB(70, 70, 70),
+            pressed_color: Color::RGB(35, 35, 35),
+            radius: 0,
+            key: None,
+        }
+    }
+
+    /// Sets the color of the button when released and when pressed.
+    pub fn colors(mut self, color: Color, pressed_color: Color) -> Self {
+        self.color = color;
+        self.pressed_color = pressed_color;
+        self
+    }
+
+    /// Rounds the corners of the button to the given radius in pixels.
+    pub fn rounded(mut self, radius: u32) -> Self {
+        self.radius = radius;
+        self
+    }
+
+    /// Sets a key on the host keyboard which presses the button while it is held down.
+    pub fn key(mut self, key: Keycode) -> Self {
+        self.key = Some(key);
+        self
+    }
+}
+
+/// A round indicator LED on a [`DevicePanel`], which is switched on and off by the application with
+/// [`DevicePanel::set_led`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Led {
+    rect: Rect,
+    on_color: Color,
+    off_color: Color,
+}
+
+impl Led {
+    /// Creates a red LED with its top-left corner at (`x`, `y`) in the panel.
+    pub fn new(x: i32, y: i32, diameter: u32) -> Self {
+        Self {
+            rect: Rect::new(x, y, diameter, diameter),
+            on_color: Color::RGB(235, 40, 30),
+            off_color: Color::RGB(70, 25, 20),
+        }
+    }
+
+    /// Sets the color of the LED when on and when off.
+    pub fn colors(mut self, on_color: Color, off_color: Color) -> Self {
+        self.on_color = on_color;
+        self.off_color = off_color;
+        self
+    }
+}
+
+/// A rotary encoder knob on a [`DevicePanel`].
+///
+/// The knob is turned by scrolling the mouse wheel over it, or by pressing its keys, and can be pushed
+/// like a [`Button`] by clicking it or by holding down its push key.
+///
+/// # Examples
+///
+/// ```
+/// # use simulate_lcd::Encoder;
+/// # use sdl2::keyboard::Keycode;
+/// let knob = Encoder::new(500, 290, 50)
+///     .keys(Keycode::Left, Keycode::Right)
+///     .push_key(Keycode::Space);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Encoder {
+    rect: Rect,
+    color: Color,
+    mark_color: Color,
+    detents: u32,
+    keys: Option<(Keycode, Keycode)>,
+    push_key: Option<Keycode>,
+}
+
+impl Encoder {
+    /// Creates a knob with 20 detents per revolution and its top-left corner at (`x`, `y`) in the panel.
+    pub fn new(x: i32, y: i32, diameter: u32) -> Self {
+        Self {
+            rect: Rect::new(x, y, diameter, diameter),
+            color: Color::RGB(90, 90, 90),
+            mark_color: Color::RGB(225, 225, 225),
+            detents: 20,
+            keys: None,
+            push_key: None,
+        }
+    }
+
+    /// Sets the color of the knob and of the mark showing its position.
+    pub fn colors(mut self, color: Color, mark_color: Color) -> Self {
+        self.color = color;
+        self.mark_color = mark_color;
+        self
+    }
+
+    /// Sets the number of steps in one full turn of the knob, which is used to draw its mark. A value of
+    /// zero is treated as one.
+    pub fn detents(mut self, detents: u32) -> Self {
+        self.detents = detents.max(1);
+        self
+    }
+
+    /// Sets the keys on the host keyboard which turn the knob one step counter-clockwise and clockwise.
+    /// Holding a key down turns the knob repeatedly.
+    pub fn keys(mut self, counter_clockwise: Keycode, clockwise: Keycode) -> Self {
+        self.keys = Some((counter_clockwise, clockwise));
+        self
+    }
+
+    /// Sets a key on the host keyboard which pushes the knob while it is held down.
+    pub fn push_key(mut self, key: Keycode) -> Self {
+        self.push_key = Some(key);
+        self
+    }
+
+    // Whether a point lies inside the circle of the knob.
+    fn contains(&self, x: i32, y: i32) -> bool {
+        let diameter = i64::from(self.rect.width());
+        let dx = 2 * (i64::from(x) - i64::from(self.rect.x())) - diameter;
+        let dy = 2 * (i64::from(y) - i64::from(self.rect.y())) - diameter;
+        dx * dx + dy * dy <= diameter * diameter
+    }
+}
+
+/// Identifies a [`Button`] added to a [`DevicePanel`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ButtonId(usize);
+
+/// Identifies an [`Led`] added to a [`DevicePanel`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LedId(usize);
+
+/// Identifies an [`Encoder`] added to a [`DevicePanel`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EncoderId(usize);
+
+/// An input event from the widgets of a [`DevicePanel`], as returned by [`DevicePanel::poll_event`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PanelEvent {
+    /// A button was pressed.
+    ButtonPressed(ButtonId),
+    /// A button was released.
+    ButtonReleased(ButtonId),
+    /// A knob was turned by the given number of steps. Positive steps are clockwise.
+    EncoderTurned {
+        /// the knob which was turned
+        encoder: EncoderId,
+        /// the number of steps the knob was turned by
+        steps: i32,
+    },
+    /// A knob was pushed.
+    EncoderPressed(EncoderId),
+    /// A knob was released.
+    EncoderReleased(EncoderId),
+}
+
+// Controls
+
+// Whether a push control is held down by the mouse and/or its key. It is pressed while either is held.
+#[derive(Debug, Clone, Copy, Default)]
+struct Hold {
+    mouse: bool,
+    key: bool,
+}
+
+impl Hold {
+    fn pressed(&self) -> bool {
+        self.mouse || self.key
+    }
+}
+
+// The widgets of a panel and their state, and the translation of SDL events into panel events.
+#[derive(Debug, Default)]
+struct Controls {
+    buttons: Vec<(Button, Hold)>,
+    leds: Vec<(Led, bool)>,
+    encoders: Vec<(Encoder, Hold, i32)>,
+    events: VecDeque<PanelEvent>,
+    mouse: (i32, i32),
+    // Widgets whose appearance has changed since they were last drawn
+    dirty: Vec<Rect>,
+}
+
+impl Controls {
+    fn set_button(&mut self, index: usize, update: impl FnOnce(&mut Hold)) {
+        let (button, hold) = &mut self.buttons[index];
+        let was_pressed = hold.pressed();
+        update(hold);
+        match (was_pressed, hold.pressed()) {
+            (false, true) => self
+                .events
+                .push_back(PanelEvent::ButtonPressed(ButtonId(index))),
+            (true, false) => self
+                .events
+                .push_back(PanelEvent::ButtonReleased(ButtonId(index))),
+            _ => return,
+        }
+        self.dirty.push(button.rect);
+    }
+
+    fn set_encoder(&mut self, index: usize, update: impl FnOnce(&mut Hold)) {
+        let (_, hold, _) = &mut self.encoders[index];
+        let was_pressed = hold.pressed();
+        update(hold);
+        match (was_pressed, hold.pressed()) {
+            (false, true) => self
+                .events
+                .push_back(PanelEvent::EncoderPressed(EncoderId(index))),
+            (true, false) => self
+                .events
+                .push_back(PanelEvent::EncoderReleased(EncoderId(index))),
+            _ => {}
+        }
+    }
+
+    fn turn_encoder(&mut self, index: usize, steps: i32) {
+        let (encoder, _, position) = &mut self.encoders[index];
+        *position = position.wrapping_add(steps);
+        self.dirty.push(encoder.rect);
+        self.events.push_back(PanelEvent::EncoderTurned {
+            encoder: EncoderId(index),
+            steps,
+        });
+    }
+
+    // Releases every control held by the mouse (if `mouse`) or by a key (if not).
+    fn release_all(&mut self, mouse: bool) {
+        let release = |hold: &mut Hold| {
+            if mouse {
+                hold.mouse = false
+            } else {
+                hold.key = false
+            }
+        };
+        for index in 0..self.buttons.len() {
+            self.set_button(index, release);
+        }
+        for index in 0..self.encoders.len() {
+            self.set_encoder(index, release);
+        }
+    }
+
+    fn handle_event(&mut self, event: &Event) -> bool {
+        match *event {
+            Event::MouseMotion { x, y, .. } => {
+                self.mouse = (x, y);
+                false
+            }
+            Event::MouseButtonDown {
+                mouse_btn: MouseButton::Left,
+                x,
+                y,
+                ..
+            } => {
+                self.mouse = (x, y);
+                if let Some(index) = self
+                    .buttons
+                    .iter()
+                    .position(|(button, _)| button.rect.contains_point((x, y)))
+                {
+                    self.set_button(index, |hold| hold.mouse = true);
+                    true
+                } else if let Some(index) = self
+                    .encoders
+                    .iter()
+                    .position(|(encoder, ..)| encoder.contains(x, y))
+                {
+                    self.set_encoder(index, |hold| hold.mouse = true);
+                    true
+                } else {
+                    false
+                }
+            }
+            Event::MouseButtonUp {
+                mouse_btn: MouseButton::Left,
+                ..
+            } => {
+                let held = self.buttons.iter().any(|(_, hold)| hold.mouse)
+                    || self.encoders.iter().any(|(_, hold, _)| hold.mouse);
+                self.release_all(true);
+                held
+            }
+            Event::MouseWheel { y, direction, .. } => {
+                let (mouse_x, mouse_y) = self.mouse;
+                match self
+                    .encoders
+                    .iter()
+                    .position(|(encoder, ..)| encoder.contains(mouse_x, mouse_y))
+                {
+                    Some(index) if y != 0 => {
+                        let steps = match direction {
+                            MouseWheelDirection::Flipped => y.saturating_neg(),
+                            _ => y,
+                        };
+                        self.turn_encoder(index, steps);
+                        true
+                    }
+                    _ => false,
+                }
+            }
+            Event::KeyDown {
+                keycode: Some(key),
+                repeat,
+                ..
+            } => {
+                let mut used = false;
+                for index in 0..self.encoders.len() {
+                    let encoder = self.encoders[index].0;
+                    if let Some((counter_clockwise, clockwise)) = encoder.keys {
+                        if key == counter_clockwise || key == clockwise {
+                            self.turn_encoder(index, if key == clockwise { 1 } else { -1 });
+                            used = true;
+                        }
+                    }
+                    if encoder.push_key == Some(key) {
+                        if !repeat {
+                            self.set_encoder(index, |hold| hold.key = true);
+                        }
+                        used = true;
+                    }
+                }
+                for index in 0..self.buttons.len() {
+                    if self.buttons[index].0.key == Some(key) {
+                        if !repeat {
+                            self.set_button(index, |hold| hold.key = true);
+                        }
+                        used = true;
+                    }
+                }
+                used
+            }
+            Event::KeyUp {
+                keycode: Some(key), ..
+            } => {
+                let mut used = false;
+                for index in 0..self.encoders.len() {
+                    if self.encoders[index].0.push_key == Some(key) {
+                        self.set_encoder(index, |hold| hold.key = false);
+                        used = true;
+                    }
+                }
+                for index in 0..self.buttons.len() {
+                    if self.buttons[index].0.key == Some(key) {
+                        self.set_button(index, |hold| hold.key = false);
+                        used = true;
+                    }
+                }
+                used
+            }
+            // Keys released while the window is unfocused are never reported, so release them all now
+            Event::Window {
+                win_event: WindowEvent::FocusLost,
+                ..
+            } => {
+                self.release_all(false);
+                self.release_all(true);
+                false
+            }
+            _ => false,
+        }
+    }
+}
+
+// * Device Panel *
+
+/// A mockup of the face of a device, with [`LcdScreens`] alongside clickable [`Buttons`], rotary
+/// [`Encoders`] and indicator [`Leds`], all in one window.
+///
+/// Screens are placed on the panel by building them inside its [`window`] with
+/// [`LcdScreenBuilder::build_in`]. Widgets are placed with [`add_button`], [`add_encoder`] and
+/// [`add_led`]. Each SDL event received by the application should be passed to [`handle_event`], which
+/// translates mouse and keyboard input on the widgets into [`PanelEvents`]. These are queued until read
+/// with [`poll_event`].
+///
+/// As with an [`LcdWindow`], drawing to the screens on a panel does not show the drawing until the
+/// panel is presented with [`present`].
+///
+/// # Examples
+///
+/// ```no_run
+/// # use simulate_lcd::{Button, DevicePanel, Led, LcdScreenBuilder, PanelEvent};
+/// # use sdl2::keyboard::Keycode;
+/// let sdl_context = sdl2::init().unwrap();
+/// let mut panel = DevicePanel::new(&sdl_context, "Device", 400, 300).unwrap();
+/// let mut screen = LcdScreenBuilder::<32, 64>::new()
+///     .dot_size(5, 5)
+///     .build_in(panel.window(), 40, 40)
+///     .unwrap();
+/// let ok = panel.add_button(Button::new(170, 240, 60, 30).key(Keycode::Return));
+/// let busy = panel.add_led(Led::new(360, 20, 16));
+/// panel.present().unwrap();
+///
+/// let mut event_pump = sdl_context.event_pump().unwrap();
+/// loop {
+///     for event in event_pump.poll_iter() {
+///         panel.handle_event(&event);
+///     }
+///     while let Some(event) = panel.poll_event() {
+///         if event == PanelEvent::ButtonPressed(ok) {
+///             let busy_on = panel.led(busy);
+///             panel.set_led(busy, !busy_on);
+///         }
+///     }
+///     panel.present().unwrap();
+/// }
+/// ```
+///
+/// [`LcdScreens`]: crate::LcdScreen
+/// [`Buttons`]: crate::Button
+/// [`Encoders`]: crate::Encoder
+/// [`Leds`]: crate::Led
+/// [`window`]: crate::DevicePanel::window
+/// [`LcdScreenBuilder::build_in`]: crate::LcdScreenBuilder::build_in
+/// [`add_button`]: crate::DevicePanel::add_button
+/// [`add_encoder`]: crate::DevicePanel::add_encoder
+/// [`add_led`]: crate::DevicePanel::add_led
+/// [`handle_event`]: crate::DevicePanel::handle_event
+/// [`PanelEvents`]: crate::PanelEvent
+/// [`poll_event`]: crate::DevicePanel::poll_event
+/// [`present`]: crate::DevicePanel::present
+pub struct DevicePanel {
+    window: LcdWindow,
+    face: Option<Surface<'static>>,
+    controls: Controls,
+}
+
+impl DevicePanel {
+    /// Creates an empty panel in a new window with the default [`WindowOptions`].
+    ///
+    /// # Arguments
+    ///
+    /// * `sdl_context` - An [`Sdl`] context object
+    /// * `title` - The title of the window
+    /// * `width` - The width of the panel in pixels
+    /// * `height` - The height of the panel in pixels
+    ///
+    /// # Errors
+    ///
+    /// The same as those of [`LcdWindow::new`].
+    ///
+    /// [`WindowOptions`]: crate::WindowOptions
+    /// [`Sdl`]: sdl2::Sdl
+    ///
+    pub fn new(sdl_context: &Sdl, title: &str, width: u32, height: u32) -> Result<Self, LcdError> {
+        Ok(Self::with_window(LcdWindow::new(
+            sdl_context,
+            title,
+            width,
+            height,
+        )?))
+    }
+
+    /// Creates an empty panel covering an existing window, for example one created with
+    /// [`LcdWindow::with_options`]. The background color of the window is used as the color of the
+    /// face of the panel.
+    pub fn with_window(window: LcdWindow) -> Self {
+        Self {
+            window,
+            face: None,
+            controls: Controls::default(),
+        }
+    }
+
+    /// Returns the window of the panel, in which screens can be built with
+    /// [`LcdScreenBuilder::build_in`].
+    ///
+    /// [`LcdScreenBuilder::build_in`]: crate::LcdScreenBuilder::build_in
+    pub fn window(&self) -> &LcdWindow {
+        &self.window
+    }
+
+    /// Sets the color of the face of the panel. The new color is drawn the next time the panel is
+    /// cleared with [`clear`].
+    ///
+    /// [`clear`]: crate::DevicePanel::clear
+    pub fn set_face_color(&mut self, color: Color) {
+        self.window.set_background(color);
+    }
+
+    /// Sets an image, such as a photo or rendering of the device, which is stretched to cover the face
+    /// of the panel behind its screens and widgets. The image must be a BMP file. The new image is drawn
+    /// the next time the panel is cleared with [`clear`].
+    ///
+    /// # Errors
+    ///
+    /// - [`LcdError::Overlay`] when the image cannot be loaded
+    ///
+    /// [`clear`]: crate::DevicePanel::clear
+    ///
+    pub fn set_face_image<P: AsRef<Path>>(&mut self, path: P) -> Result<(), LcdError> {
+        self.face = Some(Surface::load_bmp(path).map_err(LcdError::Overlay)?);
+        Ok(())
+    }
+
+    /// Adds a button to the panel, returning the id used to identify it in [`PanelEvents`].
+    ///
+    /// [`PanelEvents`]: crate::PanelEvent
+    pub fn add_button(&mut self, button: Button) -> ButtonId {
+        self.controls.buttons.push((button, Hold::default()));
+        self.controls.dirty.push(button.rect);
+        ButtonId(self.controls.buttons.len() - 1)
+    }
+
+    /// Adds an LED to the panel, initially off, returning the id used to switch it on and off.
+    pub fn add_led(&mut self, led: Led) -> LedId {
+        self.controls.leds.push((led, false));
+        self.controls.dirty.push(led.rect);
+        LedId(self.controls.leds.len() - 1)
+    }
+
+    /// Adds a rotary encoder knob to the panel, returning the id used to identify it in
+    /// [`PanelEvents`].
+    ///
+    /// [`PanelEvents`]: crate::PanelEvent
+    pub fn add_encoder(&mut self, encoder: Encoder) -> EncoderId {
+        self.controls.encoders.push((encoder, Hold::default(), 0));
+        self.controls.dirty.push(encoder.rect);
+        EncoderId(self.controls.encoders.len() - 1)
+    }
+
+    /// Returns whether a button is currently pressed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `button` was not returned by [`add_button`] on this panel.
+    ///
+    /// [`add_button`]: crate::DevicePanel::add_button
+    pub fn is_pressed(&self, button: ButtonId) -> bool {
+        self.controls.buttons[button.0].1.pressed()
+    }
+
+    /// Returns whether an LED is on.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `led` was not returned by [`add_led`] on this panel.
+    ///
+    /// [`add_led`]: crate::DevicePanel::add_led
+    pub fn led(&self, led: LedId) -> bool {
+        self.controls.leds[led.0].1
+    }
+
+    /// Switches an LED on or off. The change is drawn the next time the panel is presented.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `led` was not returned by [`add_led`] on this panel.
+    ///
+    /// [`add_led`]: crate::DevicePanel::add_led
+    pub fn set_led(&mut self, led: LedId, on: bool) {
+        let (spec, state) = &mut self.controls.leds[led.0];
+        if *state != on {
+            *state = on;
+            self.controls.dirty.push(spec.rect);
+        }
+    }
+
+    /// Returns the number of steps a knob has been turned clockwise in total since it was added to the
+    /// panel. Counter-clockwise turns count as negative steps.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `encoder` was not returned by [`add_encoder`] on this panel.
+    ///
+    /// [`add_encoder`]: crate::DevicePanel::add_encoder
+    pub fn encoder_position(&self, encoder: EncoderId) -> i32 {
+        self.controls.encoders[encoder.0].2
+    }
+
+    /// Translates an SDL event into [`PanelEvents`] if it is input on a widget of the panel, returning
+    /// whether it was.
+    ///
+    /// A left click on a button or knob presses it until the mouse button is released, and scrolling the
+    /// mouse wheel over a knob turns it. Keys bound to widgets press and turn them in the same way. Events
+    /// which are not input on a widget, such as [`Event::Quit`], are left for the application to handle.
+    ///
+    /// [`PanelEvents`]: crate::PanelEvent
+    /// [`Event::Quit`]: sdl2::event::Event::Quit
+    pub fn handle_event(&mut self, event: &Event) -> bool {
+        self.controls.handle_event(event)
+    }
+
+    /// Removes and returns the oldest [`PanelEvent`] which has not yet been read, if there is one.
+    pub fn poll_event(&mut self) -> Option<PanelEvent> {
+        self.controls.events.pop_front()
+    }
+
+    /// Redraws the whole face of the panel, covering every screen in it.
+    ///
+    /// After clearing the panel, each screen on it should be redrawn with [`LcdScreen::repaint`] before
+    /// the panel is presented. This is necessary when the contents of the window have been lost, for
+    /// example because it has been resized.
+    ///
+    /// # Errors
+    ///
+    /// - [`LcdError::Overlay`] when there is an error drawing the face image
+    ///
+    /// [`LcdScreen::repaint`]: crate::LcdScreen::repaint
+    ///
+    pub fn clear(&mut self) -> Result<(), LcdError> {
+        self.window.clear();
+        if let Some(face) = &self.face {
+            let mut canvas = self.window.canvas.borrow_mut();
+            let texture_creator = canvas.texture_creator();
+            let texture = texture_creator
+                .create_texture_from_surface(face)
+                .map_err(|err| LcdError::Overlay(err.to_string()))?;
+            canvas
+                .copy(&texture, None, None)
+                .map_err(LcdError::Overlay)?;
+        }
+        let controls = &mut self.controls;
+        controls.dirty = (controls.buttons.iter().map(|(button, _)| button.rect))
+            .chain(controls.leds.iter().map(|(led, _)| led.rect))
+            .chain(controls.encoders.iter().map(|(encoder, ..)| encoder.rect))
+            .collect();
+        Ok(())
+    }
+
+    /// Draws every widget which has changed since the panel was last presented, and then shows the
+    /// panel along with everything drawn to the screens on it.
+    ///
+    /// # Errors
+    ///
+    /// - [`LcdError::Fill`] when there is an error drawing a widget
+    /// - [`LcdError::Overlay`] when there is an error drawing the face image behind a widget
+    ///
+    pub fn present(&mut self) -> Result<(), LcdError> {
+        if !self.controls.dirty.is_empty() {
+            let mut canvas = self.window.canvas.borrow_mut();
+            let texture_creator = canvas.texture_creator();
+            let face = match &self.face {
+                Some(face) => Some(
+                    texture_creator
+                        .create_texture_from_surface(face)
+                        .map_err(|err| LcdError::Overlay(err.to_string()))?,
+                ),
+                None => None,
+            };
+            let background = self.window.background();
+            for rect in std::mem::take(&mut self.controls.dirty) {
+                Self::paint_face(&mut canvas, background, face.as_ref(), rect)?;
+                self.paint_widgets(&mut canvas, rect)?;
+            }
+        }
+        self.window.present();
+        Ok(())
+    }
+
+    // Restores the face of the panel within a rect.
+    fn paint_face(
+        canvas: &mut Canvas<Window>,
+        background: Color,
+        face: Option<&Texture>,
+        rect: Rect,
+    ) -> Result<(), LcdError> {
+        match face {
+            Some(face) => {
+                // The face image is stretched over the whole panel, so the part of it behind the rect is
+                // scaled down by the same factor.
+                let (width, height) = canvas.logical_size();
+                let query = face.query();
+                let scale = |value: i32, image: u32, panel: u32| -> i32 {
+                    (i64::from(value) * i64::from(image) / i64::from(panel.max(1))) as i32
+                };
+                let left = scale(rect.left(), query.width, width);
+                let top = scale(rect.top(), query.height, height);
+                let right = scale(rect.right(), query.width, width);
+                let bottom = scale(rect.bottom(), query.height, height);
+                let source = Rect::new(
+                    left,
+                    top,
+                    right.saturating_sub(left).max(1) as u32,
+                    bottom.saturating_sub(top).max(1) as u32,
+                );
+                canvas.copy(face, source, rect).map_err(LcdError::Overlay)
+            }
+            None => {
+                canvas.set_draw_color(background);
+                canvas.fill_rect(rect).map_err(LcdError::Fill)
+            }
+        }
+    }
+
+    // Draws every widget which overlaps a rect.
+    fn paint_widgets(&self, canvas: &mut Canvas<Window>, rect: Rect) -> Result<(), LcdError> {
+        let overlaps = |other: Rect| other.has_intersection(rect);
+        for (button, hold) in &self.controls.buttons {
+            if overlaps(button.rect) {
+                canvas.set_draw_color(if hold.pressed() {
+                    button.pressed_color
+                } else {
+                    button.color
+                });
+                Self::paint_shape(canvas, DotShape::Rounded(button.radius), button.rect)?;
+            }
+        }
+        for (led, on) in &self.controls.leds {
+            if overlaps(led.rect) {
+                canvas.set_draw_color(if *on { led.on_color } else { led.off_color });
+                Self::paint_shape(canvas, DotShape::Circle, led.rect)?;
+            }
+        }
+        for (encoder, _, position) in &self.controls.encoders {
+            if overlaps(encoder.rect) {
+                canvas.set_draw_color(encoder.color);
+                Self::paint_shape(canvas, DotShape::Circle, encoder.rect)?;
+
+                // The mark is a small dot which starts at the top of the knob and moves around it by one
+                // detent per step.
+                let diameter = f64::from(encoder.rect.width());
+                let mark_size = (encoder.rect.width() / 6).max(2);
+                let angle = TAU * f64::from(*position) / f64::from(encoder.detents);
+                let (center_x, center_y) = (
+                    f64::from(encoder.rect.x()) + diameter / 2.0,
+                    f64::from(encoder.rect.y()) + diameter / 2.0,
+                );
+                let radius = diameter * 0.3;
+                let mark = Rect::from_center(
+                    (
+                        (center_x + radius * angle.sin()).round() as i32,
+                        (center_y - radius * angle.cos()).round() as i32,
+                    ),
+                    mark_size,
+                    mark_size,
+                );
+                canvas.set_draw_color(encoder.mark_color);
+                Self::paint_shape(canvas, DotShape::Circle, mark)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn paint_shape(
+        canvas: &mut Canvas<Window>,
+        shape: DotShape,
+        rect: Rect,
+    ) -> Result<(), LcdError> {
+        let spans = shape_spans(shape, rect.width(), rect.height());
+        DotPainter::fill(canvas, &spans, rect.x(), rect.y())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sdl2::keyboard::Mod;
+
+    fn mouse_down(x: i32, y: i32) -> Event {
+        Event::MouseButtonDown {
+            timestamp: 0,
+            window_id: 0,
+            which: 0,
+            mouse_btn: MouseButton::Left,
+            clicks: 1,
+            x,
+            y,
+        }
+    }
+
+    fn mouse_up(x: i32, y: i32) -> Event {
+        Event::MouseButtonUp {
+            timestamp: 0,
+            window_id: 0,
+            which: 0,
+            mouse_btn: MouseButton::Left,
+            clicks: 1,
+            x,
+            y,
+        }
+    }
+
+    fn key(key: Keycode, down: bool, repeat: bool) -> Event {
+        if down {
+            Event::KeyDown {
+                timestamp: 0,
+                window_id: 0,
+                keycode: Some(key),
+                scancode: None,
+                keymod: Mod::NOMOD,
+                repeat,
+            }
+        } else {
+            Event::KeyUp {
+                timestamp: 0,
+                window_id: 0,
+                keycode: Some(key),
+                scancode: None,
+                keymod: Mod::NOMOD,
+                repeat,
+            }
+        }
+    }
+
+    #[test]
+    fn test_buttons() {
+        let mut controls = Controls::default();
+        controls
+            .buttons
+            .push((Button::new(10, 10, 20, 10).key(Keycode::A), Hold::default()));
+
+        assert!(!controls.handle_event(&mouse_down(5, 5)));
+        assert!(controls.handle_event(&mouse_down(15, 15)));
+        // Holding the key as well as the mouse does not press the button a second time
+        assert!(controls.handle_event(&key(Keycode::A, true, false)));
+        assert!(controls.handle_event(&mouse_up(50, 50)));
+        assert!(controls.handle_event(&key(Keycode::A, true, true)));
+        assert!(controls.handle_event(&key(Keycode::A, false, false)));
+
+        let events: Vec<_> = controls.events.drain(..).collect();
+        assert_eq!(
+            events,
+            vec![
+                PanelEvent::ButtonPressed(ButtonId(0)),
+                PanelEvent::ButtonReleased(ButtonId(0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_encoders() {
+        let mut controls = Controls::default();
+        controls.encoders.push((
+            Encoder::new(0, 0, 20).keys(Keycode::Left, Keycode::Right),
+            Hold::default(),
+            0,
+        ));
+        let wheel = |y| Event::MouseWheel {
+            timestamp: 0,
+            window_id: 0,
+            which: 0,
+            x: 0,
+            y,
+            direction: MouseWheelDirection::Normal,
+        };
+
+        // The corner of the bounding square is outside the knob
+        assert!(!controls.handle_event(&mouse_down(1, 1)));
+        assert!(!controls.handle_event(&wheel(2)));
+        assert!(controls.handle_event(&mouse_down(10, 10)));
+        assert!(controls.handle_event(&mouse_up(10, 10)));
+        assert!(controls.handle_event(&wheel(2)));
+        assert!(controls.handle_event(&key(Keycode::Left, true, false)));
+        assert!(controls.handle_event(&key(Keycode::Left, true, true)));
+        assert_eq!(controls.encoders[0].2, 0);
+
+        let events: Vec<_> = controls.events.drain(..).collect();
+        let turned = |steps| PanelEvent::EncoderTurned {
+            encoder: EncoderId(0),
+            steps,
+        };
+        assert_eq!(
+            events,
+            vec![
+                PanelEvent::EncoderPressed(EncoderId(0)),
+                PanelEvent::EncoderReleased(EncoderId(0)),
+                turned(2),
+                turned(-1),
+                turned(-1),
+            ]
+        );
+    }
+}
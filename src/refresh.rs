@@ -0,0 +1,188 @@
+// * Simulate LCD: A Simple LCD Screen Simulator *
+
+// Copyright 2023 Simon Varey - github.com/simonvarey
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// Imports
+
+use std::time::Duration;
+
+// Refresh Settings
+
+/// The refresh timing of a simulated panel which scans its display RAM out to the glass line by line.
+///
+/// Each refresh scans the physical rows of dots of the screen from top to bottom, one line at a time,
+/// followed by `blanking_lines` lines of vertical blanking during which nothing is scanned. The
+/// tearing effect (TE) signal is raised at the start of the blanking period, which is the safe time for
+/// a new frame to be written without tearing.
+///
+/// # Examples
+///
+/// ```
+/// # use simulate_lcd::Refresh;
+/// let refresh = Refresh {
+///     rate: 30,
+///     ..Default::default()
+/// };
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Refresh {
+    /// The number of refreshes per second. A rate of zero is treated as one.
+    pub rate: u32,
+    /// The number of lines of vertical blanking after the last row of dots in each refresh
+    pub blanking_lines: usize,
+}
+
+impl Default for Refresh {
+    /// Returns the timing of a typical panel: 60 refreshes per second, with 4 lines of blanking.
+    fn default() -> Self {
+        Self {
+            rate: 60,
+            blanking_lines: 4,
+        }
+    }
+}
+
+// Scanner
+
+// A step of the scan of a panel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ScanStep {
+    // The physical row of dots with the given index is scanned.
+    Line(usize),
+    // The tearing effect signal is raised.
+    TearingEffect,
+}
+
+// Tracks the position of the scan of a panel with `lines` rows of dots over time.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Scanner {
+    refresh: Refresh,
+    lines: usize,
+    // The position of the scan in lines since the start of the current refresh, including blanking
+    position: f64,
+}
+
+impl Scanner {
+    pub(crate) fn new(refresh: Refresh, lines: usize) -> Self {
+        Self {
+            refresh,
+            lines,
+            position: 0.0,
+        }
+    }
+
+    pub(crate) fn refresh(&self) -> Refresh {
+        self.refresh
+    }
+
+    // The number of lines in a whole refresh, including blanking.
+    fn total(&self) -> usize {
+        self.lines
+            .saturating_add(self.refresh.blanking_lines)
+            .max(1)
+    }
+
+    // Returns the row of dots being scanned, or `None` during blanking.
+    pub(crate) fn line(&self) -> Option<usize> {
+        // Note: the position is always between 0 and total, so this is a true cast
+        let line = self.position as usize;
+        (line < self.lines).then_some(line)
+    }
+
+    // Moves the scan on by `elapsed`, returning the steps it passed through in order.
+    //
+    // A line is scanned when the scan reaches it. If more than a whole refresh has elapsed, only the steps of
+    // the last refresh are returned, as the earlier ones would be overwritten by it.
+    pub(crate) fn advance(&mut self, elapsed: Duration) -> Vec<ScanStep> {
+        let total = self.total();
+        let lines_per_second = f64::from(self.refresh.rate.max(1)) * total as f64;
+        let end = self.position + elapsed.as_secs_f64() * lines_per_second;
+
+        // Note: positions are non-negative and far below u64::MAX, so these are true casts
+        let (first, last) = (self.position as u64 + 1, end as u64);
+        let first = first.max(last.saturating_sub(total as u64) + 1);
+        let te_line = self.lines % total;
+        let mut steps = Vec::new();
+        for boundary in first..=last {
+            // Note: the remainder is less than total, so this is a true cast
+            let line = (boundary % total as u64) as usize;
+            if line == te_line {
+                steps.push(ScanStep::TearingEffect);
+            }
+            if line < self.lines {
+                steps.push(ScanStep::Line(line));
+            }
+        }
+        self.position = end % total as f64;
+        steps
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scanner() {
+        let refresh = Refresh {
+            rate: 10,
+            blanking_lines: 2,
+        };
+        // 4 rows and 2 blanking lines at 10 Hz scan one line every 1/60 seconds
+        let mut scanner = Scanner::new(refresh, 4);
+        let line = Duration::from_secs(1) / 60;
+        assert_eq!(scanner.line(), Some(0));
+
+        assert_eq!(scanner.advance(line / 2), vec![]);
+        assert_eq!(
+            scanner.advance(line * 3),
+            vec![ScanStep::Line(1), ScanStep::Line(2), ScanStep::Line(3)]
+        );
+        assert_eq!(scanner.advance(line), vec![ScanStep::TearingEffect]);
+        assert_eq!(scanner.line(), None);
+        assert_eq!(
+            scanner.advance(line * 3),
+            vec![ScanStep::Line(0), ScanStep::Line(1)]
+        );
+
+        // Only the last refresh of a long wait is scanned
+        let steps = scanner.advance(line * 100);
+        assert_eq!(steps.len(), 5);
+        assert_eq!(
+            steps
+                .iter()
+                .filter(|step| **step == ScanStep::TearingEffect)
+                .count(),
+            1
+        );
+
+        // Without blanking, the signal is raised as the scan returns to the top
+        let mut scanner = Scanner::new(
+            Refresh {
+                rate: 10,
+                blanking_lines: 0,
+            },
+            2,
+        );
+        assert_eq!(
+            scanner.advance(Duration::from_secs(1) / 10 + line / 10),
+            vec![
+                ScanStep::Line(1),
+                ScanStep::TearingEffect,
+                ScanStep::Line(0)
+            ]
+        );
+    }
+}
@@ -0,0 +1,235 @@
+// * Simulate LCD: A Simple LCD Screen Simulator *
+
+// Copyright 2023 Simon Varey - github.com/simonvarey
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// Imports
+
+use std::{
+    thread::sleep,
+    time::{Duration, Instant},
+};
+
+use sdl2::{event::Event, keyboard::Keycode, EventPump, Sdl};
+
+use crate::LcdError;
+
+// Pacing
+
+// Schedules ticks at fixed deadlines, so that the time taken by each tick does not delay the next one and
+// the tick rate does not drift.
+#[derive(Debug, Clone, Copy)]
+struct Pacer {
+    period: Duration,
+    deadline: Instant,
+}
+
+impl Pacer {
+    // The number of periods a tick may fall behind its deadline before the schedule is restarted.
+    const MAX_LAG: u32 = 4;
+
+    fn new(period: Duration, start: Instant) -> Self {
+        Self {
+            period,
+            deadline: start + period,
+        }
+    }
+
+    // Returns how long to wait from `now` until the next tick, and schedules the tick after it.
+    //
+    // A tick which is late is not waited for, and the following ticks run early to catch up. If the ticks
+    // fall too far behind, for example because the window was being dragged, they are rescheduled from
+    // `now` instead of running a burst of ticks to catch up.
+    fn wait(&mut self, now: Instant) -> Duration {
+        let wait = self.deadline.saturating_duration_since(now);
+        if now.saturating_duration_since(self.deadline) > self.period * Self::MAX_LAG {
+            self.deadline = now + self.period;
+        } else {
+            self.deadline += self.period;
+        }
+        wait
+    }
+}
+
+// * Runner *
+
+/// Why [`Runner::run`] returned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitReason {
+    /// The application was asked to quit, usually because its window was closed.
+    Quit,
+    /// The Escape key was pressed.
+    Escape,
+    /// The update closure returned [`Control::Stop`].
+    Stopped,
+}
+
+/// Whether [`Runner::run`] should keep running after a tick, as returned by its update closure.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Control {
+    /// Keep running.
+    Continue,
+    /// Return from [`Runner::run`] with [`ExitReason::Stopped`].
+    Stop,
+}
+
+/// The state of a [`Runner`] at a tick, as passed to its update closure.
+#[derive(Debug)]
+pub struct Tick<'a> {
+    /// The number of the tick, counting from zero
+    pub number: u64,
+    /// The time since the runner started
+    pub elapsed: Duration,
+    /// The SDL events received since the previous tick, apart from those which stop the runner
+    pub events: &'a [Event],
+}
+
+/// A main loop which owns the SDL event pump and calls an update closure at a fixed tick rate.
+///
+/// Ticks are scheduled at fixed deadlines, so the time spent drawing in each tick does not slow the tick
+/// rate down. The runner stops when the application is asked to quit, or when the Escape key is
+/// pressed, unless disabled with [`quit_on_escape`].
+///
+/// # Examples
+///
+/// ```no_run
+/// # use simulate_lcd::{Control, LcdScreen, Runner};
+/// # let sdl_context = sdl2::init().unwrap();
+/// let mut screen = LcdScreen::<8, 8>::builder().build(&sdl_context).unwrap();
+/// let mut runner = Runner::new(&sdl_context, 2).unwrap();
+///
+/// let mut on = false;
+/// let reason = runner
+///     .run(|_| {
+///         on = !on;
+///         screen.draw_bitmap(&[[on; 8]; 8])?;
+///         Ok(Control::Continue)
+///     })
+///     .unwrap();
+/// println!("Stopped: {reason:?}");
+/// ```
+///
+/// [`quit_on_escape`]: crate::Runner::quit_on_escape
+pub struct Runner {
+    event_pump: EventPump,
+    period: Duration,
+    quit_on_escape: bool,
+}
+
+impl Runner {
+    /// Creates a runner which calls its update closure `ticks_per_second` times a second. A rate of zero
+    /// is treated as one.
+    ///
+    /// # Errors
+    ///
+    /// - [`LcdError::EventPump`] when the SDL event pump cannot be obtained, for example because one
+    ///   already exists
+    ///
+    pub fn new(sdl_context: &Sdl, ticks_per_second: u32) -> Result<Self, LcdError> {
+        Ok(Self {
+            event_pump: sdl_context.event_pump().map_err(LcdError::EventPump)?,
+            period: Duration::from_secs(1) / ticks_per_second.max(1),
+            quit_on_escape: true,
+        })
+    }
+
+    /// Sets whether pressing the Escape key stops the runner. It does by default.
+    pub fn quit_on_escape(mut self, quit: bool) -> Self {
+        self.quit_on_escape = quit;
+        self
+    }
+
+    /// Returns the event pump of the runner, for example to read the state of the keyboard or mouse.
+    pub fn event_pump(&mut self) -> &mut EventPump {
+        &mut self.event_pump
+    }
+
+    /// Runs the main loop, calling `update` once per tick with the events received since the previous
+    /// tick, until the application quits or `update` returns [`Control::Stop`].
+    ///
+    /// # Errors
+    ///
+    /// Any error returned by `update`, which also stops the runner.
+    ///
+    pub fn run<F>(&mut self, mut update: F) -> Result<ExitReason, LcdError>
+    where
+        F: FnMut(&Tick) -> Result<Control, LcdError>,
+    {
+        let start = Instant::now();
+        let mut pacer = Pacer::new(self.period, start);
+        let mut events = Vec::new();
+        let mut number = 0;
+        loop {
+            events.clear();
+            for event in self.event_pump.poll_iter() {
+                match event {
+                    Event::Quit { .. } => return Ok(ExitReason::Quit),
+                    Event::KeyDown {
+                        keycode: Some(Keycode::Escape),
+                        ..
+                    } if self.quit_on_escape => return Ok(ExitReason::Escape),
+                    _ => events.push(event),
+                }
+            }
+
+            let tick = Tick {
+                number,
+                elapsed: start.elapsed(),
+                events: &events,
+            };
+            if update(&tick)? == Control::Stop {
+                return Ok(ExitReason::Stopped);
+            }
+
+            sleep(pacer.wait(Instant::now()));
+            number += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pacer() {
+        let period = Duration::from_millis(10);
+        let start = Instant::now();
+        let mut pacer = Pacer::new(period, start);
+
+        // A quick tick waits for the rest of its period, and a slow one is made up by the next tick
+        assert_eq!(
+            pacer.wait(start + Duration::from_millis(3)),
+            Duration::from_millis(7)
+        );
+        assert_eq!(
+            pacer.wait(start + Duration::from_millis(25)),
+            Duration::ZERO
+        );
+        assert_eq!(
+            pacer.wait(start + Duration::from_millis(26)),
+            Duration::from_millis(4)
+        );
+
+        // Falling far behind restarts the schedule
+        assert_eq!(
+            pacer.wait(start + Duration::from_millis(200)),
+            Duration::ZERO
+        );
+        assert_eq!(
+            pacer.wait(start + Duration::from_millis(201)),
+            Duration::from_millis(9)
+        );
+    }
+}
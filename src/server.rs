@@ -0,0 +1,430 @@
+// * Simulate LCD: A Simple LCD Screen Simulator *
+
+// Copyright 2023 Simon Varey - github.com/simonvarey
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// Imports
+
+use std::{
+    io::{self, BufReader, ErrorKind, Read, Write},
+    net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+#[cfg(unix)]
+use std::{
+    os::unix::net::{UnixListener, UnixStream},
+    path::{Path, PathBuf},
+};
+
+use crate::{Bitmap, DrawCommand, FrameSender, LcdError};
+
+// Protocol
+
+// The bytes which start the handshake of a client.
+const MAGIC: &[u8; 4] = b"SLCD";
+
+// The reply to a handshake whose dimensions match those of the screen.
+const STATUS_OK: u8 = 0;
+// The reply to a handshake whose dimensions do not match those of the screen.
+const STATUS_MISMATCH: u8 = 1;
+
+// The opcodes of the messages a client sends after the handshake.
+const OP_FRAME: u8 = 0x01;
+const OP_RECT: u8 = 0x02;
+const OP_CLEAR: u8 = 0x03;
+const OP_INVERT: u8 = 0x04;
+
+// How long the server waits between checks for new connections.
+const ACCEPT_POLL: Duration = Duration::from_millis(20);
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(ErrorKind::InvalidData, message)
+}
+
+fn read_u16(reader: &mut impl Read) -> io::Result<usize> {
+    let mut bytes = [0; 2];
+    reader.read_exact(&mut bytes)?;
+    Ok(usize::from(u16::from_be_bytes(bytes)))
+}
+
+fn write_u16(writer: &mut impl Write, value: usize) -> io::Result<()> {
+    // Note: a dimension above u16::MAX cannot be sent, and is reported as u16::MAX. Clients cannot send
+    //   it either, so their handshakes never match such a screen
+    writer.write_all(&u16::try_from(value).unwrap_or(u16::MAX).to_be_bytes())
+}
+
+// Reads `rows` rows of `cols` dots, packed 8 to a byte with the first dot in the most significant bit
+// and each row starting on a new byte, and returns them unpacked in row-major order.
+fn read_dots(reader: &mut impl Read, rows: usize, cols: usize) -> io::Result<Vec<bool>> {
+    let mut packed = vec![0; cols.div_ceil(8)];
+    let mut dots = Vec::with_capacity(rows * cols);
+    for _ in 0..rows {
+        reader.read_exact(&mut packed)?;
+        dots.extend((0..cols).map(|col| packed[col / 8] & (0x80 >> (col % 8)) != 0));
+    }
+    Ok(dots)
+}
+
+// Performs the server side of the handshake of a client, returning whether the dimensions the client
+// sent match those of a screen with `rows` rows and `cols` columns of dots.
+fn handshake<S: Read + Write>(stream: &mut S, rows: usize, cols: usize) -> io::Result<bool> {
+    let mut magic = [0; 4];
+    stream.read_exact(&mut magic)?;
+    if &magic != MAGIC {
+        Err(invalid_data(format!("{magic:?} is not a handshake")))?
+    }
+    let (client_rows, client_cols) = (read_u16(stream)?, read_u16(stream)?);
+    let matches = client_rows == rows && client_cols == cols;
+    stream.write_all(&[if matches { STATUS_OK } else { STATUS_MISMATCH }])?;
+    write_u16(stream, rows)?;
+    write_u16(stream, cols)?;
+    stream.flush()?;
+    Ok(matches)
+}
+
+// Reads the next message of a client as a draw command, or returns `None` if the client has closed the
+// connection.
+fn read_command<const R: usize, const C: usize>(
+    reader: &mut impl Read,
+) -> io::Result<Option<DrawCommand<R, C>>> {
+    let mut opcode = [0];
+    if reader.read(&mut opcode)? == 0 {
+        return Ok(None);
+    }
+    let command = match opcode[0] {
+        OP_FRAME => {
+            // Note: the vec must be R in length, so this cannot fail
+            let mut frame: Box<Bitmap<C, R>> =
+                vec![[false; C]; R].into_boxed_slice().try_into().unwrap();
+            let dots = read_dots(reader, R, C)?;
+            for (row, row_dots) in frame.iter_mut().zip(dots.chunks(C.max(1))) {
+                row.copy_from_slice(row_dots);
+            }
+            DrawCommand::Frame(frame)
+        }
+        OP_RECT => {
+            let (row, col) = (read_u16(reader)?, read_u16(reader)?);
+            let (height, width) = (read_u16(reader)?, read_u16(reader)?);
+            if row + height > R || col + width > C {
+                Err(invalid_data(format!(
+                    "a {width}x{height} rect at ({row}, {col}) does not fit on a {C}x{R} screen"
+                )))?
+            }
+            DrawCommand::Patch {
+                row,
+                col,
+                width,
+                dots: read_dots(reader, height, width)?,
+            }
+        }
+        OP_CLEAR => DrawCommand::Clear,
+        OP_INVERT => DrawCommand::Invert,
+        opcode => Err(invalid_data(format!("{opcode:#04x} is not an opcode")))?,
+    };
+    Ok(Some(command))
+}
+
+// Serves a single client, sending its messages to the screen until either of them disconnects.
+fn serve_client<S: Read + Write, const R: usize, const C: usize>(
+    mut stream: S,
+    sender: &FrameSender<R, C>,
+) -> io::Result<()> {
+    if !handshake(&mut stream, R, C)? {
+        return Ok(());
+    }
+    let mut reader = BufReader::new(stream);
+    while let Some(command) = read_command(&mut reader)? {
+        if sender.send(command).is_err() {
+            break;
+        }
+    }
+    Ok(())
+}
+
+// Accepts clients until the server is stopped or the screen is dropped, serving each on its own thread.
+fn accept_clients<L, S, const R: usize, const C: usize>(
+    listener: L,
+    accept: fn(&L) -> io::Result<S>,
+    sender: FrameSender<R, C>,
+    stop: Arc<AtomicBool>,
+) where
+    S: Read + Write + Send + 'static,
+{
+    while !stop.load(Ordering::Relaxed) && sender.is_connected() {
+        match accept(&listener) {
+            Ok(stream) => {
+                let sender = sender.clone();
+                thread::spawn(move || serve_client(stream, &sender));
+            }
+            // Note: errors accepting a client, such as running out of file descriptors, only affect
+            //   that client, so the server keeps going
+            Err(_) => thread::sleep(ACCEPT_POLL),
+        }
+    }
+}
+
+// * Frame Server *
+
+/// A server which lets other processes draw to an [`LcdScreen`] over a TCP or Unix socket, such as
+/// firmware running in an emulator, or a test script in another language.
+///
+/// The server sends what its clients draw through the [`FrameSender`] of a channel opened with
+/// [`LcdScreen::frame_channel`], so it is drawn when the main thread calls [`LcdScreen::drain`]. It
+/// listens on a background thread, and serves each client on a thread of its own. Dropping the server
+/// stops it listening, while clients which are already connected are served until they disconnect or the
+/// screen is dropped.
+///
+/// This type is only available with the `server` feature.
+///
+/// # Protocol
+///
+/// Numbers are unsigned 16-bit big-endian integers. Dots are packed 8 to a byte with the first dot in
+/// the most significant bit and `1` meaning 'on', and each row of dots starts on a new byte, as in the
+/// binary PBM image format.
+///
+/// A client starts with a handshake: the 4 ASCII bytes `SLCD`, followed by the number of rows and the
+/// number of columns of dots it will draw. The server replies with a status byte, which is `0` if the
+/// dimensions match those of the screen and `1` if not, followed by the number of rows and columns of
+/// the screen. If the dimensions do not match, the server closes the connection.
+///
+/// The client then sends any number of messages, each starting with an opcode byte:
+///
+/// | Opcode | Message | Followed by |
+/// |--------|---------|-------------|
+/// | `0x01` | Full frame | `rows` rows of packed dots |
+/// | `0x02` | Partial rect | row, column, height and width of the rect, then `height` rows of `width` packed dots |
+/// | `0x03` | Clear every dot | nothing |
+/// | `0x04` | Invert every dot | nothing |
+///
+/// A rect must fit entirely on the screen. The server closes the connection on a rect which does not,
+/// or on an unknown opcode. For example, a Python client could draw a striped frame with:
+///
+/// ```python
+/// import socket, struct
+///
+/// client = socket.create_connection(("localhost", 7878))
+/// client.sendall(b"SLCD" + struct.pack(">HH", 32, 64))
+/// status, rows, cols = struct.unpack(">BHH", client.recv(5))
+/// client.sendall(b"\x01" + bytes([0xAA] * (32 * 64 // 8)))
+/// ```
+///
+/// # Examples
+///
+/// ```no_run
+/// # use simulate_lcd::{Control, Delivery, FrameServer, LcdScreen, Runner};
+/// # let sdl_context = sdl2::init().unwrap();
+/// let mut screen = LcdScreen::<32, 64>::builder().build(&sdl_context).unwrap();
+/// let sender = screen.frame_channel(4, Delivery::Queued);
+/// let _server = FrameServer::tcp("127.0.0.1:7878", sender).unwrap();
+///
+/// let mut runner = Runner::new(&sdl_context, 60).unwrap();
+/// runner
+///     .run(|_| {
+///         screen.drain()?;
+///         Ok(Control::Continue)
+///     })
+///     .unwrap();
+/// ```
+///
+/// [`LcdScreen`]: crate::LcdScreen
+/// [`LcdScreen::frame_channel`]: crate::LcdScreen::frame_channel
+/// [`LcdScreen::drain`]: crate::LcdScreen::drain
+#[derive(Debug)]
+pub struct FrameServer {
+    local_addr: Option<SocketAddr>,
+    #[cfg(unix)]
+    socket_path: Option<PathBuf>,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl FrameServer {
+    /// Starts a server listening for TCP clients on `addr`. A port of `0` picks a free port, which can
+    /// be read with [`local_addr`].
+    ///
+    /// # Errors
+    ///
+    /// - [`LcdError::Socket`] when the socket cannot be bound or configured
+    ///
+    /// [`local_addr`]: crate::FrameServer::local_addr
+    ///
+    pub fn tcp<A: ToSocketAddrs, const R: usize, const C: usize>(
+        addr: A,
+        sender: FrameSender<R, C>,
+    ) -> Result<Self, LcdError> {
+        let listener = TcpListener::bind(addr).map_err(LcdError::Socket)?;
+        listener.set_nonblocking(true).map_err(LcdError::Socket)?;
+        let local_addr = listener.local_addr().map_err(LcdError::Socket)?;
+        let accept = |listener: &TcpListener| -> io::Result<TcpStream> {
+            let (stream, _) = listener.accept()?;
+            stream.set_nonblocking(false)?;
+            stream.set_nodelay(true)?;
+            Ok(stream)
+        };
+        Ok(Self::start(listener, accept, sender, Some(local_addr)))
+    }
+
+    /// Starts a server listening for clients on a Unix socket at `path`. The socket file is removed
+    /// when the server is dropped.
+    ///
+    /// This method is only available on Unix platforms.
+    ///
+    /// # Errors
+    ///
+    /// - [`LcdError::Socket`] when the socket cannot be bound or configured, for example because `path`
+    ///   already exists
+    ///
+    #[cfg(unix)]
+    pub fn unix<P: AsRef<Path>, const R: usize, const C: usize>(
+        path: P,
+        sender: FrameSender<R, C>,
+    ) -> Result<Self, LcdError> {
+        let listener = UnixListener::bind(&path).map_err(LcdError::Socket)?;
+        listener.set_nonblocking(true).map_err(LcdError::Socket)?;
+        let accept = |listener: &UnixListener| -> io::Result<UnixStream> {
+            let (stream, _) = listener.accept()?;
+            stream.set_nonblocking(false)?;
+            Ok(stream)
+        };
+        let mut server = Self::start(listener, accept, sender, None);
+        server.socket_path = Some(path.as_ref().to_path_buf());
+        Ok(server)
+    }
+
+    fn start<L, S, const R: usize, const C: usize>(
+        listener: L,
+        accept: fn(&L) -> io::Result<S>,
+        sender: FrameSender<R, C>,
+        local_addr: Option<SocketAddr>,
+    ) -> Self
+    where
+        L: Send + 'static,
+        S: Read + Write + Send + 'static,
+    {
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = stop.clone();
+        Self {
+            local_addr,
+            #[cfg(unix)]
+            socket_path: None,
+            stop,
+            thread: Some(thread::spawn(move || {
+                accept_clients(listener, accept, sender, thread_stop)
+            })),
+        }
+    }
+
+    /// Returns the address the server is listening on, or `None` if it is listening on a Unix socket.
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.local_addr
+    }
+}
+
+impl Drop for FrameServer {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            // Note: a panic on the listening thread has already been printed, and the server is stopped
+            //   either way
+            let _ = thread.join();
+        }
+        #[cfg(unix)]
+        if let Some(path) = &self.socket_path {
+            // Note: the socket file may already have been removed by something else, which is fine
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::{channel::Inbox, Delivery};
+
+    #[test]
+    fn test_protocol() {
+        // Handshakes
+        let mut stream = Cursor::new(b"SLCD\x00\x02\x00\x0a".to_vec());
+        assert!(handshake(&mut stream, 2, 10).unwrap());
+        assert_eq!(stream.get_ref()[8..], [0, 0, 2, 0, 10]);
+        let mut stream = Cursor::new(b"SLCD\x00\x03\x00\x0a".to_vec());
+        assert!(!handshake(&mut stream, 2, 10).unwrap());
+        assert_eq!(stream.get_ref()[8], STATUS_MISMATCH);
+        assert!(handshake(&mut Cursor::new(b"LCDS\x00\x02\x00\x0a".to_vec()), 2, 10).is_err());
+
+        // Messages
+        let mut messages = Cursor::new(
+            [
+                &[OP_FRAME, 0b1000_0000, 0b0100_0000, 0x00, 0b1100_0000][..],
+                &[OP_RECT, 0, 1, 0, 8, 0, 1, 0, 2, 0b0100_0000],
+                &[OP_CLEAR, OP_INVERT],
+            ]
+            .concat(),
+        );
+        let mut frame = [[false; 10]; 2];
+        frame[0][0] = true;
+        frame[0][9] = true;
+        frame[1][8] = true;
+        frame[1][9] = true;
+        let expected: [DrawCommand<2, 10>; 4] = [
+            DrawCommand::Frame(Box::new(frame)),
+            DrawCommand::Patch {
+                row: 1,
+                col: 8,
+                width: 2,
+                dots: vec![false, true],
+            },
+            DrawCommand::Clear,
+            DrawCommand::Invert,
+        ];
+        for command in expected {
+            assert_eq!(read_command(&mut messages).unwrap(), Some(command));
+        }
+        assert_eq!(read_command::<2, 10>(&mut messages).unwrap(), None);
+
+        // A rect off the screen, an unknown opcode and a truncated frame are errors
+        let rect = [OP_RECT, 0, 1, 0, 9, 0, 1, 0, 2, 0];
+        assert!(read_command::<2, 10>(&mut Cursor::new(rect)).is_err());
+        assert!(read_command::<2, 10>(&mut Cursor::new([0x7f])).is_err());
+        assert!(read_command::<2, 10>(&mut Cursor::new([OP_FRAME, 0])).is_err());
+    }
+
+    #[test]
+    fn test_server() {
+        let (inbox, sender) = Inbox::<2, 10>::new(4, Delivery::Queued);
+        let server = FrameServer::tcp("127.0.0.1:0", sender).unwrap();
+
+        let mut client = TcpStream::connect(server.local_addr().unwrap()).unwrap();
+        client.write_all(b"SLCD\x00\x02\x00\x0a").unwrap();
+        let mut reply = [0; 5];
+        client.read_exact(&mut reply).unwrap();
+        assert_eq!(reply, [STATUS_OK, 0, 2, 0, 10]);
+        client.write_all(&[OP_INVERT, OP_CLEAR]).unwrap();
+        drop(client);
+
+        let mut received = Vec::new();
+        while received.len() < 2 {
+            received.extend(inbox.take());
+            thread::yield_now();
+        }
+        assert_eq!(received, [DrawCommand::Invert, DrawCommand::Clear]);
+    }
+}
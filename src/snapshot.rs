@@ -0,0 +1,613 @@
+// * Simulate LCD: A Simple LCD Screen Simulator *
+
+// Copyright 2023 Simon Varey - github.com/simonvarey
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// Imports
+
+use std::{
+    env,
+    fmt::{Display, Formatter, Result as FmtResult, Write},
+    fs,
+    io::{self, BufRead, ErrorKind},
+    path::Path,
+    str::FromStr,
+};
+
+use crate::{Bitmap, LcdError};
+
+// Text Format
+
+const ON: char = '#';
+const OFF: char = '.';
+const HEADER: &str = "lcd ";
+
+/// The environment variable which makes [`Snapshot::assert_golden`] write golden files instead of
+/// comparing against them, when it is set to `1`.
+pub const UPDATE_GOLDEN_VAR: &str = "SIMULATE_LCD_UPDATE_GOLDEN";
+
+// * Snapshot *
+
+/// The dots of a screen of `R` rows and `C` columns at one moment, which can be written and read as text.
+///
+/// The text of a snapshot is a grid of `#` for a dot which is 'on' and `.` for a dot which is 'off', with
+/// one line per row of dots. Formatting a snapshot with `{}` writes just the grid, and formatting it with
+/// `{:#}` first writes a header line giving the number of rows and columns, such as `lcd 2x3`. A snapshot
+/// can be parsed from either form with [`str::parse`], and the size in the header, if there is one, must
+/// match the size of the snapshot. Blank lines before and after the grid are ignored.
+///
+/// Snapshots make the expected contents of a screen readable in tests and in code review. See
+/// [`assert_golden`] for comparing a screen against a snapshot stored in a file.
+///
+/// # Examples
+///
+/// ```
+/// # use simulate_lcd::Snapshot;
+/// let snapshot = Snapshot::new(&[[true, false, true], [false, true, false]]);
+/// assert_eq!(snapshot.to_string(), "#.#\n.#.\n");
+/// assert_eq!(format!("{snapshot:#}"), "lcd 2x3\n#.#\n.#.\n");
+///
+/// let parsed: Snapshot<2, 3> = "lcd 2x3\n#.#\n.#.\n".parse().unwrap();
+/// assert_eq!(parsed, snapshot);
+/// ```
+///
+/// [`str::parse`]: std::primitive::str::parse
+/// [`assert_golden`]: crate::Snapshot::assert_golden
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snapshot<const R: usize, const C: usize> {
+    bitmap: Box<Bitmap<C, R>>,
+}
+
+impl<const R: usize, const C: usize> Snapshot<R, C> {
+    /// Creates a snapshot of the dots of a bitmap.
+    pub fn new(bm: &Bitmap<C, R>) -> Self {
+        Self {
+            bitmap: Box::new(*bm),
+        }
+    }
+
+    /// Returns the dots of the snapshot, with `true` for a dot which is 'on'.
+    pub fn bitmap(&self) -> &Bitmap<C, R> {
+        &self.bitmap
+    }
+
+    /// Returns the dots of the snapshot as a bitmap, which can be drawn to a screen.
+    pub fn into_bitmap(self) -> Box<Bitmap<C, R>> {
+        self.bitmap
+    }
+
+    /// Compares the snapshot to the golden snapshot stored in the file at `path`, and panics with a
+    /// visual diff of the two if they do not match. This is meant to be called from tests, such as
+    /// `screen.snapshot().assert_golden("tests/golden/menu.txt")`.
+    ///
+    /// The diff shows the golden snapshot, with `+` for a dot which is 'on' but should be 'off', and `-`
+    /// for a dot which is 'off' but should be 'on'.
+    ///
+    /// When the environment variable [`UPDATE_GOLDEN_VAR`] is set to `1`, the snapshot is written to the
+    /// file instead, with its header, creating the file and its directory if needed. This is how golden
+    /// files are first created, and updated after an intended change to what the screen shows.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the snapshot does not match the golden snapshot, or the golden file cannot be read,
+    /// parsed or written.
+    ///
+    /// [`UPDATE_GOLDEN_VAR`]: crate::UPDATE_GOLDEN_VAR
+    ///
+    #[track_caller]
+    pub fn assert_golden<P: AsRef<Path>>(&self, path: P) {
+        let path = path.as_ref();
+        if env::var(UPDATE_GOLDEN_VAR).is_ok_and(|update| update == "1") {
+            if let Some(dir) = path.parent() {
+                if let Err(err) = fs::create_dir_all(dir) {
+                    panic!(
+                        "could not create the directory of {}: {err}",
+                        path.display()
+                    );
+                }
+            }
+            if let Err(err) = fs::write(path, format!("{self:#}")) {
+                panic!("could not write the golden file {}: {err}", path.display());
+            }
+            return;
+        }
+
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(err) => panic!(
+                "could not read the golden file {}: {err}\nRun the test with {UPDATE_GOLDEN_VAR}=1 to \
+                 create it.",
+                path.display()
+            ),
+        };
+        let golden: Snapshot<R, C> = match text.parse() {
+            Ok(golden) => golden,
+            Err(err) => panic!("could not parse the golden file {}: {err}", path.display()),
+        };
+        if let Some(diff) = golden.visual_diff(self) {
+            panic!(
+                "the screen does not match the golden file {}\n{diff}\nRun the test with \
+                 {UPDATE_GOLDEN_VAR}=1 to accept the new snapshot.",
+                path.display()
+            );
+        }
+    }
+
+    // Returns a grid of the dots of this snapshot, marking the dots of `actual` which differ from it, or
+    // `None` if they do not differ.
+    fn visual_diff(&self, actual: &Snapshot<R, C>) -> Option<String> {
+        let mut differing = 0;
+        let mut grid = String::new();
+        for (expected_row, actual_row) in self.bitmap.iter().zip(actual.bitmap.iter()) {
+            for (expected, actual) in expected_row.iter().zip(actual_row) {
+                grid.push(match (expected, actual) {
+                    (true, true) => ON,
+                    (false, false) => OFF,
+                    (false, true) => '+',
+                    (true, false) => '-',
+                });
+                differing += usize::from(expected != actual);
+            }
+            grid.push('\n');
+        }
+        if differing == 0 {
+            return None;
+        }
+        let mut diff = format!(
+            "{differing} of {} dots differ ('+' is 'on' but should be 'off', '-' is 'off' but should be \
+             'on'):\n",
+            R * C
+        );
+        // Note: writing to a `String` cannot fail
+        let _ = write!(diff, "{HEADER}{R}x{C}\n{grid}");
+        Some(diff)
+    }
+}
+
+impl<const R: usize, const C: usize> From<&Bitmap<C, R>> for Snapshot<R, C> {
+    fn from(bm: &Bitmap<C, R>) -> Self {
+        Self::new(bm)
+    }
+}
+
+impl<const R: usize, const C: usize> From<Box<Bitmap<C, R>>> for Snapshot<R, C> {
+    fn from(bitmap: Box<Bitmap<C, R>>) -> Self {
+        Self { bitmap }
+    }
+}
+
+impl<const R: usize, const C: usize> Display for Snapshot<R, C> {
+    fn fmt(&self, fmtr: &mut Formatter<'_>) -> FmtResult {
+        write_text(fmtr, R, C, self.bitmap.as_flattened())
+    }
+}
+
+impl<const R: usize, const C: usize> FromStr for Snapshot<R, C> {
+    type Err = LcdError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let (_, _, dots) = parse_text(text, Some((R, C)))?;
+        // Note: the vec must be R in length, so this cannot fail
+        let mut bitmap: Box<Bitmap<C, R>> =
+            vec![[false; C]; R].into_boxed_slice().try_into().unwrap();
+        // Note: the text has been checked to have R rows of C dots, so the lengths match
+        bitmap.as_flattened_mut().copy_from_slice(&dots);
+        Ok(Self { bitmap })
+    }
+}
+
+// Writes the text of a snapshot of `rows` rows and `cols` columns of dots, with its header if the
+// formatter is alternate.
+fn write_text(fmtr: &mut Formatter<'_>, rows: usize, cols: usize, dots: &[bool]) -> FmtResult {
+    if fmtr.alternate() {
+        writeln!(fmtr, "{HEADER}{rows}x{cols}")?;
+    }
+    for row in 0..rows {
+        for &on in &dots[row * cols..(row + 1) * cols] {
+            fmtr.write_char(if on { ON } else { OFF })?;
+        }
+        fmtr.write_char('\n')?;
+    }
+    Ok(())
+}
+
+// Parses the text of a snapshot, returning its number of rows and columns and its dots in row-major
+// order. The size of the snapshot is `size` if it is given, which a header must then match. Otherwise
+// it is the size given by the header, or without a header, the number of rows before the first blank
+// line and the number of dots of the first row.
+fn parse_text(
+    text: &str,
+    size: Option<(usize, usize)>,
+) -> Result<(usize, usize, Vec<bool>), LcdError> {
+    let error = |line: usize, reason: String| LcdError::ParseSnapshot { line, reason };
+
+    let mut lines = text
+        .lines()
+        .enumerate()
+        .map(|(index, line)| (index + 1, line.trim_end()))
+        .skip_while(|(_, line)| line.is_empty())
+        .peekable();
+    let mut size = size;
+    if let Some((number, header)) = lines.next_if(|(_, line)| line.starts_with(HEADER)) {
+        let header_size = &header[HEADER.len()..];
+        let (rows, cols) = header_size
+            .split_once('x')
+            .and_then(|(rows, cols)| Some((rows.parse().ok()?, cols.parse().ok()?)))
+            .ok_or_else(|| {
+                error(
+                    number,
+                    format!("{header_size:?} is not a size such as \"64x128\""),
+                )
+            })?;
+        match size {
+            Some((expected_rows, expected_cols))
+                if (rows, cols) != (expected_rows, expected_cols) =>
+            {
+                Err(error(
+                    number,
+                    format!(
+                        "the snapshot has {rows} rows and {cols} columns, not {expected_rows} and \
+                         {expected_cols}"
+                    ),
+                ))?
+            }
+            _ => size = Some((rows, cols)),
+        }
+    }
+    let (rows, cols) = size.unwrap_or_else(|| {
+        let grid = lines.clone().take_while(|(_, line)| !line.is_empty());
+        let cols = lines.peek().map_or(0, |(_, line)| line.chars().count());
+        (grid.count(), cols)
+    });
+
+    // Note: the size may come from a header, so the dots are not allocated up front
+    let mut dots = Vec::new();
+    let mut last = 0;
+    for _ in 0..rows {
+        let Some((number, line)) = lines.next() else {
+            Err(error(
+                last + 1,
+                format!("the snapshot has fewer than {rows} rows"),
+            ))?
+        };
+        if line.chars().count() != cols {
+            Err(error(
+                number,
+                format!("the row {line:?} does not have {cols} dots"),
+            ))?
+        }
+        for char in line.chars() {
+            dots.push(match char {
+                ON => true,
+                OFF => false,
+                _ => Err(error(
+                    number,
+                    format!("{char:?} is not a dot. Use '{ON}' or '{OFF}'"),
+                ))?,
+            });
+        }
+        last = number;
+    }
+    if let Some((number, _)) = lines.find(|(_, line)| !line.is_empty()) {
+        Err(error(
+            number,
+            format!("the snapshot has more than {rows} rows"),
+        ))?
+    }
+    Ok((rows, cols, dots))
+}
+
+// * Dynamic Snapshot *
+
+/// The dots of a screen at one moment, whose number of rows and columns is only known at run time.
+///
+/// A [`Snapshot`] is sized by const parameters. Programs which read captures of screens of any size,
+/// such as the `lcd-diff` binary, can use this type instead. It is written and parsed as the same text
+/// as a snapshot, and without a header the size of the snapshot is taken from its grid, which ends at the
+/// first blank line. It can also be read from a binary (P4) or plain (P1) PBM image with [`read_pbm`].
+///
+/// # Examples
+///
+/// ```
+/// # use simulate_lcd::DynSnapshot;
+/// let snapshot: DynSnapshot = "#.#\n.#.\n".parse().unwrap();
+/// assert_eq!((snapshot.rows(), snapshot.cols()), (2, 3));
+/// assert_eq!(format!("{snapshot:#}"), "lcd 2x3\n#.#\n.#.\n");
+///
+/// let pbm = DynSnapshot::read_pbm(&mut &b"P1 3 2\n1 0 1\n0 1 0\n"[..]).unwrap();
+/// assert_eq!(pbm, Some(snapshot));
+/// ```
+///
+/// [`read_pbm`]: crate::DynSnapshot::read_pbm
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DynSnapshot {
+    rows: usize,
+    cols: usize,
+    dots: Vec<bool>,
+}
+
+impl DynSnapshot {
+    /// Creates a snapshot of `rows` rows and `cols` columns of dots, given in rows from top to bottom,
+    /// with `true` for a dot which is 'on'.
+    ///
+    /// # Errors
+    ///
+    /// - [`LcdError::BitmapSize`] when the number of dots is not `rows` times `cols`
+    ///
+    pub fn new(rows: usize, cols: usize, dots: Vec<bool>) -> Result<Self, LcdError> {
+        if rows.checked_mul(cols) != Some(dots.len()) {
+            Err(LcdError::BitmapSize {
+                expected: rows.saturating_mul(cols),
+                actual: dots.len(),
+            })?
+        }
+        Ok(Self { rows, cols, dots })
+    }
+
+    /// Returns the number of rows of dots of the snapshot.
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Returns the number of columns of dots of the snapshot.
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// Returns the dots of the snapshot in rows from top to bottom, with `true` for a dot which is 'on'.
+    pub fn dots(&self) -> &[bool] {
+        &self.dots
+    }
+
+    /// Returns the dots of the snapshot in rows from top to bottom, which can be drawn to a
+    /// [`DynLcdScreen`].
+    ///
+    /// [`DynLcdScreen`]: crate::DynLcdScreen
+    pub fn into_dots(self) -> Vec<bool> {
+        self.dots
+    }
+
+    /// Reads a binary (P4) or plain (P1) PBM image, in which a `1` is a dot which is 'on'. Any bytes after
+    /// the image are left in the reader, so a stream of images can be read one after another.
+    ///
+    /// Returns `None` if the reader has nothing but whitespace left before an image starts.
+    ///
+    /// # Errors
+    ///
+    /// Returns any error from reading from `reader`, an [`ErrorKind::UnexpectedEof`] error when the
+    /// image is truncated, and an [`ErrorKind::InvalidData`] error when it is not a PBM image, or its
+    /// size is too large to be held in memory.
+    ///
+    /// [`ErrorKind::UnexpectedEof`]: std::io::ErrorKind::UnexpectedEof
+    /// [`ErrorKind::InvalidData`]: std::io::ErrorKind::InvalidData
+    ///
+    pub fn read_pbm(reader: &mut impl BufRead) -> io::Result<Option<Self>> {
+        let invalid_data = |message: String| io::Error::new(ErrorKind::InvalidData, message);
+
+        skip_pbm_space(reader)?;
+        if reader.fill_buf()?.is_empty() {
+            return Ok(None);
+        }
+        let magic = read_pbm_token(reader)?;
+        if magic != "P4" && magic != "P1" {
+            Err(invalid_data(format!("{magic:?} is not a PBM image")))?
+        }
+        let mut size = [0_usize; 2];
+        for value in &mut size {
+            let token = read_pbm_token(reader)?;
+            *value = token
+                .parse()
+                .map_err(|_| invalid_data(format!("{token:?} is not a PBM image size")))?;
+        }
+        let [cols, rows] = size;
+        let len = rows
+            .checked_mul(cols)
+            .ok_or_else(|| invalid_data(format!("a {cols}x{rows} PBM image is too large")))?;
+
+        // Note: the size comes from the header, so the dots are not allocated up front
+        let mut dots = Vec::new();
+        if magic == "P4" {
+            // Note: a single whitespace byte separates the header from the packed dots
+            reader.consume(1);
+            // Note: each row starts on a new byte, with its first dot in the most significant bit
+            let mut byte = [0];
+            for _ in 0..rows {
+                for col in 0..cols {
+                    if col % 8 == 0 {
+                        reader.read_exact(&mut byte)?;
+                    }
+                    dots.push(byte[0] & (0x80 >> (col % 8)) != 0);
+                }
+            }
+        } else {
+            while dots.len() < len {
+                skip_pbm_space(reader)?;
+                let Some(&byte) = reader.fill_buf()?.first() else {
+                    Err(io::Error::from(ErrorKind::UnexpectedEof))?
+                };
+                match byte {
+                    b'0' | b'1' => dots.push(byte == b'1'),
+                    _ => Err(invalid_data(format!(
+                        "{:?} is not a PBM dot",
+                        char::from(byte)
+                    )))?,
+                }
+                reader.consume(1);
+            }
+        }
+        Ok(Some(Self { rows, cols, dots }))
+    }
+}
+
+impl<const R: usize, const C: usize> From<&Snapshot<R, C>> for DynSnapshot {
+    fn from(snapshot: &Snapshot<R, C>) -> Self {
+        Self {
+            rows: R,
+            cols: C,
+            dots: snapshot.bitmap.as_flattened().to_vec(),
+        }
+    }
+}
+
+impl Display for DynSnapshot {
+    fn fmt(&self, fmtr: &mut Formatter<'_>) -> FmtResult {
+        write_text(fmtr, self.rows, self.cols, &self.dots)
+    }
+}
+
+impl FromStr for DynSnapshot {
+    type Err = LcdError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let (rows, cols, dots) = parse_text(text, None)?;
+        Ok(Self { rows, cols, dots })
+    }
+}
+
+// * PBM *
+
+// Skips the whitespace and comments between the tokens of a PBM header.
+fn skip_pbm_space(reader: &mut impl BufRead) -> io::Result<()> {
+    let mut in_comment = false;
+    loop {
+        let Some(&byte) = reader.fill_buf()?.first() else {
+            return Ok(());
+        };
+        match byte {
+            b'\n' | b'\r' => in_comment = false,
+            b'#' => in_comment = true,
+            _ if in_comment || byte.is_ascii_whitespace() => {}
+            _ => return Ok(()),
+        }
+        reader.consume(1);
+    }
+}
+
+// Reads a token of a PBM header, after skipping any whitespace and comments before it.
+fn read_pbm_token(reader: &mut impl BufRead) -> io::Result<String> {
+    skip_pbm_space(reader)?;
+    let mut token = String::new();
+    while let Some(&byte) = reader.fill_buf()?.first() {
+        if byte.is_ascii_whitespace() || byte == b'#' {
+            break;
+        }
+        token.push(char::from(byte));
+        reader.consume(1);
+    }
+    Ok(token)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text() {
+        let snapshot = Snapshot::new(&[[true, false, false], [false, false, true]]);
+        assert_eq!(snapshot.to_string(), "#..\n..#\n");
+        assert_eq!(format!("{snapshot:#}"), "lcd 2x3\n#..\n..#\n");
+        assert_eq!("#..\r\n..#".parse::<Snapshot<2, 3>>().unwrap(), snapshot);
+        assert_eq!(
+            "\n\nlcd 2x3\n#..\n..#\n\n"
+                .parse::<Snapshot<2, 3>>()
+                .unwrap(),
+            snapshot
+        );
+
+        let line = |text: &str| match text.parse::<Snapshot<2, 3>>() {
+            Err(LcdError::ParseSnapshot { line, .. }) => line,
+            other => panic!("{other:?} is not a parse error"),
+        };
+        assert_eq!(line("lcd 3x2\n#..\n..#\n"), 1);
+        assert_eq!(line("lcd 2 by 3\n#..\n..#\n"), 1);
+        assert_eq!(line("lcd 2x3\n#..\n..#.\n"), 3);
+        assert_eq!(line("#..\n..o\n"), 2);
+        assert_eq!(line("#..\n"), 2);
+        assert_eq!(line("#..\n..#\n\n...\n"), 4);
+    }
+
+    #[test]
+    fn test_dyn_snapshot() {
+        let snapshot: DynSnapshot = "\n#..\n..#\n\n".parse().unwrap();
+        assert_eq!((snapshot.rows(), snapshot.cols()), (2, 3));
+        assert_eq!(snapshot.dots(), [true, false, false, false, false, true]);
+        assert_eq!(
+            "lcd 2x3\n#..\n..#\n".parse::<DynSnapshot>().unwrap(),
+            snapshot
+        );
+        assert_eq!(
+            DynSnapshot::from(&Snapshot::new(&[
+                [true, false, false],
+                [false, false, true]
+            ])),
+            snapshot
+        );
+
+        // Rows after the grid are rejected, as for a snapshot of a known size
+        assert!("#..\n..#\n\n...\n".parse::<DynSnapshot>().is_err());
+        assert!("lcd 2x3\n#..\n".parse::<DynSnapshot>().is_err());
+        assert!("lcd 99999999999999999999x2\n"
+            .parse::<DynSnapshot>()
+            .is_err());
+        assert!("#..\n..\n".parse::<DynSnapshot>().is_err());
+    }
+
+    #[test]
+    fn test_pbm() {
+        let expected =
+            DynSnapshot::new(2, 3, vec![true, false, false, false, false, true]).unwrap();
+        let mut reader = &b"P4\n# a comment\n3 2\n\x80\x20P1 3 2\n1 0 0\n001\n"[..];
+        assert_eq!(
+            DynSnapshot::read_pbm(&mut reader).unwrap().as_ref(),
+            Some(&expected)
+        );
+        assert_eq!(
+            DynSnapshot::read_pbm(&mut reader).unwrap().as_ref(),
+            Some(&expected)
+        );
+        assert_eq!(DynSnapshot::read_pbm(&mut reader).unwrap(), None);
+
+        let error = |mut bytes: &[u8]| DynSnapshot::read_pbm(&mut bytes).unwrap_err().kind();
+        assert_eq!(error(b"P4 3 2\n\x80"), ErrorKind::UnexpectedEof);
+        assert_eq!(error(b"P6 3 2\n"), ErrorKind::InvalidData);
+        assert_eq!(error(b"P1 3 2\n1 0 2\n"), ErrorKind::InvalidData);
+        assert_eq!(
+            error(format!("P4 {} {}\n", usize::MAX, usize::MAX).as_bytes()),
+            ErrorKind::InvalidData
+        );
+    }
+
+    #[test]
+    fn test_golden() {
+        let golden = Snapshot::new(&[[true, true], [false, false]]);
+        assert_eq!(golden.visual_diff(&golden.clone()), None);
+        let diff = golden
+            .visual_diff(&Snapshot::new(&[[true, false], [false, true]]))
+            .unwrap();
+        assert!(diff.starts_with("2 of 4 dots differ"));
+        assert!(diff.ends_with("\nlcd 2x2\n#-\n.+\n"));
+
+        let path = env::temp_dir()
+            .join(format!("simulate-lcd-{}", std::process::id()))
+            .join("golden.txt");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, format!("{golden:#}")).unwrap();
+        golden.assert_golden(&path);
+        let mismatch = std::panic::catch_unwind(|| {
+            Snapshot::new(&[[true, true], [true, false]]).assert_golden(&path)
+        });
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+        assert!(mismatch.is_err());
+    }
+}
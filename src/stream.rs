@@ -0,0 +1,171 @@
+// * Simulate LCD: A Simple LCD Screen Simulator *
+
+// Copyright 2023 Simon Varey - github.com/simonvarey
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// Imports
+
+use std::{
+    borrow::Borrow,
+    future::{poll_fn, Future},
+    pin::{pin, Pin},
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use futures_core::Stream;
+use sdl2::{event::Event, EventPump, Sdl};
+use tokio::time::{sleep, Instant, Sleep};
+
+use crate::{Bitmap, LcdError, LcdScreen};
+
+// Frame Streams
+
+impl<const R: usize, const C: usize> LcdScreen<R, C> {
+    /// Draws each bitmap from a stream with [`draw_bitmap`] as soon as it arrives, until the stream ends,
+    /// and returns the number of bitmaps drawn. While waiting for a bitmap, the executor is free to run
+    /// other tasks.
+    ///
+    /// SDL windows can only be drawn from the main thread, so the returned future is not `Send`. It can
+    /// be awaited in the main task of a tokio runtime, or spawned on a [`LocalSet`]. If the screen has a
+    /// [`Refresh`] timing, its scan is updated each time a bitmap arrives.
+    ///
+    /// This method is only available with the `async` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use simulate_lcd::LcdScreen;
+    /// # use tokio::sync::mpsc;
+    /// # use tokio_stream::wrappers::ReceiverStream;
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    /// # let sdl_context = sdl2::init().unwrap();
+    /// let mut screen = LcdScreen::<32, 64>::builder().build(&sdl_context).unwrap();
+    ///
+    /// let (frames, receiver) = mpsc::channel(4);
+    /// tokio::spawn(async move {
+    ///     for on in [true, false, true] {
+    ///         frames.send([[on; 64]; 32]).await.unwrap();
+    ///     }
+    /// });
+    /// screen.play(ReceiverStream::new(receiver)).await.unwrap();
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// - [`LcdError::Fill`] when there is an error filling one of the dots with the relevant color
+    /// - [`LcdError::Texture`] when there is an error drawing the dots through a texture
+    ///
+    /// [`draw_bitmap`]: crate::LcdScreen::draw_bitmap
+    /// [`LocalSet`]: https://docs.rs/tokio/latest/tokio/task/struct.LocalSet.html
+    /// [`Refresh`]: crate::Refresh
+    ///
+    pub async fn play<S>(&mut self, frames: S) -> Result<u64, LcdError>
+    where
+        S: Stream,
+        S::Item: Borrow<Bitmap<C, R>>,
+    {
+        let mut frames = pin!(frames);
+        let mut drawn = 0;
+        while let Some(frame) = poll_fn(|cx| frames.as_mut().poll_next(cx)).await {
+            self.update()?;
+            self.draw_bitmap(frame.borrow())?;
+            drawn += 1;
+        }
+        Ok(drawn)
+    }
+}
+
+// * Event Stream *
+
+/// A stream of SDL events, for reading input from async code without blocking the executor.
+///
+/// SDL cannot wake an executor when an event arrives, so the stream checks for events at a fixed poll
+/// interval while it is waiting, sleeping on a tokio timer in between. Like the SDL event pump it owns,
+/// an event stream can only be created and used on the main thread, and only one can exist at a time.
+/// The stream never ends, as closing the window is reported by an [`Event::Quit`] event.
+///
+/// This type is only available with the `async` feature.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use std::time::Duration;
+/// # use sdl2::event::Event;
+/// # use simulate_lcd::EventStream;
+/// # use tokio_stream::StreamExt;
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// # let sdl_context = sdl2::init().unwrap();
+/// let mut events = EventStream::new(&sdl_context, Duration::from_millis(5)).unwrap();
+/// while let Some(event) = events.next().await {
+///     if let Event::Quit { .. } = event {
+///         break;
+///     }
+/// }
+/// # }
+/// ```
+///
+/// [`Event::Quit`]: sdl2::event::Event::Quit
+pub struct EventStream {
+    event_pump: EventPump,
+    poll_interval: Duration,
+    sleep: Pin<Box<Sleep>>,
+}
+
+impl EventStream {
+    /// Creates a stream of the SDL events of `sdl_context`, which checks for new events every
+    /// `poll_interval` while it is waiting for one.
+    ///
+    /// # Errors
+    ///
+    /// - [`LcdError::EventPump`] when the SDL event pump cannot be obtained, for example because one
+    ///   already exists
+    ///
+    pub fn new(sdl_context: &Sdl, poll_interval: Duration) -> Result<Self, LcdError> {
+        Ok(Self {
+            event_pump: sdl_context.event_pump().map_err(LcdError::EventPump)?,
+            poll_interval,
+            sleep: Box::pin(sleep(poll_interval)),
+        })
+    }
+
+    /// Returns the event pump of the stream, for example to read the state of the keyboard or mouse.
+    pub fn event_pump(&mut self) -> &mut EventPump {
+        &mut self.event_pump
+    }
+}
+
+impl Stream for EventStream {
+    type Item = Event;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Event>> {
+        let stream = self.get_mut();
+        loop {
+            if let Some(event) = stream.event_pump.poll_event() {
+                return Poll::Ready(Some(event));
+            }
+            // Wait for the next poll, which registers the waker with the timer
+            if stream.sleep.as_mut().poll(cx).is_pending() {
+                return Poll::Pending;
+            }
+            stream
+                .sleep
+                .as_mut()
+                .reset(Instant::now() + stream.poll_interval);
+        }
+    }
+}
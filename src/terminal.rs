@@ -0,0 +1,420 @@
+// * Simulate LCD: A Simple LCD Screen Simulator *
+
+// Copyright 2023 Simon Varey - github.com/simonvarey
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// Imports
+
+use std::io::{self, Stdout, Write};
+
+use sdl2::pixels::Color;
+
+use crate::{Bitmap, DotMatrix, LcdError, LcdTheme, Snapshot};
+
+// Glyphs
+
+/// The characters a [`TerminalScreen`] draws its dots with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Glyphs {
+    /// Half-block characters, such as '▀' and '▄', with each character cell showing a column of 2 dots.
+    /// The dots are close to square in most terminal fonts.
+    #[default]
+    HalfBlocks,
+    /// Braille patterns, such as '⣿', with each character cell showing 2 columns of 4 dots. The screen
+    /// takes up a quarter as many cells as with half-blocks, but the dots are small and spaced apart.
+    Braille,
+}
+
+impl Glyphs {
+    // The number of rows and columns of dots in each character cell.
+    fn cell_size(self) -> (usize, usize) {
+        match self {
+            Glyphs::HalfBlocks => (2, 1),
+            Glyphs::Braille => (4, 2),
+        }
+    }
+
+    // The bit of a cell's pattern for the dot at `row` and `col` within the cell.
+    fn bit(self, row: usize, col: usize) -> u8 {
+        match self {
+            Glyphs::HalfBlocks => 1 << row,
+            // Note: Braille numbers the dots down the left column, then down the right column, with the
+            // bottom row added later as dots 7 and 8
+            Glyphs::Braille => match (row, col) {
+                (3, col) => 0x40 << col,
+                (row, col) => 1 << (row + 3 * col),
+            },
+        }
+    }
+
+    // The character showing the 'on' dots of a cell's pattern.
+    fn glyph(self, pattern: u8) -> char {
+        match self {
+            Glyphs::HalfBlocks => [' ', '▀', '▄', '█'][usize::from(pattern)],
+            // Note: the Braille patterns are in Unicode order from U+2800, so the pattern is always a
+            // valid character
+            Glyphs::Braille => char::from_u32(0x2800 + u32::from(pattern)).unwrap(),
+        }
+    }
+}
+
+// Terminal Colors
+
+/// The ANSI color escape codes a [`TerminalScreen`] uses to show the colors of its theme.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TerminalColors {
+    /// 24-bit color, which shows the colors of the theme exactly, and is supported by most modern
+    /// terminals
+    #[default]
+    TrueColor,
+    /// The nearest colors of the standard 256-color palette, for terminals without 24-bit color
+    Ansi256,
+    /// No color codes, leaving the dots in the terminal's own foreground and background colors
+    Plain,
+}
+
+impl TerminalColors {
+    // Writes the escape code setting the foreground (38) or background (48) color.
+    fn write(self, out: &mut Vec<u8>, layer: u8, color: Color) {
+        // Note: writing to a `Vec` cannot fail
+        match self {
+            TerminalColors::TrueColor => {
+                let _ = write!(out, "\x1b[{layer};2;{};{};{}m", color.r, color.g, color.b);
+            }
+            TerminalColors::Ansi256 => {
+                let _ = write!(out, "\x1b[{layer};5;{}m", ansi256(color));
+            }
+            TerminalColors::Plain => {}
+        }
+    }
+}
+
+// Returns the index of the color in the 256-color palette nearest to `color`, from its 6x6x6 color
+// cube and its grey ramp.
+fn ansi256(color: Color) -> u8 {
+    const LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+    let distance = |r: u8, g: u8, b: u8| {
+        [(r, color.r), (g, color.g), (b, color.b)]
+            .iter()
+            .map(|&(a, b)| (i32::from(a) - i32::from(b)).pow(2))
+            .sum::<i32>()
+    };
+    let nearest_level = |value: u8| {
+        // Note: `LEVELS` is not empty, so there is always a nearest level
+        (0..LEVELS.len())
+            .min_by_key(|&level| (i32::from(LEVELS[level]) - i32::from(value)).abs())
+            .unwrap()
+    };
+    let (r, g, b) = (
+        nearest_level(color.r),
+        nearest_level(color.g),
+        nearest_level(color.b),
+    );
+    // Note: the indices are at most 5, so the cube index is at most 231
+    let cube = (16 + 36 * r + 6 * g + b) as u8;
+    let cube_distance = distance(LEVELS[r], LEVELS[g], LEVELS[b]);
+
+    let mean = (u16::from(color.r) + u16::from(color.g) + u16::from(color.b)) / 3;
+    // Note: the grey ramp runs from 8 to 238 in steps of 10, so the step is at most 23
+    let step = (mean.saturating_sub(3) / 10).min(23) as u8;
+    let grey = 8 + 10 * step;
+    if distance(grey, grey, grey) < cube_distance {
+        232 + step
+    } else {
+        cube
+    }
+}
+
+// * Terminal Screen *
+
+/// A simulated LCD dot-matrix screen drawn as text in a terminal, for working without a display, such
+/// as over SSH.
+///
+/// The screen has `R` rows and `C` columns of dots, like an [`LcdScreen`], and is drawn with the same
+/// [`draw_bitmap`] method. Code which should draw to either kind of screen can take a [`DotMatrix`].
+/// The dots are drawn as [`Glyphs`] in the 'on' color of a theme, on its 'off' color. The screen takes
+/// up the top left corner of the terminal, which is cleared when it is first drawn, and each later
+/// bitmap only rewrites the character cells whose dots have changed.
+///
+/// A terminal screen writes to the standard output by default, but can write to any [`Write`]r, such
+/// as a file or a socket. When it is dropped, it restores the cursor and colors of the terminal and
+/// moves the cursor below the screen.
+///
+/// # Examples
+///
+/// ```
+/// # use simulate_lcd::{Glyphs, LcdTheme, TerminalColors, TerminalScreen};
+/// let mut screen = TerminalScreen::<8, 8, Vec<u8>>::with_writer(
+///     Vec::new(),
+///     &LcdTheme::GREEN,
+///     Glyphs::Braille,
+///     TerminalColors::Ansi256,
+/// );
+/// let mut bm = [[false; 8]; 8];
+/// bm[0][0] = true;
+/// screen.draw_bitmap(&bm).unwrap();
+/// assert!(String::from_utf8_lossy(screen.writer()).contains('⠁'));
+/// ```
+///
+/// [`LcdScreen`]: crate::LcdScreen
+/// [`draw_bitmap`]: crate::TerminalScreen::draw_bitmap
+/// [`Write`]: std::io::Write
+pub struct TerminalScreen<const R: usize, const C: usize, W: Write = Stdout> {
+    writer: W,
+    glyphs: Glyphs,
+    colors: TerminalColors,
+    on_color: Color,
+    off_color: Color,
+    bitmap: Box<Bitmap<C, R>>,
+    // The patterns of the character cells as last written, or `None` if the screen must be written in full
+    shown: Option<Vec<Vec<u8>>>,
+}
+
+impl<const R: usize, const C: usize> TerminalScreen<R, C> {
+    /// Creates a terminal screen which writes to the standard output, in the colors of `theme`. Nothing
+    /// is written until the first bitmap is drawn.
+    ///
+    /// # Arguments
+    ///
+    /// * `theme` - The [`LcdTheme`] giving the colors of the dots
+    /// * `glyphs` - The [`Glyphs`] the dots are drawn with
+    /// * `colors` - The [`TerminalColors`] the terminal supports
+    ///
+    pub fn new(theme: &LcdTheme, glyphs: Glyphs, colors: TerminalColors) -> Self {
+        Self::with_writer(io::stdout(), theme, glyphs, colors)
+    }
+}
+
+impl<const R: usize, const C: usize, W: Write> TerminalScreen<R, C, W> {
+    /// Creates a terminal screen which writes to `writer`, in the colors of `theme`. See
+    /// [`TerminalScreen::new`].
+    pub fn with_writer(
+        writer: W,
+        theme: &LcdTheme,
+        glyphs: Glyphs,
+        colors: TerminalColors,
+    ) -> Self {
+        Self {
+            writer,
+            glyphs,
+            colors,
+            on_color: theme.on_color,
+            off_color: theme.off_color,
+            bitmap: Box::new([[false; C]; R]),
+            shown: None,
+        }
+    }
+
+    /// Returns the writer the screen is drawn to.
+    pub fn writer(&self) -> &W {
+        &self.writer
+    }
+
+    /// Returns the number of rows and columns of character cells the screen takes up in the terminal.
+    pub fn size_in_cells(&self) -> (usize, usize) {
+        let (cell_rows, cell_cols) = self.glyphs.cell_size();
+        (R.div_ceil(cell_rows), C.div_ceil(cell_cols))
+    }
+
+    /// Returns a copy of the dots of the screen, with `true` for a dot which is 'on'.
+    pub fn bitmap(&self) -> Box<Bitmap<C, R>> {
+        self.bitmap.clone()
+    }
+
+    /// Returns a [`Snapshot`] of the dots of the screen, for example to compare against a golden file in a
+    /// test.
+    pub fn snapshot(&self) -> Snapshot<R, C> {
+        Snapshot::new(&self.bitmap)
+    }
+
+    /// Clears the terminal and redraws every dot of the screen in its current state, for example after
+    /// the terminal has been resized or written over.
+    ///
+    /// # Errors
+    ///
+    /// - [`LcdError::Terminal`] when there is an error writing to the terminal
+    ///
+    pub fn repaint(&mut self) -> Result<(), LcdError> {
+        self.shown = None;
+        self.write_changes()
+    }
+
+    /// Draws a bitmap to the screen, writing only the character cells whose dots have changed. See
+    /// [`LcdScreen::draw_bitmap`].
+    ///
+    /// # Errors
+    ///
+    /// - [`LcdError::Terminal`] when there is an error writing to the terminal
+    ///
+    /// [`LcdScreen::draw_bitmap`]: crate::LcdScreen::draw_bitmap
+    ///
+    pub fn draw_bitmap<'a, BM: Into<&'a Bitmap<C, R>>>(&mut self, bm: BM) -> Result<(), LcdError> {
+        *self.bitmap = *bm.into();
+        self.write_changes()
+    }
+
+    // Returns the patterns of the character cells showing the current bitmap.
+    fn cells(&self) -> Vec<Vec<u8>> {
+        let (cell_rows, cell_cols) = self.size_in_cells();
+        let (rows_per_cell, cols_per_cell) = self.glyphs.cell_size();
+        let mut cells = vec![vec![0; cell_cols]; cell_rows];
+        for (row, row_bm) in self.bitmap.iter().enumerate() {
+            for (col, _) in row_bm.iter().enumerate().filter(|(_, on)| **on) {
+                cells[row / rows_per_cell][col / cols_per_cell] |=
+                    self.glyphs.bit(row % rows_per_cell, col % cols_per_cell);
+            }
+        }
+        cells
+    }
+
+    // Writes the character cells which differ from those last written, moving the cursor only where
+    // the changed cells are not next to each other.
+    fn write_changes(&mut self) -> Result<(), LcdError> {
+        let cells = self.cells();
+        let mut out = Vec::new();
+        if self.shown.is_none() {
+            // Hide the cursor and clear the terminal
+            out.extend_from_slice(b"\x1b[?25l\x1b[2J");
+        }
+        let mut cursor = None;
+        for (cell_row, row_cells) in cells.iter().enumerate() {
+            for (cell_col, &pattern) in row_cells.iter().enumerate() {
+                if let Some(shown) = &self.shown {
+                    if shown[cell_row][cell_col] == pattern {
+                        continue;
+                    }
+                }
+                if cursor.is_none() {
+                    self.colors.write(&mut out, 38, self.on_color);
+                    self.colors.write(&mut out, 48, self.off_color);
+                }
+                if cursor != Some((cell_row, cell_col)) {
+                    // Note: writing to a `Vec` cannot fail
+                    let _ = write!(out, "\x1b[{};{}H", cell_row + 1, cell_col + 1);
+                }
+                let mut glyph = [0; 4];
+                out.extend_from_slice(
+                    self.glyphs
+                        .glyph(pattern)
+                        .encode_utf8(&mut glyph)
+                        .as_bytes(),
+                );
+                cursor = Some((cell_row, cell_col + 1));
+            }
+        }
+        if cursor.is_some() {
+            out.extend_from_slice(b"\x1b[0m");
+        }
+        self.shown = Some(cells);
+        if !out.is_empty() {
+            self.writer
+                .write_all(&out)
+                .and_then(|_| self.writer.flush())
+                .map_err(LcdError::Terminal)?;
+        }
+        Ok(())
+    }
+}
+
+impl<const R: usize, const C: usize, W: Write> DotMatrix<R, C> for TerminalScreen<R, C, W> {
+    fn draw_bitmap(&mut self, bm: &Bitmap<C, R>) -> Result<(), LcdError> {
+        TerminalScreen::draw_bitmap(self, bm)
+    }
+}
+
+impl<const R: usize, const C: usize, W: Write> Drop for TerminalScreen<R, C, W> {
+    fn drop(&mut self) {
+        if self.shown.is_some() {
+            // Note: there is nowhere to report an error while dropping, and the terminal is left usable
+            // either way
+            let (cell_rows, _) = self.size_in_cells();
+            let _ = write!(self.writer, "\x1b[0m\x1b[{};1H\x1b[?25h", cell_rows + 1);
+            let _ = self.writer.flush();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn screen<const R: usize, const C: usize>(glyphs: Glyphs) -> TerminalScreen<R, C, Vec<u8>> {
+        TerminalScreen::with_writer(Vec::new(), &LcdTheme::GREEN, glyphs, TerminalColors::Plain)
+    }
+
+    #[test]
+    fn test_glyphs() {
+        let mut half_blocks = screen::<3, 2>(Glyphs::HalfBlocks);
+        assert_eq!(half_blocks.size_in_cells(), (2, 2));
+        half_blocks
+            .draw_bitmap(&[[true, false], [true, true], [false, true]])
+            .unwrap();
+        assert_eq!(half_blocks.cells(), vec![vec![3, 2], vec![0, 1]]);
+        assert_eq!(
+            String::from_utf8_lossy(half_blocks.writer()),
+            "\x1b[?25l\x1b[2J\x1b[1;1H█▄\x1b[2;1H ▀\x1b[0m"
+        );
+
+        let mut braille = screen::<4, 2>(Glyphs::Braille);
+        assert_eq!(braille.size_in_cells(), (1, 1));
+        braille.draw_bitmap(&[[true, false]; 4]).unwrap();
+        assert!(String::from_utf8_lossy(braille.writer()).ends_with("⡇\x1b[0m"));
+        braille
+            .draw_bitmap(&[[false, false], [false, true], [false, false], [false, true]])
+            .unwrap();
+        assert!(String::from_utf8_lossy(braille.writer()).ends_with("⢐\x1b[0m"));
+    }
+
+    #[test]
+    fn test_minimal_updates() {
+        let mut screen = screen::<2, 4>(Glyphs::HalfBlocks);
+        screen.draw_bitmap(&[[false; 4]; 2]).unwrap();
+        screen.writer.clear();
+
+        // An unchanged bitmap writes nothing
+        screen.draw_bitmap(&[[false; 4]; 2]).unwrap();
+        assert!(screen.writer().is_empty());
+
+        // Neighbouring changed cells share one cursor movement
+        screen
+            .draw_bitmap(&[[false, true, true, false], [false; 4]])
+            .unwrap();
+        assert_eq!(
+            String::from_utf8_lossy(screen.writer()),
+            "\x1b[1;2H▀▀\x1b[0m"
+        );
+        screen.writer.clear();
+        screen
+            .draw_bitmap(&[[true, true, true, false], [false, false, false, true]])
+            .unwrap();
+        assert_eq!(
+            String::from_utf8_lossy(screen.writer()),
+            "\x1b[1;1H▀\x1b[1;4H▄\x1b[0m"
+        );
+    }
+
+    #[test]
+    fn test_colors() {
+        assert_eq!(ansi256(Color::RGB(0, 0, 0)), 16);
+        assert_eq!(ansi256(Color::RGB(255, 255, 255)), 231);
+        assert_eq!(ansi256(Color::RGB(255, 0, 0)), 196);
+        assert_eq!(ansi256(Color::RGB(128, 128, 128)), 244);
+
+        let mut out = Vec::new();
+        TerminalColors::TrueColor.write(&mut out, 38, Color::RGB(1, 2, 3));
+        TerminalColors::Ansi256.write(&mut out, 48, Color::RGB(0, 0, 0));
+        assert_eq!(out, b"\x1b[38;2;1;2;3m\x1b[48;5;16m");
+    }
+}
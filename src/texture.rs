@@ -0,0 +1,172 @@
+// * Simulate LCD: A Simple LCD Screen Simulator *
+
+// Copyright 2023 Simon Varey - github.com/simonvarey
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// Imports
+
+use std::mem;
+
+use sdl2::{
+    pixels::{Color, PixelFormatEnum},
+    rect::Rect,
+    render::{Canvas, Texture, TextureCreator},
+    sys::{SDL_ScaleMode, SDL_SetTextureScaleMode},
+    video::{Window, WindowContext},
+};
+
+use crate::LcdError;
+
+// Constants
+
+// The number of bytes of each texel.
+const TEXEL_BYTES: usize = 4;
+
+// Render Path
+
+/// How the dots of an [`LcdScreen`] are drawn into its window.
+///
+/// # Examples
+///
+/// ```
+/// # use simulate_lcd::{LcdScreen, RenderPath};
+/// # let sdl_context = sdl2::init().unwrap();
+/// let mut screen = LcdScreen::<240, 320>::builder()
+///     .dot_size(2, 2)
+///     .render_path(RenderPath::Texture)
+///     .build(&sdl_context)
+///     .unwrap();
+/// # std::thread::sleep(std::time::Duration::from_secs(1));
+/// ```
+///
+/// [`LcdScreen`]: crate::LcdScreen
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RenderPath {
+    /// The changed dots of each frame are filled as rects, with runs of adjacent dots of the same color
+    /// merged. Every [`DotStyle`] can be drawn this way. This is the default path.
+    ///
+    /// [`DotStyle`]: crate::DotStyle
+    #[default]
+    Rects,
+    /// The dots are uploaded into a streaming texture with one texel per dot, which is scaled into the
+    /// window with nearest-neighbour filtering. Each frame costs one upload and one copy however many dots
+    /// change, which is much faster for large screens such as 320x240 panels.
+    ///
+    /// Only square dots with no gap between them and no visible grid can be drawn this way. Screens with
+    /// any other [`DotStyle`] are drawn with rects instead.
+    ///
+    /// [`DotStyle`]: crate::DotStyle
+    Texture,
+}
+
+// Streaming Texture
+
+// A streaming texture kept together with the texture creator it was created from, so that it can be
+// reused for every frame drawn to a canvas.
+struct StreamingTexture {
+    // Note: the texture is declared before its creator, so that it is destroyed first
+    texture: Texture<'static>,
+    _creator: TextureCreator<WindowContext>,
+}
+
+impl StreamingTexture {
+    // Creates a streaming texture of `width` by `height` texels for `canvas`, scaled with
+    // nearest-neighbour filtering so that every dot keeps sharp edges.
+    fn new(canvas: &Canvas<Window>, width: usize, height: usize) -> Result<Self, LcdError> {
+        let creator = canvas.texture_creator();
+        // Note: the buffer is as large as the physical dots of a screen whose window size has been
+        //   checked, so these are true casts
+        let texture = creator
+            .create_texture_streaming(PixelFormatEnum::RGBA32, width as u32, height as u32)
+            .map_err(|err| LcdError::Texture(err.to_string()))?;
+        // Note: the texture only borrows its creator to make sure the renderer outlives it. The creator
+        //   holds the renderer alive and is dropped after the texture, so extending the lifetime of the
+        //   texture to that of this struct is sound
+        let texture = unsafe { mem::transmute::<Texture<'_>, Texture<'static>>(texture) };
+        // Note: setting the scale mode of the texture itself leaves the scale quality hint of the
+        //   application untouched
+        if unsafe { SDL_SetTextureScaleMode(texture.raw(), SDL_ScaleMode::SDL_ScaleModeNearest) }
+            != 0
+        {
+            Err(LcdError::Texture(sdl2::get_error()))?
+        }
+        Ok(Self {
+            texture,
+            _creator: creator,
+        })
+    }
+}
+
+// Texel Buffer
+
+// The colors of the dots of a screen, one texel per physical dot, which are uploaded to a streaming
+// texture to draw them. The texture is created on the first draw and reused for every later draw.
+pub(crate) struct TexelBuffer {
+    width: usize,
+    height: usize,
+    pixels: Vec<u8>,
+    texture: Option<StreamingTexture>,
+}
+
+impl TexelBuffer {
+    // Creates a buffer for `width` columns and `height` rows of physical dots, all transparent.
+    pub(crate) fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![0; width * height * TEXEL_BYTES],
+            texture: None,
+        }
+    }
+
+    // Sets the color of the texel of the physical dot in column `x` of row `y`.
+    pub(crate) fn set(&mut self, x: usize, y: usize, color: Color) {
+        let start = (y * self.width + x) * TEXEL_BYTES;
+        self.pixels[start..start + TEXEL_BYTES]
+            .copy_from_slice(&[color.r, color.g, color.b, color.a]);
+    }
+
+    // Uploads the buffer into its streaming texture, and copies it scaled onto `area` of the canvas.
+    pub(crate) fn draw(&mut self, canvas: &mut Canvas<Window>, area: Rect) -> Result<(), LcdError> {
+        let texture = match &mut self.texture {
+            Some(streaming) => &mut streaming.texture,
+            None => {
+                let streaming = StreamingTexture::new(canvas, self.width, self.height)?;
+                &mut self.texture.insert(streaming).texture
+            }
+        };
+        texture
+            .update(None, &self.pixels, self.width * TEXEL_BYTES)
+            .map_err(|err| LcdError::Texture(err.to_string()))?;
+        canvas.copy(texture, None, area).map_err(LcdError::Texture)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_texel_buffer() {
+        let mut texels = TexelBuffer::new(3, 2);
+        assert_eq!(texels.pixels.len(), 3 * 2 * 4);
+        assert!(texels.pixels.iter().all(|byte| *byte == 0));
+
+        texels.set(2, 1, Color::RGB(10, 20, 30));
+        texels.set(0, 0, Color::RGBA(1, 2, 3, 4));
+        assert_eq!(texels.pixels[..4], [1, 2, 3, 4]);
+        assert_eq!(texels.pixels[20..], [10, 20, 30, 255]);
+        assert!(texels.pixels[4..20].iter().all(|byte| *byte == 0));
+    }
+}
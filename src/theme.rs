@@ -0,0 +1,169 @@
+// * Simulate LCD: A Simple LCD Screen Simulator *
+
+// Copyright 2023 Simon Varey - github.com/simonvarey
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// Imports
+
+use sdl2::pixels::Color;
+
+use crate::{LCD_DARK_GREEN, LCD_LIGHT_GREEN};
+
+// * LCD Theme *
+
+/// A set of colors describing a type of display panel.
+///
+/// A theme can be used to create an [`LcdScreen`] with [`LcdScreenBuilder::theme`]. The crate provides
+/// presets for several common kinds of panel as associated constants, such as
+/// [`LcdTheme::BLUE_WHITE_STN`] and [`LcdTheme::RED_LED_MATRIX`].
+///
+/// [`LcdScreen`]: crate::LcdScreen
+/// [`LcdScreenBuilder::theme`]: crate::LcdScreenBuilder::theme
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LcdTheme {
+    /// The color of a dot when it is 'on'
+    pub on_color: Color,
+    /// The color of a dot when it is 'off'
+    pub off_color: Color,
+    /// The color of the light emitted by the backlight. While the backlight is lit, the displayed colors
+    /// of the screen are multiplied by this color, so white leaves them unchanged. Emissive panels, such
+    /// as OLEDs and LED matrices, use white.
+    pub backlight_color: Color,
+    /// The color of the bezel surrounding the panel
+    pub bezel_color: Color,
+}
+
+impl Default for LcdTheme {
+    fn default() -> Self {
+        Self::GREEN
+    }
+}
+
+impl LcdTheme {
+    /// A green backlight STN LCD, using [`LCD_DARK_GREEN`] and [`LCD_LIGHT_GREEN`]. This is the default
+    /// theme.
+    ///
+    /// [`LCD_DARK_GREEN`]: crate::LCD_DARK_GREEN
+    /// [`LCD_LIGHT_GREEN`]: crate::LCD_LIGHT_GREEN
+    pub const GREEN: LcdTheme = LcdTheme {
+        on_color: LCD_DARK_GREEN,
+        off_color: LCD_LIGHT_GREEN,
+        backlight_color: Color::WHITE,
+        bezel_color: Color::RGB(40, 40, 40),
+    };
+
+    /// A negative-mode blue STN LCD, with white dots on a blue background.
+    pub const BLUE_WHITE_STN: LcdTheme = LcdTheme {
+        on_color: Color::RGB(232, 240, 255),
+        off_color: Color::RGB(28, 64, 196),
+        backlight_color: Color::WHITE,
+        bezel_color: Color::RGB(30, 30, 36),
+    };
+
+    /// A positive-mode STN LCD, with black dots on a yellow-green background.
+    pub const BLACK_ON_YELLOW_GREEN: LcdTheme = LcdTheme {
+        on_color: Color::RGB(36, 40, 28),
+        off_color: Color::RGB(196, 214, 72),
+        backlight_color: Color::RGB(250, 255, 225),
+        bezel_color: Color::RGB(32, 32, 32),
+    };
+
+    /// A white monochrome OLED.
+    pub const WHITE_OLED: LcdTheme = LcdTheme {
+        on_color: Color::RGB(240, 244, 255),
+        off_color: Color::RGB(8, 8, 10),
+        backlight_color: Color::WHITE,
+        bezel_color: Color::RGB(16, 16, 16),
+    };
+
+    /// A yellow monochrome OLED.
+    pub const YELLOW_OLED: LcdTheme = LcdTheme {
+        on_color: Color::RGB(255, 214, 0),
+        off_color: Color::RGB(10, 10, 8),
+        backlight_color: Color::WHITE,
+        bezel_color: Color::RGB(16, 16, 16),
+    };
+
+    /// An amber gas plasma display.
+    pub const AMBER_PLASMA: LcdTheme = LcdTheme {
+        on_color: Color::RGB(255, 136, 0),
+        off_color: Color::RGB(42, 16, 0),
+        backlight_color: Color::WHITE,
+        bezel_color: Color::RGB(20, 20, 20),
+    };
+
+    /// A red LED dot-matrix display. This theme looks best with [`DotShape::Circle`] dots.
+    ///
+    /// [`DotShape::Circle`]: crate::DotShape::Circle
+    pub const RED_LED_MATRIX: LcdTheme = LcdTheme {
+        on_color: Color::RGB(255, 32, 20),
+        off_color: Color::RGB(58, 10, 10),
+        backlight_color: Color::WHITE,
+        bezel_color: Color::RGB(10, 10, 10),
+    };
+
+    /// A grey electronic paper display.
+    pub const E_PAPER_GREY: LcdTheme = LcdTheme {
+        on_color: Color::RGB(48, 48, 50),
+        off_color: Color::RGB(204, 204, 198),
+        backlight_color: Color::WHITE,
+        bezel_color: Color::RGB(232, 232, 228),
+    };
+
+    /// The four shades of the original Game Boy (DMG) display, from darkest to lightest.
+    pub const DMG_SHADES: [Color; 4] = [
+        Color::RGB(15, 56, 15),
+        Color::RGB(48, 98, 48),
+        Color::RGB(139, 172, 15),
+        Color::RGB(155, 188, 15),
+    ];
+
+    /// The original Game Boy (DMG) display, using the darkest and lightest of the
+    /// [`DMG_SHADES`](LcdTheme::DMG_SHADES).
+    pub const GAME_BOY_DMG: LcdTheme = LcdTheme {
+        on_color: Self::DMG_SHADES[0],
+        off_color: Self::DMG_SHADES[3],
+        backlight_color: Color::WHITE,
+        bezel_color: Color::RGB(92, 93, 108),
+    };
+
+    /// All of the preset themes, with their names.
+    pub const PRESETS: [(&'static str, LcdTheme); 9] = [
+        ("green", Self::GREEN),
+        ("blue-white-stn", Self::BLUE_WHITE_STN),
+        ("black-on-yellow-green", Self::BLACK_ON_YELLOW_GREEN),
+        ("white-oled", Self::WHITE_OLED),
+        ("yellow-oled", Self::YELLOW_OLED),
+        ("amber-plasma", Self::AMBER_PLASMA),
+        ("red-led-matrix", Self::RED_LED_MATRIX),
+        ("e-paper-grey", Self::E_PAPER_GREY),
+        ("game-boy-dmg", Self::GAME_BOY_DMG),
+    ];
+
+    /// Looks up a preset theme by its name in [`PRESETS`](LcdTheme::PRESETS).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simulate_lcd::LcdTheme;
+    /// assert_eq!(LcdTheme::preset("white-oled"), Some(LcdTheme::WHITE_OLED));
+    /// assert_eq!(LcdTheme::preset("vfd"), None);
+    /// ```
+    pub fn preset(name: &str) -> Option<LcdTheme> {
+        Self::PRESETS
+            .iter()
+            .find(|(preset, _)| *preset == name)
+            .map(|(_, theme)| *theme)
+    }
+}
@@ -0,0 +1,451 @@
+// * Simulate LCD: A Simple LCD Screen Simulator *
+
+// Copyright 2023 Simon Varey - github.com/simonvarey
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// Imports
+
+use std::time::{Duration, Instant};
+
+use crate::{Bitmap, LcdError, LcdScreen};
+
+// Scrolling
+
+/// The direction in which the viewport of a [`VirtualCanvas`] moves over the canvas. Moving the viewport
+/// right makes the contents of the screen appear to move left.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScrollDirection {
+    /// Towards the top row of the canvas
+    Up,
+    /// Towards the bottom row of the canvas
+    Down,
+    /// Towards the left column of the canvas
+    Left,
+    /// Towards the right column of the canvas
+    Right,
+}
+
+impl ScrollDirection {
+    // The change in row and column of one step in this direction.
+    fn step(self) -> (isize, isize) {
+        match self {
+            ScrollDirection::Up => (-1, 0),
+            ScrollDirection::Down => (1, 0),
+            ScrollDirection::Left => (0, -1),
+            ScrollDirection::Right => (0, 1),
+        }
+    }
+}
+
+// How the viewport is moving over time.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Motion {
+    // Towards a position, stopping there
+    To { row: usize, col: usize },
+    // In a direction, wrapping around the canvas, until stopped
+    Marquee(ScrollDirection),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Scroll {
+    motion: Motion,
+    dots_per_second: f32,
+    // The part of a dot moved but not yet shown
+    progress: f32,
+}
+
+// * Virtual Canvas *
+
+/// A canvas of dots larger than an [`LcdScreen`] of `R` rows and `C` columns, of which the screen shows
+/// the `R` by `C` window under a viewport, such as a menu taller than the screen or a line of text wider
+/// than it.
+///
+/// The viewport can be moved to any position at once with [`scroll_to`] and [`scroll_by`], scrolled
+/// smoothly one dot at a time with [`scroll_smoothly_to`], or moved continuously in a [`marquee`]. The
+/// last two move the viewport as time passes, through [`update`] or [`advance`]. The visible window is
+/// drawn to a screen with [`LcdScreen::draw_canvas`], which only redraws the dots that have changed
+/// since the screen was last drawn.
+///
+/// By default the viewport stops at the edges of the canvas. Either axis can be set to wrap around
+/// instead, in which case the canvas repeats in that direction, and the viewport can move past its end
+/// and back to its start, as in a marquee.
+///
+/// # Examples
+///
+/// ```
+/// # use simulate_lcd::{ScrollDirection, VirtualCanvas};
+/// # use std::time::Duration;
+/// let mut canvas = VirtualCanvas::<2, 3>::new(2, 4);
+/// canvas.set(0, 3, true);
+/// assert_eq!(*canvas.view(), [[false; 3]; 2]);
+///
+/// // Scroll one dot to the right, so the last column is visible
+/// canvas.scroll_by(0, 1);
+/// assert_eq!(*canvas.view(), [[false, false, true], [false; 3]]);
+///
+/// // Move the viewport right at 10 dots per second, so the contents move left and wrap around
+/// canvas.marquee(ScrollDirection::Right, 10.0);
+/// assert!(canvas.advance(Duration::from_millis(100)));
+/// assert_eq!(canvas.position(), (0, 2));
+/// assert_eq!(*canvas.view(), [[false, true, false], [false; 3]]);
+/// ```
+///
+/// [`LcdScreen`]: crate::LcdScreen
+/// [`scroll_to`]: crate::VirtualCanvas::scroll_to
+/// [`scroll_by`]: crate::VirtualCanvas::scroll_by
+/// [`scroll_smoothly_to`]: crate::VirtualCanvas::scroll_smoothly_to
+/// [`marquee`]: crate::VirtualCanvas::marquee
+/// [`update`]: crate::VirtualCanvas::update
+/// [`advance`]: crate::VirtualCanvas::advance
+/// [`LcdScreen::draw_canvas`]: crate::LcdScreen::draw_canvas
+#[derive(Debug, Clone)]
+pub struct VirtualCanvas<const R: usize, const C: usize> {
+    rows: usize,
+    cols: usize,
+    dots: Vec<bool>,
+    row: usize,
+    col: usize,
+    wrap_rows: bool,
+    wrap_cols: bool,
+    scroll: Option<Scroll>,
+    last_update: Instant,
+}
+
+impl<const R: usize, const C: usize> VirtualCanvas<R, C> {
+    /// Creates a canvas of `rows` rows and `cols` columns of dots, all 'off', with the viewport at its top
+    /// left. A canvas smaller than the screen is shown with 'off' dots past its edges, unless it wraps
+    /// around.
+    pub fn new(rows: usize, cols: usize) -> Self {
+        Self {
+            rows,
+            cols,
+            dots: vec![false; rows * cols],
+            row: 0,
+            col: 0,
+            wrap_rows: false,
+            wrap_cols: false,
+            scroll: None,
+            last_update: Instant::now(),
+        }
+    }
+
+    /// Returns the number of rows of dots of the canvas.
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Returns the number of columns of dots of the canvas.
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// Returns whether the dot at `row` and `col` of the canvas is 'on', or `None` if there is no such dot.
+    pub fn get(&self, row: usize, col: usize) -> Option<bool> {
+        (row < self.rows && col < self.cols).then(|| self.dots[row * self.cols + col])
+    }
+
+    /// Sets the dot at `row` and `col` of the canvas to 'on' if `on` is `true`, or 'off' if not. Dots
+    /// outside the canvas are ignored.
+    pub fn set(&mut self, row: usize, col: usize, on: bool) {
+        if row < self.rows && col < self.cols {
+            self.dots[row * self.cols + col] = on;
+        }
+    }
+
+    /// Sets every dot of the canvas to 'off'.
+    pub fn clear(&mut self) {
+        self.dots.fill(false);
+    }
+
+    /// Copies a bitmap onto the canvas with its top left dot at `row` and `col`. The parts of the bitmap
+    /// outside the canvas are ignored.
+    pub fn draw_bitmap_at<const BR: usize, const BC: usize>(
+        &mut self,
+        row: usize,
+        col: usize,
+        bm: &Bitmap<BC, BR>,
+    ) {
+        for (bm_row, row_bm) in bm.iter().enumerate() {
+            for (bm_col, &on) in row_bm.iter().enumerate() {
+                self.set(row + bm_row, col + bm_col, on);
+            }
+        }
+    }
+
+    /// Returns the row and column of the canvas at the top left of the viewport.
+    pub fn position(&self) -> (usize, usize) {
+        (self.row, self.col)
+    }
+
+    /// Returns whether the canvas wraps around vertically and horizontally.
+    pub fn wrap(&self) -> (bool, bool) {
+        (self.wrap_rows, self.wrap_cols)
+    }
+
+    /// Sets whether the canvas wraps around vertically (`rows`) and horizontally (`cols`). Without
+    /// wrapping, the viewport is kept within the canvas along that axis, where the canvas is large enough.
+    pub fn set_wrap(&mut self, rows: bool, cols: bool) {
+        self.wrap_rows = rows;
+        self.wrap_cols = cols;
+        (self.row, self.col) = (
+            self.row_in_range(self.row as isize),
+            self.col_in_range(self.col as isize),
+        );
+    }
+
+    // Returns the nearest position of the viewport along an axis to `position`, wrapped around the axis
+    // or kept within it.
+    fn in_range(position: isize, size: usize, visible: usize, wrap: bool) -> usize {
+        // Note: canvas sizes are the lengths of vecs, so they fit in an isize
+        if wrap && size > 0 {
+            position.rem_euclid(size as isize) as usize
+        } else {
+            position.clamp(0, size.saturating_sub(visible) as isize) as usize
+        }
+    }
+
+    fn row_in_range(&self, row: isize) -> usize {
+        Self::in_range(row, self.rows, R, self.wrap_rows)
+    }
+
+    fn col_in_range(&self, col: isize) -> usize {
+        Self::in_range(col, self.cols, C, self.wrap_cols)
+    }
+
+    /// Moves the viewport at once so that its top left is at `row` and `col` of the canvas, stopping any
+    /// smooth scroll or marquee.
+    pub fn scroll_to(&mut self, row: usize, col: usize) {
+        self.scroll = None;
+        // Note: positions beyond an isize are beyond any canvas, so they are kept at its edge
+        let row = isize::try_from(row).unwrap_or(isize::MAX);
+        let col = isize::try_from(col).unwrap_or(isize::MAX);
+        (self.row, self.col) = (self.row_in_range(row), self.col_in_range(col));
+    }
+
+    /// Moves the viewport at once by `rows` rows down and `cols` columns right, or up and left for
+    /// negative values, stopping any smooth scroll or marquee.
+    pub fn scroll_by(&mut self, rows: isize, cols: isize) {
+        self.scroll = None;
+        self.step(rows, cols);
+    }
+
+    fn step(&mut self, rows: isize, cols: isize) {
+        let row = self.row_in_range((self.row as isize).saturating_add(rows));
+        let col = self.col_in_range((self.col as isize).saturating_add(cols));
+        (self.row, self.col) = (row, col);
+    }
+
+    /// Starts moving the viewport towards `row` and `col` of the canvas at `dots_per_second`, one dot at
+    /// a time along each axis, as time passes.
+    pub fn scroll_smoothly_to(&mut self, row: usize, col: usize, dots_per_second: f32) {
+        let row = self.row_in_range(isize::try_from(row).unwrap_or(isize::MAX));
+        let col = self.col_in_range(isize::try_from(col).unwrap_or(isize::MAX));
+        self.start(Motion::To { row, col }, dots_per_second);
+    }
+
+    /// Starts moving the viewport continuously in `direction` at `dots_per_second`, as time passes, until
+    /// it is stopped. The canvas is set to wrap around along the axis of `direction`, so its contents
+    /// leave one edge of the screen and come back in at the other.
+    pub fn marquee(&mut self, direction: ScrollDirection, dots_per_second: f32) {
+        match direction {
+            ScrollDirection::Up | ScrollDirection::Down => self.set_wrap(true, self.wrap_cols),
+            ScrollDirection::Left | ScrollDirection::Right => self.set_wrap(self.wrap_rows, true),
+        }
+        self.start(Motion::Marquee(direction), dots_per_second);
+    }
+
+    fn start(&mut self, motion: Motion, dots_per_second: f32) {
+        self.scroll = Some(Scroll {
+            motion,
+            dots_per_second: dots_per_second.max(0.0),
+            progress: 0.0,
+        });
+        self.last_update = Instant::now();
+    }
+
+    /// Stops any smooth scroll or marquee, leaving the viewport where it is.
+    pub fn stop(&mut self) {
+        self.scroll = None;
+    }
+
+    /// Returns whether the viewport is moving in a smooth scroll or marquee.
+    pub fn is_scrolling(&self) -> bool {
+        self.scroll.is_some()
+    }
+
+    /// Moves the viewport on by the time since it was last updated, if it is moving, and returns whether
+    /// its position changed. This should be called frequently, such as on every iteration of the main
+    /// loop.
+    pub fn update(&mut self) -> bool {
+        let now = Instant::now();
+        let elapsed = now.saturating_duration_since(self.last_update);
+        self.last_update = now;
+        self.advance(elapsed)
+    }
+
+    /// Moves the viewport on by `elapsed`, if it is moving, and returns whether its position changed.
+    /// This allows scrolling to follow a simulated clock instead of the real time used by [`update`].
+    ///
+    /// [`update`]: crate::VirtualCanvas::update
+    pub fn advance(&mut self, elapsed: Duration) -> bool {
+        let Some(scroll) = &mut self.scroll else {
+            return false;
+        };
+        scroll.progress += scroll.dots_per_second * elapsed.as_secs_f32();
+        let steps = scroll.progress.floor();
+        scroll.progress -= steps;
+        // Note: the steps are whole and not negative, and are capped at a number no canvas needs
+        let steps = steps.min(1e9) as usize;
+        if steps == 0 {
+            return false;
+        }
+
+        let motion = scroll.motion;
+        let before = self.position();
+        match motion {
+            Motion::To { row, col } => {
+                let towards = |from: usize, to: usize| {
+                    let moved = from.abs_diff(to).min(steps);
+                    if to > from {
+                        from + moved
+                    } else {
+                        from - moved
+                    }
+                };
+                (self.row, self.col) = (towards(self.row, row), towards(self.col, col));
+                if (self.row, self.col) == (row, col) {
+                    self.scroll = None;
+                }
+            }
+            Motion::Marquee(direction) => {
+                let (rows, cols) = direction.step();
+                // Note: wrapping makes any number of steps past the size of the canvas along the moving
+                //   axis equivalent
+                let size = match direction {
+                    ScrollDirection::Up | ScrollDirection::Down => self.rows,
+                    ScrollDirection::Left | ScrollDirection::Right => self.cols,
+                };
+                let steps = (steps % size.max(1)) as isize;
+                self.step(rows * steps, cols * steps);
+            }
+        }
+        self.position() != before
+    }
+
+    /// Returns the window of the canvas under the viewport, as a bitmap which can be drawn to a screen.
+    pub fn view(&self) -> Box<Bitmap<C, R>> {
+        // Note: the vec must be R in length, so this cannot fail
+        let mut bm: Box<Bitmap<C, R>> = vec![[false; C]; R].into_boxed_slice().try_into().unwrap();
+        let wrapped = |position: usize, size: usize, wrap: bool| match (wrap, size) {
+            (true, 1..) => Some(position % size),
+            _ => (position < size).then_some(position),
+        };
+        for (view_row, row_bm) in bm.iter_mut().enumerate() {
+            let Some(row) = wrapped(self.row + view_row, self.rows, self.wrap_rows) else {
+                continue;
+            };
+            for (view_col, dot) in row_bm.iter_mut().enumerate() {
+                if let Some(col) = wrapped(self.col + view_col, self.cols, self.wrap_cols) {
+                    *dot = self.dots[row * self.cols + col];
+                }
+            }
+        }
+        bm
+    }
+}
+
+impl<const R: usize, const C: usize> LcdScreen<R, C> {
+    /// Draws the window of a [`VirtualCanvas`] under its viewport to the screen. Like [`draw_bitmap`],
+    /// only the dots which have changed since the screen was last drawn are redrawn, so scrolling by one
+    /// dot only redraws the dots at the edges of shapes.
+    ///
+    /// # Errors
+    ///
+    /// - [`LcdError::Fill`] when there is an error filling one of the dots with the relevant color
+    /// - [`LcdError::Texture`] when there is an error drawing the dots through a texture
+    ///
+    /// [`draw_bitmap`]: crate::LcdScreen::draw_bitmap
+    ///
+    pub fn draw_canvas(&mut self, canvas: &VirtualCanvas<R, C>) -> Result<(), LcdError> {
+        self.draw_bitmap(&*canvas.view())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn canvas() -> VirtualCanvas<2, 2> {
+        let mut canvas = VirtualCanvas::new(3, 4);
+        canvas.draw_bitmap_at(0, 0, &[[true, false, false, true]]);
+        canvas.set(2, 2, true);
+        canvas
+    }
+
+    #[test]
+    fn test_viewport() {
+        let mut canvas = canvas();
+        assert_eq!(canvas.get(2, 2), Some(true));
+        assert_eq!(canvas.get(3, 0), None);
+        assert_eq!(*canvas.view(), [[true, false], [false, false]]);
+
+        // Without wrapping, the viewport stays inside the canvas
+        canvas.scroll_to(5, 5);
+        assert_eq!(canvas.position(), (1, 2));
+        assert_eq!(*canvas.view(), [[false, false], [true, false]]);
+        canvas.scroll_by(-4, -1);
+        assert_eq!(canvas.position(), (0, 1));
+
+        // With wrapping, the canvas repeats
+        canvas.set_wrap(true, true);
+        canvas.scroll_by(-1, 2);
+        assert_eq!(canvas.position(), (2, 3));
+        assert_eq!(*canvas.view(), [[false, false], [true, true]]);
+
+        // A canvas smaller than the screen is padded with 'off' dots
+        let mut small = VirtualCanvas::<2, 2>::new(1, 1);
+        small.set(0, 0, true);
+        assert_eq!(*small.view(), [[true, false], [false, false]]);
+    }
+
+    #[test]
+    fn test_scrolling() {
+        let mut canvas = canvas();
+        canvas.scroll_smoothly_to(1, 2, 4.0);
+        assert!(canvas.is_scrolling());
+        assert!(!canvas.advance(Duration::from_millis(100)));
+        assert!(canvas.advance(Duration::from_millis(200)));
+        assert_eq!(canvas.position(), (1, 1));
+        assert!(canvas.advance(Duration::from_secs(5)));
+        assert_eq!(canvas.position(), (1, 2));
+        assert!(!canvas.is_scrolling());
+
+        canvas.marquee(ScrollDirection::Right, 1.0);
+        assert_eq!(canvas.wrap(), (false, true));
+        assert!(canvas.advance(Duration::from_secs(3)));
+        assert_eq!(canvas.position(), (1, 1));
+        canvas.stop();
+        assert!(!canvas.advance(Duration::from_secs(1)));
+
+        // Steps past the size of the moving axis wrap around it, whatever the size of the other axis
+        let mut canvas = self::canvas();
+        canvas.marquee(ScrollDirection::Down, 1.0);
+        assert!(canvas.advance(Duration::from_secs(4)));
+        assert_eq!(canvas.position(), (1, 0));
+        let mut wide = VirtualCanvas::<1, 1>::new(5, 2);
+        wide.marquee(ScrollDirection::Right, 1.0);
+        assert!(wide.advance(Duration::from_secs(5)));
+        assert_eq!(wide.position(), (0, 1));
+    }
+}
@@ -0,0 +1,425 @@
+// * Simulate LCD: A Simple LCD Screen Simulator *
+
+// Copyright 2023 Simon Varey - github.com/simonvarey
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// Imports
+
+use std::{
+    cell::{Ref, RefCell, RefMut},
+    rc::Rc,
+};
+
+use sdl2::{
+    pixels::Color,
+    rect::Rect,
+    render::Canvas,
+    sys::SDL_WindowFlags,
+    video::{Window, WindowBuilder},
+    IntegerOrSdlError, Sdl, VideoSubsystem,
+};
+
+use crate::LcdError;
+
+// Window Settings
+
+/// Where the window containing an [`LcdScreen`] is placed on the desktop.
+///
+/// [`LcdScreen`]: crate::LcdScreen
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WindowPosition {
+    /// The window is centered on the desktop. This is the default position.
+    #[default]
+    Centered,
+    /// The top-left corner of the window is placed at the given desktop coordinates in pixels.
+    At(i32, i32),
+}
+
+/// How the dots of an [`LcdScreen`] are scaled when its window is larger than the screen, such as
+/// when the window has been resized, is fullscreen or is on a high-DPI display.
+///
+/// [`LcdScreen`]: crate::LcdScreen
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Scaling {
+    /// The screen is scaled by the largest factor which fits in the window while preserving its aspect
+    /// ratio, and centered with bars on either side. This is the default scaling.
+    #[default]
+    Aspect,
+    /// As [`Scaling::Aspect`], except that the scale factor is rounded down to a whole number, so that
+    /// every dot is drawn with the same number of pixels.
+    Integer,
+}
+
+/// Whether the window containing an [`LcdScreen`] fills the display.
+///
+/// [`LcdScreen`]: crate::LcdScreen
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Fullscreen {
+    /// The window is an ordinary window. This is the default.
+    #[default]
+    Off,
+    /// The window is a borderless window covering the whole display at the desktop resolution.
+    Desktop,
+    /// The window takes exclusive control of the display, changing its resolution to match the window.
+    Exclusive,
+}
+
+/// The SDL renderer used to draw an [`LcdScreen`].
+///
+/// [`LcdScreen`]: crate::LcdScreen
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Backend {
+    /// SDL chooses the renderer, preferring a hardware accelerated one. This is the default backend.
+    #[default]
+    Auto,
+    /// A hardware accelerated renderer.
+    Accelerated,
+    /// The software renderer, which is available on every platform, including those without a GPU.
+    Software,
+}
+
+impl Backend {
+    // Builds a canvas for a window using this backend.
+    pub(crate) fn build_canvas(&self, window: Window) -> Result<Canvas<Window>, LcdError> {
+        let builder = window.into_canvas();
+        let builder = match self {
+            Backend::Auto => builder,
+            Backend::Accelerated => builder.accelerated(),
+            Backend::Software => builder.software(),
+        };
+        Ok(builder.build()?)
+    }
+}
+
+// * Window Options *
+
+/// A builder for the settings of the window containing an [`LcdScreen`].
+///
+/// Window options are passed to [`LcdScreenBuilder::window_options`]. [`LcdScreen::new`] uses the default
+/// options, which create an ordinary, visible, non-resizable window centered on the desktop.
+///
+/// Whatever the size of the window, the dots of the screen are drawn scaled according to the
+/// [`Scaling`] of the window. When a window is resized its contents may be lost, so the screen should
+/// be redrawn with [`LcdScreen::repaint`] on receiving a window event.
+///
+/// # Examples
+///
+/// ```
+/// # use simulate_lcd::{Scaling, WindowOptions};
+/// let options = WindowOptions::new()
+///     .position(100, 100)
+///     .resizable(Scaling::Integer)
+///     .always_on_top()
+///     .high_dpi();
+/// ```
+///
+/// [`LcdScreen`]: crate::LcdScreen
+/// [`LcdScreen::new`]: crate::LcdScreen::new
+/// [`LcdScreenBuilder::window_options`]: crate::LcdScreenBuilder::window_options
+/// [`LcdScreen::repaint`]: crate::LcdScreen::repaint
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct WindowOptions {
+    position: WindowPosition,
+    resizable: bool,
+    scaling: Scaling,
+    fullscreen: Fullscreen,
+    borderless: bool,
+    always_on_top: bool,
+    hidden: bool,
+    high_dpi: bool,
+}
+
+impl WindowOptions {
+    /// Creates the default window options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Places the top-left corner of the window at the given desktop coordinates in pixels.
+    pub fn position(mut self, x: i32, y: i32) -> Self {
+        self.position = WindowPosition::At(x, y);
+        self
+    }
+
+    /// Centers the window on the desktop.
+    pub fn centered(mut self) -> Self {
+        self.position = WindowPosition::Centered;
+        self
+    }
+
+    /// Allows the window to be resized by the user, scaling the screen with the given [`Scaling`].
+    pub fn resizable(mut self, scaling: Scaling) -> Self {
+        self.resizable = true;
+        self.scaling = scaling;
+        self
+    }
+
+    /// Sets how the screen is scaled when the window is larger than the screen, without making the
+    /// window resizable.
+    pub fn scaling(mut self, scaling: Scaling) -> Self {
+        self.scaling = scaling;
+        self
+    }
+
+    /// Sets whether the window fills the display.
+    pub fn fullscreen(mut self, fullscreen: Fullscreen) -> Self {
+        self.fullscreen = fullscreen;
+        self
+    }
+
+    /// Removes the title bar and border of the window.
+    pub fn borderless(mut self) -> Self {
+        self.borderless = true;
+        self
+    }
+
+    /// Keeps the window above all other windows.
+    pub fn always_on_top(mut self) -> Self {
+        self.always_on_top = true;
+        self
+    }
+
+    /// Creates the window hidden. The window can be shown with [`Window::show`] through
+    /// [`LcdScreen::window_mut`].
+    ///
+    /// [`Window::show`]: sdl2::video::Window::show
+    /// [`LcdScreen::window_mut`]: crate::LcdScreen::window_mut
+    pub fn hidden(mut self) -> Self {
+        self.hidden = true;
+        self
+    }
+
+    /// Creates the window in high-DPI mode where supported, so that the screen is drawn at the full
+    /// resolution of the display rather than being upscaled by the operating system.
+    pub fn high_dpi(mut self) -> Self {
+        self.high_dpi = true;
+        self
+    }
+
+    // Builds a window with these options.
+    pub(crate) fn build_window(
+        &self,
+        video_subsystem: &VideoSubsystem,
+        title: &str,
+        width: u32,
+        height: u32,
+    ) -> Result<Window, LcdError> {
+        let mut builder: WindowBuilder = video_subsystem.window(title, width, height);
+        match self.position {
+            WindowPosition::Centered => builder.position_centered(),
+            WindowPosition::At(x, y) => builder.position(x, y),
+        };
+        match self.fullscreen {
+            Fullscreen::Off => &mut builder,
+            Fullscreen::Desktop => builder.fullscreen_desktop(),
+            Fullscreen::Exclusive => builder.fullscreen(),
+        };
+        if self.resizable {
+            builder.resizable();
+        }
+        if self.borderless {
+            builder.borderless();
+        }
+        if self.hidden {
+            builder.hidden();
+        }
+        if self.high_dpi {
+            builder.allow_highdpi();
+        }
+        if self.always_on_top {
+            let flags = builder.window_flags();
+            builder.set_window_flags(flags | SDL_WindowFlags::SDL_WINDOW_ALWAYS_ON_TOP as u32);
+        }
+        Ok(builder.build()?)
+    }
+
+    // Sets up the scaling of a canvas showing a screen of the given logical size.
+    pub(crate) fn configure_canvas(
+        &self,
+        canvas: &mut Canvas<Window>,
+        width: u32,
+        height: u32,
+    ) -> Result<(), LcdError> {
+        canvas.set_logical_size(width, height)?;
+        canvas
+            .set_integer_scale(self.scaling == Scaling::Integer)
+            .map_err(|err| LcdError::CanvasBuild(IntegerOrSdlError::SdlError(err)))
+    }
+}
+
+// * LCD Window *
+
+/// A window which can contain several [`LcdScreens`].
+///
+/// Each screen created in the window with [`LcdScreenBuilder::build_in`] occupies its own area of the
+/// window, and can have a different size, colors and dot size from the other screens. Drawing to a screen
+/// in an `LcdWindow` does not present the window, so several screens can be updated and then shown
+/// together with a single call to [`present`].
+///
+/// # Examples
+///
+/// ```
+/// # use simulate_lcd::{LcdScreenBuilder, LcdTheme, LcdWindow};
+/// # let sdl_context = sdl2::init().unwrap();
+/// let window = LcdWindow::new(&sdl_context, "LCD Example: Two Screens", 660, 460).unwrap();
+///
+/// let mut main_screen = LcdScreenBuilder::<64, 128>::new()
+///     .dot_size(5, 5)
+///     .build_in(&window, 10, 10)
+///     .unwrap();
+/// let mut status_screen = LcdScreenBuilder::<8, 64>::new()
+///     .theme(&LcdTheme::BLUE_WHITE_STN)
+///     .dot_size(5, 5)
+///     .build_in(&window, 170, 350)
+///     .unwrap();
+///
+/// main_screen.draw_bitmap(&[[true; 128]; 64]).unwrap();
+/// status_screen.draw_bitmap(&[[true; 64]; 8]).unwrap();
+/// window.present();
+/// # std::thread::sleep(std::time::Duration::from_secs(1));
+/// ```
+///
+/// [`LcdScreens`]: crate::LcdScreen
+/// [`LcdScreenBuilder::build_in`]: crate::LcdScreenBuilder::build_in
+/// [`present`]: crate::LcdWindow::present
+pub struct LcdWindow {
+    pub(crate) canvas: Rc<RefCell<Canvas<Window>>>,
+    background: Color,
+}
+
+impl LcdWindow {
+    /// Creates an empty window for [`LcdScreens`], with the default [`WindowOptions`].
+    ///
+    /// # Arguments
+    ///
+    /// * `sdl_context` - An [`Sdl`] context object
+    /// * `title` - The title of the window
+    /// * `width` - The width of the window in pixels
+    /// * `height` - The height of the window in pixels
+    ///
+    /// # Errors
+    ///
+    /// - [`LcdError::Video`] when there is an error initializing the SDL video subsystem
+    /// - [`LcdError::WindowBuild`] when there is an error building the window
+    /// - [`LcdError::CanvasBuild`] when there is an error building the window canvas
+    ///
+    /// [`LcdScreens`]: crate::LcdScreen
+    /// [`Sdl`]: sdl2::Sdl
+    ///
+    pub fn new(sdl_context: &Sdl, title: &str, width: u32, height: u32) -> Result<Self, LcdError> {
+        Self::with_options(
+            sdl_context,
+            title,
+            width,
+            height,
+            WindowOptions::default(),
+            Backend::default(),
+        )
+    }
+
+    /// Creates an empty window for [`LcdScreens`], with the given [`WindowOptions`] and [`Backend`].
+    ///
+    /// However large the window is, its contents are scaled to fit it according to the [`Scaling`] of
+    /// the options.
+    ///
+    /// # Arguments
+    ///
+    /// * `sdl_context` - An [`Sdl`] context object
+    /// * `title` - The title of the window
+    /// * `width` - The width of the window in pixels
+    /// * `height` - The height of the window in pixels
+    /// * `options` - The [`WindowOptions`] of the window
+    /// * `backend` - The SDL renderer [`Backend`] used to draw the window
+    ///
+    /// # Errors
+    ///
+    /// The same as those of [`new`].
+    ///
+    /// [`LcdScreens`]: crate::LcdScreen
+    /// [`Sdl`]: sdl2::Sdl
+    /// [`new`]: crate::LcdWindow::new
+    ///
+    pub fn with_options(
+        sdl_context: &Sdl,
+        title: &str,
+        width: u32,
+        height: u32,
+        options: WindowOptions,
+        backend: Backend,
+    ) -> Result<Self, LcdError> {
+        let video_subsystem = sdl_context.video().map_err(LcdError::Video)?;
+        let window = options.build_window(&video_subsystem, title, width, height)?;
+        let mut canvas = backend.build_canvas(window)?;
+        options.configure_canvas(&mut canvas, width, height)?;
+
+        let window = Self {
+            canvas: Rc::new(RefCell::new(canvas)),
+            background: Color::BLACK,
+        };
+        window.clear();
+        window.present();
+        Ok(window)
+    }
+
+    /// Returns the size of the window in pixels, as given when it was created.
+    pub fn size(&self) -> (u32, u32) {
+        self.canvas.borrow().logical_size()
+    }
+
+    /// Returns the color of the parts of the window not covered by any screen.
+    pub fn background(&self) -> Color {
+        self.background
+    }
+
+    /// Sets the color of the parts of the window not covered by any screen. The new color is drawn the
+    /// next time the window is cleared.
+    pub fn set_background(&mut self, color: Color) {
+        self.background = color;
+    }
+
+    /// Fills the whole window with its background color, covering every screen in it.
+    ///
+    /// After clearing the window, each screen in it should be redrawn with [`LcdScreen::repaint`] before
+    /// the window is presented. This is necessary when the contents of the window have been lost, for
+    /// example because it has been resized.
+    ///
+    /// [`LcdScreen::repaint`]: crate::LcdScreen::repaint
+    pub fn clear(&self) {
+        let mut canvas = self.canvas.borrow_mut();
+        canvas.set_draw_color(self.background);
+        canvas.clear();
+    }
+
+    /// Shows everything drawn to the screens in the window since it was last presented.
+    pub fn present(&self) {
+        self.canvas.borrow_mut().present();
+    }
+
+    /// Returns the underlying SDL window.
+    pub fn window(&self) -> Ref<'_, Window> {
+        Ref::map(self.canvas.borrow(), |canvas| canvas.window())
+    }
+
+    /// Returns the underlying SDL window mutably, for example to show a window created hidden or to
+    /// change its title.
+    pub fn window_mut(&self) -> RefMut<'_, Window> {
+        RefMut::map(self.canvas.borrow_mut(), |canvas| canvas.window_mut())
+    }
+
+    // Checks whether a rect lies entirely inside the window.
+    pub(crate) fn contains(&self, rect: Rect) -> bool {
+        let (width, height) = self.size();
+        Rect::new(0, 0, width, height).contains_rect(rect)
+    }
+}